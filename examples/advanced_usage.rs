@@ -16,6 +16,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         dns_timeout: Duration::from_secs(3),
         enable_cache: true,
         cache_ttl: Duration::from_secs(600), // 10-minute cache
+        ..Default::default()
     };
 
     let detector = MailGuard::with_config(config);
@@ -62,12 +63,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
                     if let Some(threat_type) = &status.threat_type {
                         println!(
-                            "  {} {} -> {} (等级: {}, 类型: {}){}",
+                            "  {} {} -> {} (等级: {}, 类型: {:?}){}",
                             threat_indicator,
                             email,
                             threat_type.description(),
                             threat_type.severity_level(),
-                            format!("{:?}", threat_type),
+                            threat_type,
                             cache_indicator
                         );
                     } else {
@@ -146,14 +147,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut threats_found = 0;
     let mut cache_hits = 0;
-    for result in &results {
-        if let Ok(status) = result {
-            if status.is_threat {
-                threats_found += 1;
-            }
-            if status.from_cache {
-                cache_hits += 1;
-            }
+    for status in results.iter().flatten() {
+        if status.is_threat {
+            threats_found += 1;
+        }
+        if status.from_cache {
+            cache_hits += 1;
         }
     }
 
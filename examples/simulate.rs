@@ -0,0 +1,64 @@
+//! Replay a labeled dataset through a candidate configuration
+//!
+//! Usage: `cargo run --example simulate -- dataset.csv`, where each line of
+//! the dataset is `address,bad` or `address,good`.
+
+use std::{env, fs};
+
+use mailguard_rs::{Confusion, MailGuard, MailGuardConfig, simulate};
+
+fn print_row(name: &str, confusion: &Confusion) {
+    println!(
+        "{name:<16} {:>8} {:>9.3} {:>9.3} {:>9.3}",
+        confusion.total(),
+        confusion.precision(),
+        confusion.recall(),
+        confusion.false_positive_rate()
+    );
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = env::args().nth(1).ok_or("usage: simulate <dataset.csv>")?;
+    let content = fs::read_to_string(path)?;
+
+    let mut samples = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (email, label) = line
+            .rsplit_once(',')
+            .ok_or_else(|| format!("line {}: expected address,label", number + 1))?;
+        let is_bad = match label.trim() {
+            "bad" => true,
+            "good" => false,
+            other => return Err(format!("line {}: unknown label {other}", number + 1).into()),
+        };
+        samples.push((email.trim(), is_bad));
+    }
+
+    // Run every check on every sample so each one gets measured
+    let candidate = MailGuard::try_with_config(MailGuardConfig {
+        enable_cache: false,
+        short_circuit: false,
+        detect_disposable_mx: true,
+        ..Default::default()
+    })?;
+    let report = simulate(&candidate, samples).await;
+
+    println!(
+        "{:<16} {:>8} {:>9} {:>9} {:>9}",
+        "check", "samples", "precision", "recall", "fp rate"
+    );
+    print_row("overall", &report.overall);
+    for (check, confusion) in &report.checks {
+        print_row(check, confusion);
+    }
+
+    for (email, err) in &report.errors {
+        eprintln!("{email}: {err}");
+    }
+    Ok(())
+}
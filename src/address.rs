@@ -0,0 +1,552 @@
+//! RFC 5321/5322 email address parsing
+//!
+//! Parses the `addr-spec` form (`local@domain`) with the length limits of
+//! RFC 5321. The local part is either an RFC 5322 dot-atom or a quoted
+//! string (`"john..doe"@example.com`). Comments (`(...)`) around the local
+//! part and domain are accepted and dropped.
+//!
+//! Internationalized addresses (RFC 6531, e.g. `用户@example.中国`) are
+//! accepted too: the local part may contain any printable non-ASCII
+//! character, and Unicode domains are converted to punycode (see
+//! [`idna`](crate::idna)).
+//!
+//! Rejected addresses carry a [`ValidationDetail`] naming the rule broken
+//! and, where it can be pinned down, the character at fault, so forms can
+//! point at the problem:
+//!
+//! ```rust
+//! use mailguard_rs::{EmailAddress, ValidationRule};
+//!
+//! let err = EmailAddress::parse("john doe@example.com").unwrap_err();
+//! let detail = err.validation_detail().unwrap();
+//! assert_eq!(detail.rule, ValidationRule::InvalidLocalCharacter);
+//! assert_eq!(detail.offset, Some(4));
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{MailGuardError, Result},
+    idna::{domain_to_ascii, domain_to_unicode},
+    validator::EmailValidator,
+};
+
+/// Maximum length of a whole address (RFC 5321 path limit minus `<>`)
+pub const MAX_ADDRESS_LENGTH: usize = 254;
+/// Maximum length of the local part (RFC 5321 section 4.5.3.1.1)
+pub const MAX_LOCAL_PART_LENGTH: usize = 64;
+/// Maximum length of the domain (RFC 5321 section 4.5.3.1.2)
+pub const MAX_DOMAIN_LENGTH: usize = 253;
+/// Maximum length of a single domain label (RFC 1035)
+pub const MAX_LABEL_LENGTH: usize = 63;
+
+/// The part of an address before the `@`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LocalPart(String);
+
+impl LocalPart {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the local part is a quoted string (`"john..doe"`)
+    pub fn is_quoted(&self) -> bool {
+        self.0.starts_with('"')
+    }
+}
+
+impl fmt::Display for LocalPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The part of an address after the `@`, lowercased, in ASCII (punycode) form
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Domain(String);
+
+impl Domain {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The domain with punycode labels decoded, for display
+    pub fn to_unicode(&self) -> String {
+        domain_to_unicode(&self.0)
+    }
+}
+
+impl fmt::Display for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A rule of address syntax
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationRule {
+    /// Parentheses of a comment don't balance
+    UnbalancedComment,
+    /// The address exceeds [`MAX_ADDRESS_LENGTH`]
+    AddressTooLong,
+    /// There is no `@`
+    MissingAt,
+    /// Nothing before the `@`
+    EmptyLocalPart,
+    /// The local part exceeds [`MAX_LOCAL_PART_LENGTH`]
+    LocalPartTooLong,
+    /// A `.` starts or ends the local part, or follows another
+    MisplacedDot,
+    /// A character not allowed in an unquoted local part
+    InvalidLocalCharacter,
+    /// A quoted local part lacks its closing `"`
+    UnterminatedQuote,
+    /// A `\` in a quoted local part escapes nothing printable
+    InvalidEscape,
+    /// A `"` inside a quoted local part is not escaped
+    UnescapedQuote,
+    /// A character not allowed in a quoted local part
+    InvalidQuotedCharacter,
+    /// Nothing after the `@`
+    EmptyDomain,
+    /// The domain is an address literal, e.g. `[127.0.0.1]`
+    DomainLiteral,
+    /// A Unicode domain can't be converted to punycode
+    InternationalDomain,
+    /// The domain exceeds [`MAX_DOMAIN_LENGTH`]
+    DomainTooLong,
+    /// The domain has an empty label, e.g. `example..com`
+    EmptyLabel,
+    /// A domain label exceeds [`MAX_LABEL_LENGTH`]
+    LabelTooLong,
+    /// A `-` starts or ends a domain label
+    MisplacedHyphen,
+    /// A character not allowed in a domain
+    InvalidDomainCharacter,
+    /// A configured [`EmailValidator`] rejected the address
+    Custom,
+}
+
+impl ValidationRule {
+    /// The characters the rule allows, in words, for character rules
+    pub fn allowed_characters(self) -> Option<&'static str> {
+        match self {
+            ValidationRule::InvalidLocalCharacter => {
+                Some("letters, digits, '.' and !#$%&'*+-/=?^_`{|}~")
+            }
+            ValidationRule::InvalidEscape => Some("printable ASCII characters and spaces"),
+            ValidationRule::InvalidQuotedCharacter => Some("printable characters and spaces"),
+            ValidationRule::InvalidDomainCharacter => Some("letters, digits, '-' and '.'"),
+            _ => None,
+        }
+    }
+}
+
+/// Which rule an address broke, and where
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationDetail {
+    pub rule: ValidationRule,
+    /// Offset, in characters, into the input of the character at fault
+    ///
+    /// `None` when no single character is: for length rules, and for
+    /// addresses with comments or Unicode domains, whose checked form
+    /// differs from the input.
+    pub offset: Option<usize>,
+    /// The characters allowed where `offset` points, for character rules
+    pub allowed: Option<String>,
+}
+
+impl ValidationDetail {
+    pub fn new(rule: ValidationRule, offset: Option<usize>) -> Self {
+        Self {
+            rule,
+            offset,
+            allowed: rule.allowed_characters().map(str::to_string),
+        }
+    }
+}
+
+/// A broken rule with its reason and offset into the checked part
+struct Violation {
+    rule: ValidationRule,
+    reason: &'static str,
+    offset: Option<usize>,
+}
+
+impl Violation {
+    fn new(rule: ValidationRule, reason: &'static str) -> Self {
+        Self {
+            rule,
+            reason,
+            offset: None,
+        }
+    }
+
+    fn at(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Move the offset from the checked part to the input, or drop it
+    /// when the two don't line up
+    fn shifted(mut self, base: Option<usize>) -> Self {
+        self.offset = base.zip(self.offset).map(|(base, offset)| base + offset);
+        self
+    }
+
+    fn into_error(self, input: &str) -> MailGuardError {
+        MailGuardError::invalid_email_at(
+            input,
+            self.reason,
+            ValidationDetail::new(self.rule, self.offset),
+        )
+    }
+}
+
+/// A parsed email address
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EmailAddress {
+    pub local_part: LocalPart,
+    pub domain: Domain,
+}
+
+impl EmailAddress {
+    /// Parse an address, rejecting anything that isn't a valid `addr-spec`
+    pub fn parse(input: &str) -> Result<Self> {
+        let invalid = |violation: Violation| violation.into_error(input);
+
+        let trimmed = input.trim();
+        let address = strip_comments(trimmed).ok_or_else(|| {
+            invalid(Violation::new(
+                ValidationRule::UnbalancedComment,
+                "Unbalanced comment",
+            ))
+        })?;
+
+        if address.len() > MAX_ADDRESS_LENGTH {
+            return Err(invalid(Violation::new(
+                ValidationRule::AddressTooLong,
+                "Address exceeds 254 characters",
+            )));
+        }
+
+        let (local, domain) = address
+            .rsplit_once('@')
+            .ok_or_else(|| invalid(Violation::new(ValidationRule::MissingAt, "Missing '@'")))?;
+
+        // Offsets only map back to the input if no comment was dropped
+        let local_base = (address == trimmed).then(|| leading_whitespace(input));
+        let domain_base = local_base.map(|base| base + local.chars().count() + 1);
+
+        validate_local_part(local).map_err(|v| invalid(v.shifted(local_base)))?;
+        let domain = validate_domain(domain).map_err(|v| invalid(v.shifted(domain_base)))?;
+
+        Ok(Self {
+            local_part: LocalPart(local.to_string()),
+            domain: Domain(domain),
+        })
+    }
+
+    /// Parse an address accepted by a custom validator
+    ///
+    /// The validator replaces the local part, comment and length rules of
+    /// [`EmailAddress::parse`]; the domain is still validated.
+    pub fn parse_with(input: &str, validator: &EmailValidator) -> Result<Self> {
+        let invalid = |violation: Violation| violation.into_error(input);
+
+        let address = input.trim();
+        validator.validate(address)?;
+
+        let (local, domain) = address
+            .rsplit_once('@')
+            .ok_or_else(|| invalid(Violation::new(ValidationRule::MissingAt, "Missing '@'")))?;
+        let base = leading_whitespace(input);
+        if local.is_empty() {
+            return Err(invalid(
+                Violation::new(ValidationRule::EmptyLocalPart, "Empty local part").at(base),
+            ));
+        }
+        let domain_base = base + local.chars().count() + 1;
+        let domain = validate_domain(domain).map_err(|v| invalid(v.shifted(Some(domain_base))))?;
+
+        Ok(Self {
+            local_part: LocalPart(local.to_string()),
+            domain: Domain(domain),
+        })
+    }
+
+    /// The address with the domain in Unicode form, for display
+    pub fn to_unicode(&self) -> String {
+        format!("{}@{}", self.local_part, self.domain.to_unicode())
+    }
+
+    /// Whether the address needs SMTPUTF8 to be delivered (RFC 6531)
+    ///
+    /// True for non-ASCII local parts; Unicode domains alone don't need it,
+    /// since they travel as punycode.
+    pub fn requires_smtputf8(&self) -> bool {
+        !self.local_part.as_str().is_ascii()
+    }
+}
+
+impl fmt::Display for EmailAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.local_part, self.domain)
+    }
+}
+
+impl std::str::FromStr for EmailAddress {
+    type Err = MailGuardError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+/// Characters allowed in a dot-atom besides ASCII alphanumerics (RFC 5322 `atext`)
+const ATEXT_SPECIALS: &str = "!#$%&'*+-/=?^_`{|}~";
+
+/// RFC 5322 `atext`, extended with `UTF8-non-ascii` by RFC 6531
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || ATEXT_SPECIALS.contains(c) || is_utf8_non_ascii(c)
+}
+
+fn is_utf8_non_ascii(c: char) -> bool {
+    !c.is_ascii() && !c.is_control() && !c.is_whitespace()
+}
+
+/// Characters of `input` dropped by trimming its start
+fn leading_whitespace(input: &str) -> usize {
+    input.chars().take_while(|c| c.is_whitespace()).count()
+}
+
+/// Character offset of the byte offset `index` into `s`
+fn char_offset(s: &str, index: usize) -> usize {
+    s[..index].chars().count()
+}
+
+fn validate_local_part(local: &str) -> std::result::Result<(), Violation> {
+    if local.is_empty() {
+        return Err(Violation::new(ValidationRule::EmptyLocalPart, "Empty local part").at(0));
+    }
+    if local.len() > MAX_LOCAL_PART_LENGTH {
+        return Err(Violation::new(
+            ValidationRule::LocalPartTooLong,
+            "Local part exceeds 64 characters",
+        ));
+    }
+    if local.starts_with('"') {
+        return validate_quoted_string(local);
+    }
+    let misplaced_dot = if local.starts_with('.') {
+        Some(0)
+    } else if let Some(index) = local.find("..") {
+        Some(char_offset(local, index) + 1)
+    } else if local.ends_with('.') {
+        Some(local.chars().count() - 1)
+    } else {
+        None
+    };
+    if let Some(offset) = misplaced_dot {
+        return Err(
+            Violation::new(ValidationRule::MisplacedDot, "Misplaced '.' in local part").at(offset),
+        );
+    }
+    if let Some(offset) = local.chars().position(|c| c != '.' && !is_atext(c)) {
+        return Err(Violation::new(
+            ValidationRule::InvalidLocalCharacter,
+            "Invalid character in local part",
+        )
+        .at(offset));
+    }
+    Ok(())
+}
+
+/// Validate an RFC 5322 `quoted-string` local part, quotes included
+fn validate_quoted_string(local: &str) -> std::result::Result<(), Violation> {
+    let inner = local
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .ok_or_else(|| {
+            Violation::new(
+                ValidationRule::UnterminatedQuote,
+                "Unterminated quoted local part",
+            )
+        })?;
+
+    // Offsets count the opening quote
+    let mut chars = inner.chars().enumerate().map(|(i, c)| (i + 1, c));
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            // quoted-pair: backslash followed by a visible character or space
+            '\\' => match chars.next() {
+                Some((_, escaped)) if escaped == ' ' || escaped.is_ascii_graphic() => {}
+                _ => {
+                    return Err(Violation::new(
+                        ValidationRule::InvalidEscape,
+                        "Invalid escape in quoted local part",
+                    )
+                    .at(offset));
+                }
+            },
+            '"' => {
+                return Err(Violation::new(
+                    ValidationRule::UnescapedQuote,
+                    "Unescaped '\"' in quoted local part",
+                )
+                .at(offset));
+            }
+            // qtext plus the space allowed by folding whitespace
+            c if c == ' ' || c.is_ascii_graphic() || is_utf8_non_ascii(c) => {}
+            _ => {
+                return Err(Violation::new(
+                    ValidationRule::InvalidQuotedCharacter,
+                    "Invalid character in quoted local part",
+                )
+                .at(offset));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate a domain, returning its lowercased ASCII form
+///
+/// Offsets are into `domain`, and dropped for Unicode domains, whose ASCII
+/// form is what gets checked.
+fn validate_domain(domain: &str) -> std::result::Result<String, Violation> {
+    if domain.is_empty() {
+        return Err(Violation::new(ValidationRule::EmptyDomain, "Empty domain").at(0));
+    }
+    if domain.starts_with('[') {
+        return Err(Violation::new(
+            ValidationRule::DomainLiteral,
+            "Domain literals are not supported",
+        )
+        .at(0));
+    }
+    let is_ascii = domain.is_ascii();
+    let domain = domain_to_ascii(domain)
+        .map_err(|reason| Violation::new(ValidationRule::InternationalDomain, reason))?;
+    if domain.len() > MAX_DOMAIN_LENGTH {
+        return Err(Violation::new(
+            ValidationRule::DomainTooLong,
+            "Domain exceeds 253 characters",
+        ));
+    }
+
+    let check_labels = || {
+        let mut start = 0;
+        for label in domain.split('.') {
+            let at = |violation: Violation, offset: usize| violation.at(start + offset);
+            if label.is_empty() {
+                return Err(at(
+                    Violation::new(ValidationRule::EmptyLabel, "Empty domain label"),
+                    0,
+                ));
+            }
+            if label.len() > MAX_LABEL_LENGTH {
+                return Err(at(
+                    Violation::new(
+                        ValidationRule::LabelTooLong,
+                        "Domain label exceeds 63 characters",
+                    ),
+                    0,
+                ));
+            }
+            if label.starts_with('-') || label.ends_with('-') {
+                let offset = if label.starts_with('-') {
+                    0
+                } else {
+                    label.len() - 1
+                };
+                return Err(at(
+                    Violation::new(
+                        ValidationRule::MisplacedHyphen,
+                        "Domain label starts or ends with '-'",
+                    ),
+                    offset,
+                ));
+            }
+            if let Some(offset) = label
+                .chars()
+                .position(|c| !c.is_ascii_alphanumeric() && c != '-')
+            {
+                return Err(at(
+                    Violation::new(
+                        ValidationRule::InvalidDomainCharacter,
+                        "Invalid character in domain",
+                    ),
+                    offset,
+                ));
+            }
+            start += label.len() + 1;
+        }
+        Ok(())
+    };
+    check_labels().map_err(|violation| {
+        if is_ascii {
+            violation
+        } else {
+            violation.shifted(None)
+        }
+    })?;
+    Ok(domain)
+}
+
+/// Remove `(comments)` (nesting allowed) and the whitespace around them
+///
+/// Parentheses inside a quoted local part are kept. Returns `None` if
+/// parentheses are unbalanced.
+fn strip_comments(input: &str) -> Option<String> {
+    if !input.contains(['(', ')']) {
+        return Some(input.to_string());
+    }
+
+    let mut output = String::with_capacity(input.len());
+    let mut depth = 0usize;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for c in input.chars() {
+        if escaped {
+            escaped = false;
+            if depth == 0 {
+                output.push(c);
+            }
+            continue;
+        }
+
+        match c {
+            '\\' if depth > 0 || in_quotes => {
+                escaped = true;
+                if depth == 0 {
+                    output.push(c);
+                }
+            }
+            '"' if depth == 0 => {
+                in_quotes = !in_quotes;
+                output.push(c);
+            }
+            _ if in_quotes => output.push(c),
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            ')' => return None,
+            _ if depth > 0 => {}
+            _ => output.push(c),
+        }
+    }
+
+    if depth > 0 {
+        return None;
+    }
+
+    // Folding whitespace next to a comment is not part of the address
+    Some(match output.rsplit_once('@') {
+        Some((local, domain)) => format!("{}@{}", local.trim(), domain.trim()),
+        None => output.trim().to_string(),
+    })
+}
@@ -0,0 +1,112 @@
+//! Parsing of pasted address lists
+//!
+//! Accepts the kind of blob users paste into "invite your team" fields:
+//! addresses separated by commas, semicolons or newlines, optionally with
+//! display names (`"Doe, Jane" <jane@example.com>`).
+
+use serde::{Deserialize, Serialize};
+
+/// An item of the blob that could not be turned into an address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressListError {
+    /// Position of the item in the blob (0-based, blank items not counted)
+    pub index: usize,
+    /// The raw item as it appeared in the blob
+    pub input: String,
+    /// Why the item was rejected
+    pub reason: String,
+}
+
+/// Result of parsing an address list
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParsedAddressList {
+    /// Cleaned addresses in input order
+    pub addresses: Vec<String>,
+    /// Items that could not be parsed
+    pub errors: Vec<AddressListError>,
+}
+
+impl ParsedAddressList {
+    /// Borrow the addresses, e.g. for [`MailGuard::check_emails_batch`]
+    ///
+    /// [`MailGuard::check_emails_batch`]: crate::MailGuard::check_emails_batch
+    pub fn as_strs(&self) -> Vec<&str> {
+        self.addresses.iter().map(String::as_str).collect()
+    }
+}
+
+/// Split a blob of addresses into cleaned candidates and per-item errors
+///
+/// Separators inside double quotes or angle brackets are ignored, so
+/// display names may contain commas. Only the shape of each address is
+/// checked here; full validation happens when the addresses are checked.
+pub fn parse_address_list(blob: &str) -> ParsedAddressList {
+    let mut parsed = ParsedAddressList::default();
+
+    for (index, item) in split_items(blob).into_iter().enumerate() {
+        match clean_item(item) {
+            Ok(address) => parsed.addresses.push(address),
+            Err(reason) => parsed.errors.push(AddressListError {
+                index,
+                input: item.to_string(),
+                reason: reason.to_string(),
+            }),
+        }
+    }
+
+    parsed
+}
+
+/// Split on `,`, `;` and newlines outside quotes and angle brackets
+fn split_items(blob: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut in_quotes = false;
+    let mut in_brackets = false;
+    let mut start = 0;
+
+    for (pos, c) in blob.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '<' if !in_quotes => in_brackets = true,
+            '>' if !in_quotes => in_brackets = false,
+            ',' | ';' | '\n' | '\r' if !in_quotes && !in_brackets => {
+                items.push(&blob[start..pos]);
+                start = pos + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    items.push(&blob[start..]);
+
+    items
+        .into_iter()
+        .filter(|item| !item.trim().is_empty())
+        .collect()
+}
+
+/// Extract the bare address from one item
+fn clean_item(item: &str) -> Result<String, &'static str> {
+    let item = item.trim();
+
+    let address = match (item.rfind('<'), item.rfind('>')) {
+        (Some(open), Some(close)) if open < close => &item[open + 1..close],
+        (None, None) => item,
+        _ => return Err("Unbalanced angle brackets"),
+    };
+
+    let address = address.trim();
+    let address = address
+        .strip_prefix("mailto:")
+        .unwrap_or(address)
+        .trim_matches(|c| c == '\'' || c == '`');
+
+    if address.chars().any(char::is_whitespace) {
+        return Err("Address contains whitespace");
+    }
+
+    match address.rsplit_once('@') {
+        Some((local, domain)) if !local.is_empty() && !domain.is_empty() => Ok(address.to_string()),
+        Some(_) => Err("Missing local part or domain"),
+        None => Err("Missing '@'"),
+    }
+}
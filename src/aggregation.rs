@@ -0,0 +1,100 @@
+//! Combining threats reported by several providers
+//!
+//! With [`MailGuardConfig::short_circuit`](crate::MailGuardConfig::short_circuit)
+//! off, several lists can flag the same domain, and they don't always agree
+//! on the category: SURBL may call a domain spam while another provider
+//! calls it phishing. By default the check that decides sets `threat_type`;
+//! a [`ThreatAggregation`] picks it from everything reported instead, and
+//! trims [`DomainStatus::threats`](crate::DomainStatus::threats) to match.
+//!
+//! ```rust
+//! use mailguard_rs::{MailGuardConfig, ThreatAggregation, check};
+//!
+//! // Trust SURBL's category over any other provider's
+//! let config = MailGuardConfig {
+//!     short_circuit: false,
+//!     threat_aggregation: ThreatAggregation::Precedence(vec![check::SURBL.to_string()]),
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! Aggregation only picks the category of a domain that is already a
+//! threat; it never turns a clean or allowlisted domain into one, and
+//! untyped threats such as blocklist hits stay untyped.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{check::CheckOutcome, threat::ThreatInfo};
+
+/// How the category of a threat is chosen when several checks report one
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreatAggregation {
+    /// The check that decides sets `threat_type`; `threats` lists every
+    /// threat reported
+    #[default]
+    Decider,
+    /// The most severe category reported sets `threat_type`; `threats`
+    /// keeps only the threats of that category
+    MaxSeverity,
+    /// The most severe category reported sets `threat_type`; `threats`
+    /// keeps the first threat of each category reported
+    Union,
+    /// The first of these checks, by [`Check::name`](crate::Check::name),
+    /// to report a category sets `threat_type`
+    ///
+    /// Checks not listed come after them, most severe category first.
+    /// `threats` lists every threat reported.
+    Precedence(Vec<String>),
+}
+
+impl ThreatAggregation {
+    /// Check names listed for precedence
+    pub(crate) fn named_checks(&self) -> &[String] {
+        match self {
+            ThreatAggregation::Precedence(checks) => checks,
+            _ => &[],
+        }
+    }
+
+    /// Fold every reported threat, sorted most severe first, into the
+    /// deciding outcome
+    pub(crate) fn combine(
+        &self,
+        outcome: CheckOutcome,
+        threats: &mut Vec<ThreatInfo>,
+    ) -> CheckOutcome {
+        let CheckOutcome::Threat(Some(decided)) = outcome else {
+            return outcome;
+        };
+        let kind = match self {
+            ThreatAggregation::Decider => decided,
+            ThreatAggregation::MaxSeverity => {
+                let kind = threats
+                    .first()
+                    .map_or(decided, |threat| threat.kind.clone());
+                threats.retain(|threat| threat.kind == kind);
+                kind
+            }
+            ThreatAggregation::Union => {
+                let mut seen = HashSet::new();
+                threats.retain(|threat| seen.insert(threat.kind.clone()));
+                threats
+                    .first()
+                    .map_or(decided, |threat| threat.kind.clone())
+            }
+            ThreatAggregation::Precedence(checks) => threats
+                .iter()
+                .min_by_key(|threat| {
+                    checks
+                        .iter()
+                        .position(|check| *check == threat.source_list)
+                        .unwrap_or(checks.len())
+                })
+                .map_or(decided, |threat| threat.kind.clone()),
+        };
+        CheckOutcome::Threat(Some(kind))
+    }
+}
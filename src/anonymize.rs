@@ -0,0 +1,124 @@
+//! Anonymization of results for sharing
+//!
+//! Turns [`EmailStatus`] results into records that keep the domain and the
+//! verdict but not the mailbox, so a dataset can go to a vendor or
+//! researcher without leaking user addresses. Local parts are either
+//! dropped or replaced by a keyed hash, which still lets the recipient count
+//! repeat addresses without being able to reverse them.
+
+use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::{
+    check::CheckResult,
+    detector::EmailStatus,
+    error::Result,
+    threat::{ThreatInfo, ThreatType},
+};
+
+/// An [`EmailStatus`] without the mailbox
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnonymizedStatus {
+    /// HMAC-SHA256 of the normalized local part, hex-encoded; `None` when
+    /// local parts are stripped
+    pub local_part_hash: Option<String>,
+    /// Domain
+    pub domain: String,
+    /// Whether it's a temporary email or malicious domain
+    pub is_threat: bool,
+    /// Threat type (if exists)
+    pub threat_type: Option<ThreatType>,
+    /// Every threat reported for the domain, most severe first
+    pub threats: Vec<ThreatInfo>,
+    /// How much the verdict can be trusted, from 0 to 1
+    pub confidence: f32,
+    /// Major provider the domain likely mistypes or imitates
+    pub typo_of: Option<String>,
+    /// Outcomes of the checks that ran, in order
+    pub check_results: Vec<CheckResult>,
+}
+
+/// How local parts are treated
+#[derive(Clone)]
+enum LocalParts {
+    Strip,
+    Hash(Vec<u8>),
+}
+
+/// Strips or hashes the personal parts of results
+#[derive(Clone)]
+pub struct Anonymizer {
+    local_parts: LocalParts,
+}
+
+impl Anonymizer {
+    /// Drop local parts entirely
+    pub fn strip() -> Self {
+        Self {
+            local_parts: LocalParts::Strip,
+        }
+    }
+
+    /// Replace local parts by their HMAC-SHA256 under `key`
+    ///
+    /// Keep the key secret: anyone holding it can confirm guesses of an
+    /// address. Use the same key across exports to keep hashes comparable.
+    pub fn hashed(key: impl AsRef<[u8]>) -> Self {
+        Self {
+            local_parts: LocalParts::Hash(key.as_ref().to_vec()),
+        }
+    }
+
+    /// Anonymize one result
+    ///
+    /// The local part is normalized first, so aliases of one mailbox
+    /// (`j.doe+news@gmail.com`, `jdoe@gmail.com`) hash alike. Subaddress tags,
+    /// the raw input and cache provenance are dropped.
+    pub fn anonymize(&self, status: &EmailStatus) -> AnonymizedStatus {
+        let local_part_hash = match &self.local_parts {
+            LocalParts::Strip => None,
+            LocalParts::Hash(key) => {
+                let normalized = &status.normalized_email;
+                let local_part = normalized
+                    .rsplit_once('@')
+                    .map_or(normalized.as_str(), |(local, _)| local);
+                Some(keyed_hash(key, local_part))
+            }
+        };
+
+        AnonymizedStatus {
+            local_part_hash,
+            domain: status.domain.clone(),
+            is_threat: status.is_threat,
+            threat_type: status.threat_type.clone(),
+            threats: status.threats.clone(),
+            confidence: status.confidence,
+            typo_of: status.typo_of.clone(),
+            check_results: status.check_results.clone(),
+        }
+    }
+
+    /// Anonymize the successful results of a batch
+    ///
+    /// Errors are dropped, since their messages quote the input address.
+    pub fn anonymize_batch(&self, results: &[Result<EmailStatus>]) -> Vec<AnonymizedStatus> {
+        results
+            .iter()
+            .flatten()
+            .map(|status| self.anonymize(status))
+            .collect()
+    }
+}
+
+/// Hex-encoded HMAC-SHA256 of `value`
+fn keyed_hash(key: &[u8], value: &str) -> String {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap_or_else(|_| unreachable!());
+    mac.update(value.as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
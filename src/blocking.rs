@@ -0,0 +1,282 @@
+//! Blocking (synchronous) API
+//!
+//! Wraps the async [`crate::MailGuard`] in an internal Tokio runtime, for
+//! CLI tools and codebases that don't use async.
+//!
+//! These functions must not be called from within an async runtime; use the
+//! async API there instead.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use mailguard_rs::blocking;
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let status = blocking::check_email("test@tempmail.com")?;
+//!     println!("Email status: {:?}", status);
+//!     Ok(())
+//! }
+//! ```
+
+use std::{path::Path, sync::OnceLock};
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+    detector::{DomainStatus, EmailStatus, MailGuardConfig},
+    dns::DnsClient,
+    error::{MailGuardError, Result},
+    scan::ScanMatch,
+    summary::BatchSummary,
+    threat::ThreatType,
+};
+
+/// Detector behind [`MailGuard::global`]
+static GLOBAL: OnceLock<MailGuard> = OnceLock::new();
+
+/// Blocking email detector
+pub struct MailGuard {
+    inner: crate::MailGuard,
+    runtime: Runtime,
+}
+
+impl MailGuard {
+    /// Create a new blocking detector with the default configuration
+    pub fn new() -> Result<Self> {
+        Self::with_config(MailGuardConfig::default())
+    }
+
+    /// The process-wide blocking detector used by [`check_email`] and the
+    /// other free functions
+    ///
+    /// Created with the default configuration on first use unless
+    /// [`MailGuard::set_global`] installed one before. Fails only if its
+    /// runtime can't be started.
+    pub fn global() -> Result<&'static MailGuard> {
+        if let Some(detector) = GLOBAL.get() {
+            return Ok(detector);
+        }
+        // Another thread may win the race; its detector is used then
+        let _ = GLOBAL.set(Self::new()?);
+        Ok(GLOBAL.get().expect("global detector was just set"))
+    }
+
+    /// Install the process-wide blocking detector
+    ///
+    /// Fails with [`MailGuardError::InvalidConfig`] if the global detector
+    /// was already installed or used.
+    pub fn set_global(detector: MailGuard) -> Result<()> {
+        GLOBAL.set(detector).map_err(|_| {
+            MailGuardError::InvalidConfig("the global detector is already initialized".to_string())
+        })
+    }
+
+    /// Create a blocking detector with a custom configuration
+    ///
+    /// Fails if the configuration is invalid (see [`MailGuardConfig::validate`]).
+    pub fn with_config(config: MailGuardConfig) -> Result<Self> {
+        config.validate()?;
+        let runtime = new_runtime()?;
+        let inner = {
+            let _guard = runtime.enter();
+            crate::MailGuard::with_config(config)
+        };
+
+        Ok(Self { inner, runtime })
+    }
+
+    /// Create a blocking detector that queries through the given DNS client
+    ///
+    /// Fails if the configuration is invalid (see [`MailGuardConfig::validate`]).
+    pub fn with_dns_client(config: MailGuardConfig, dns_client: DnsClient) -> Result<Self> {
+        config.validate()?;
+        let runtime = new_runtime()?;
+        let inner = {
+            let _guard = runtime.enter();
+            crate::MailGuard::with_dns_client(config, dns_client)
+        };
+
+        Ok(Self { inner, runtime })
+    }
+
+    /// Check a single email address
+    pub fn check_email(&self, email: &str) -> Result<EmailStatus> {
+        self.runtime.block_on(self.inner.check_email(email))
+    }
+
+    /// Check a domain
+    pub fn check_domain(&self, domain: &str) -> Result<DomainStatus> {
+        self.runtime.block_on(self.inner.check_domain(domain))
+    }
+
+    /// Batch check emails
+    pub fn check_emails_batch(&self, emails: &[&str]) -> Vec<Result<EmailStatus>> {
+        self.runtime.block_on(self.inner.check_emails_batch(emails))
+    }
+
+    /// Batch check domains
+    pub fn check_domains_batch(&self, domains: &[&str]) -> Vec<Result<DomainStatus>> {
+        self.runtime
+            .block_on(self.inner.check_domains_batch(domains))
+    }
+
+    /// Find every address in a text and check it
+    pub fn scan_text(&self, text: &str) -> Vec<ScanMatch> {
+        self.runtime.block_on(self.inner.scan_text(text))
+    }
+
+    /// Check addresses from any iterator, pairing each result with its input
+    pub fn check_emails<I, S>(&self, emails: I) -> Vec<(S, Result<EmailStatus>)>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.runtime.block_on(self.inner.check_emails(emails))
+    }
+
+    /// Check domains from any iterator, pairing each result with its input
+    pub fn check_domains<I, S>(&self, domains: I) -> Vec<(S, Result<DomainStatus>)>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.runtime.block_on(self.inner.check_domains(domains))
+    }
+
+    /// Check addresses, also returning a [`BatchSummary`] of the results
+    pub fn check_emails_summarized<I, S>(
+        &self,
+        emails: I,
+    ) -> (Vec<(S, Result<EmailStatus>)>, BatchSummary)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.runtime
+            .block_on(self.inner.check_emails_summarized(emails))
+    }
+
+    /// Check domains, also returning a [`BatchSummary`] of the results
+    pub fn check_domains_summarized<I, S>(
+        &self,
+        domains: I,
+    ) -> (Vec<(S, Result<DomainStatus>)>, BatchSummary)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.runtime
+            .block_on(self.inner.check_domains_summarized(domains))
+    }
+
+    /// Remove expired cache entries
+    pub fn cleanup_cache(&self) {
+        self.inner.cleanup_cache();
+    }
+
+    /// Get cache statistics
+    pub fn cache_stats(&self) -> Option<usize> {
+        self.inner.cache_stats()
+    }
+
+    /// Number of entries in the email-level cache, if it is enabled
+    pub fn email_cache_stats(&self) -> Option<usize> {
+        self.inner.email_cache_stats()
+    }
+
+    /// Clear the cache
+    pub fn clear_cache(&self) {
+        self.inner.clear_cache();
+    }
+
+    /// See [`crate::MailGuard::invalidate`]
+    pub fn invalidate(&self, domain: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.invalidate(domain))
+    }
+
+    /// See [`crate::MailGuard::preload`]
+    pub fn preload<I, S>(&self, domains: I) -> usize
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.runtime.block_on(self.inner.preload(domains))
+    }
+
+    /// See [`crate::MailGuard::preload_verdicts`]
+    pub fn preload_verdicts<I, S>(&self, verdicts: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = (S, Option<ThreatType>)>,
+        S: AsRef<str>,
+    {
+        self.runtime.block_on(self.inner.preload_verdicts(verdicts))
+    }
+
+    /// See [`crate::MailGuard::export_cache_json`]
+    pub fn export_cache_json(&self) -> String {
+        self.inner.export_cache_json()
+    }
+
+    /// See [`crate::MailGuard::import_cache_json`]
+    pub fn import_cache_json(&self, json: &str) -> Result<usize> {
+        self.runtime.block_on(self.inner.import_cache_json(json))
+    }
+
+    /// See [`crate::MailGuard::save_cache`]
+    pub fn save_cache(&self, path: impl AsRef<Path>) -> Result<usize> {
+        self.inner.save_cache(path)
+    }
+
+    /// See [`crate::MailGuard::load_cache`]
+    pub fn load_cache(&self, path: impl AsRef<Path>) -> Result<usize> {
+        self.runtime.block_on(self.inner.load_cache(path))
+    }
+
+    /// See [`crate::MailGuard::reload`]
+    pub fn reload(&self, config: MailGuardConfig) -> Result<()> {
+        self.inner.reload(config)
+    }
+
+    /// See [`crate::MailGuard::add_allowlist_rule`]
+    pub fn add_allowlist_rule(&self, entry: &str) -> Result<bool> {
+        self.inner.add_allowlist_rule(entry)
+    }
+
+    /// See [`crate::MailGuard::remove_allowlist_rule`]
+    pub fn remove_allowlist_rule(&self, entry: &str) -> bool {
+        self.inner.remove_allowlist_rule(entry)
+    }
+
+    /// See [`crate::MailGuard::add_blocklist_rule`]
+    pub fn add_blocklist_rule(&self, entry: &str) -> Result<bool> {
+        self.inner.add_blocklist_rule(entry)
+    }
+
+    /// See [`crate::MailGuard::remove_blocklist_rule`]
+    pub fn remove_blocklist_rule(&self, entry: &str) -> bool {
+        self.inner.remove_blocklist_rule(entry)
+    }
+}
+
+/// Check a single email address with the [global](MailGuard::global) detector
+pub fn check_email(email: &str) -> Result<EmailStatus> {
+    MailGuard::global()?.check_email(email)
+}
+
+/// Check domain with the [global](MailGuard::global) detector
+pub fn check_domain(domain: &str) -> Result<DomainStatus> {
+    MailGuard::global()?.check_domain(domain)
+}
+
+/// Batch check emails with the [global](MailGuard::global) detector
+pub fn check_emails_batch(emails: &[&str]) -> Result<Vec<Result<EmailStatus>>> {
+    Ok(MailGuard::global()?.check_emails_batch(emails))
+}
+
+/// Single-threaded runtime driving the async detector
+fn new_runtime() -> Result<Runtime> {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(MailGuardError::Runtime)
+}
@@ -1,101 +1,1104 @@
 #[cfg(feature = "cache")]
-use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+#[cfg(not(feature = "cache"))]
+use std::marker::PhantomData;
+#[cfg(feature = "cache")]
+use std::num::NonZeroUsize;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    future::Future,
+    io,
+    net::IpAddr,
+    path::Path,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use async_trait::async_trait;
 #[cfg(feature = "cache")]
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use lru::LruCache;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+use crate::{
+    detector::EmailStatus,
+    error::Result,
+    threat::{ThreatInfo, ThreatType},
+};
+
+/// How far the wall clock may appear to run backwards before an entry is
+/// no longer trusted
+pub const CLOCK_SKEW_TOLERANCE: Duration = Duration::from_secs(60);
+
+/// Poisoned cache locks recovered so far
+static POISON_RECOVERIES: AtomicU64 = AtomicU64::new(0);
+
+/// How many times a cache lock was found poisoned by a panic and recovered
+/// since the process started
+///
+/// Caches keep serving after a panic while a lock was held, so this is the
+/// only trace of one besides a warning in the logs; export it to alert on.
+pub fn poison_recoveries() -> u64 {
+    POISON_RECOVERIES.load(Ordering::Relaxed)
+}
+
+/// Lock `mutex`, recovering it if a panic poisoned it
+///
+/// The poison flag is cleared, so each panic is counted and logged once.
+pub(crate) fn lock_recovering<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        POISON_RECOVERIES.fetch_add(1, Ordering::Relaxed);
+        tracing::warn!("Recovered a cache lock poisoned by a panic");
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+/// Per-key locks, so only one task at a time computes a missing entry
+///
+/// A key's lock lives only while someone holds or waits for it.
+#[derive(Debug, Default)]
+pub(crate) struct KeyLocks {
+    locks: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+}
+
+impl KeyLocks {
+    /// Wait until no other task holds `key`, and hold it until the guard
+    /// is dropped
+    pub(crate) async fn lock(&self, key: &str) -> KeyGuard<'_> {
+        let lock = lock_recovering(&self.locks)
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+        KeyGuard {
+            guard: Some(lock.lock_owned().await),
+            locks: self,
+            key: key.to_string(),
+        }
+    }
+
+    /// Get `key` with `get`, or compute it with `init` if it is missing
+    ///
+    /// Callers missing the same key at once wait for the first one's
+    /// `init`, then look again, so `init` runs at most once per key as
+    /// long as it stores what it computes. Backs
+    /// [`TtlCache::get_or_try_insert_with`] and the detector's lookups
+    /// through its cache backend.
+    pub(crate) async fn get_or_try_insert_with<T, E, G, GetFut, F, InitFut>(
+        &self,
+        key: &str,
+        get: G,
+        init: F,
+    ) -> std::result::Result<T, E>
+    where
+        G: Fn() -> GetFut,
+        GetFut: Future<Output = Option<T>>,
+        F: FnOnce() -> InitFut,
+        InitFut: Future<Output = std::result::Result<T, E>>,
+    {
+        if let Some(value) = get().await {
+            return Ok(value);
+        }
+        let _guard = self.lock(key).await;
+        // Stored by whoever held the key while we waited
+        if let Some(value) = get().await {
+            return Ok(value);
+        }
+        init().await
+    }
+}
 
-use crate::threat::ThreatType;
+/// A held [`KeyLocks`] key
+pub(crate) struct KeyGuard<'a> {
+    guard: Option<OwnedMutexGuard<()>>,
+    locks: &'a KeyLocks,
+    key: String,
+}
+
+impl Drop for KeyGuard<'_> {
+    fn drop(&mut self) {
+        self.guard.take();
+        let mut locks = lock_recovering(&self.locks.locks);
+        // Only the map refers to the lock: nobody else holds or waits for it
+        if locks
+            .get(&self.key)
+            .is_some_and(|lock| Arc::strong_count(lock) == 1)
+        {
+            locks.remove(&self.key);
+        }
+    }
+}
 
 /// 缓存条目
+///
+/// The domain cache stores [`CachedVerdict`]s; the email cache stores whole
+/// [`EmailStatus`] values. Entries carry both a monotonic and a wall-clock
+/// creation time. The monotonic clock is immune to clock adjustments but has
+/// no meaning across restarts and may stop while the machine is suspended;
+/// the wall clock covers both. An entry expires as soon as either clock says
+/// so.
 #[derive(Debug, Clone)]
-pub struct CacheEntry {
-    pub threat_type: Option<ThreatType>,
+pub struct CacheEntry<V = CachedVerdict> {
+    pub value: V,
+    /// Monotonic creation time
     pub timestamp: Instant,
+    /// Wall-clock creation time, the one to persist
+    pub created_at: SystemTime,
     pub ttl: Duration,
 }
 
-impl CacheEntry {
-    pub fn new(threat_type: Option<ThreatType>, ttl: Duration) -> Self {
+impl<V> CacheEntry<V> {
+    pub fn new(value: V, ttl: Duration) -> Self {
         Self {
-            threat_type,
+            value,
             timestamp: Instant::now(),
+            created_at: SystemTime::now(),
+            ttl,
+        }
+    }
+
+    /// Rebuild an entry from its persisted wall-clock creation time
+    ///
+    /// The monotonic timestamp is back-dated by the entry's wall-clock age,
+    /// so an entry restored after a restart keeps its remaining lifetime.
+    pub fn restore(value: V, created_at: SystemTime, ttl: Duration) -> Self {
+        let age = SystemTime::now()
+            .duration_since(created_at)
+            .unwrap_or_default();
+        let now = Instant::now();
+
+        Self {
+            value,
+            timestamp: now.checked_sub(age).unwrap_or(now),
+            created_at,
             ttl,
         }
     }
 
     pub fn is_expired(&self) -> bool {
-        self.timestamp.elapsed() > self.ttl
+        self.is_expired_at(Instant::now(), SystemTime::now())
+    }
+
+    /// Whether the entry has expired at the given monotonic and wall-clock
+    /// times
+    ///
+    /// A wall clock behind the creation time by more than
+    /// [`CLOCK_SKEW_TOLERANCE`] (a clock reset, or an entry written on a host
+    /// with a skewed clock) also counts as expired.
+    pub fn is_expired_at(&self, now: Instant, wall_now: SystemTime) -> bool {
+        if now.saturating_duration_since(self.timestamp) > self.ttl {
+            return true;
+        }
+
+        match wall_now.duration_since(self.created_at) {
+            Ok(age) => age > self.ttl,
+            Err(err) => err.duration() > CLOCK_SKEW_TOLERANCE,
+        }
+    }
+}
+
+/// One exported cache entry, as found in snapshot files
+///
+/// For the domain cache, `key` is the domain and `value` the
+/// [`CachedVerdict`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheRecord<V = CachedVerdict> {
+    pub key: String,
+    pub value: V,
+    /// When the entry was stored
+    pub inserted_at: SystemTime,
+    pub ttl: Duration,
+}
+
+/// Records for exported entries
+pub(crate) fn to_records<V>(entries: Vec<(String, CacheEntry<V>)>) -> Vec<CacheRecord<V>> {
+    entries
+        .into_iter()
+        .map(|(key, entry)| CacheRecord {
+            key,
+            value: entry.value,
+            inserted_at: entry.created_at,
+            ttl: entry.ttl,
+        })
+        .collect()
+}
+
+/// Entries for the records that haven't expired yet
+pub(crate) fn from_records<V>(records: Vec<CacheRecord<V>>) -> Vec<(String, CacheEntry<V>)> {
+    records
+        .into_iter()
+        .map(|record| {
+            let restored = CacheEntry::restore(record.value, record.inserted_at, record.ttl);
+            (record.key, restored)
+        })
+        .filter(|(_, entry)| !entry.is_expired())
+        .collect()
+}
+
+/// Write entries to a JSON snapshot file
+fn write_snapshot<V: Serialize>(
+    path: &Path,
+    entries: Vec<(String, CacheEntry<V>)>,
+) -> io::Result<()> {
+    fs::write(path, serde_json::to_vec(&to_records(entries))?)
+}
+
+/// Read the unexpired entries of a JSON snapshot file
+fn read_snapshot<V: DeserializeOwned>(path: &Path) -> io::Result<Vec<(String, CacheEntry<V>)>> {
+    let records: Vec<CacheRecord<V>> = serde_json::from_slice(&fs::read(path)?)?;
+    Ok(from_records(records))
+}
+
+type Hook<V> = dyn Fn(&str, &V) + Send + Sync;
+
+/// Callbacks for activity in an in-process cache
+///
+/// Set [`MailGuardConfig::cache_hooks`](crate::MailGuardConfig::cache_hooks)
+/// to mirror the domain cache into telemetry or a secondary store. Each
+/// callback gets the key and the value: for the domain cache, a
+/// [`cache_key`] and the [`CachedVerdict`]. Callbacks run on the checking
+/// task, outside the cache's locks, so they should be quick.
+///
+/// ```rust
+/// use mailguard_rs::{CacheHooks, MailGuardConfig};
+///
+/// let config = MailGuardConfig {
+///     cache_hooks: CacheHooks::new()
+///         .on_hit(|key, _| tracing::debug!("cache hit for {key}"))
+///         .on_evict(|key, _| tracing::debug!("evicted {key}")),
+///     ..Default::default()
+/// };
+/// ```
+pub struct CacheHooks<V = CachedVerdict> {
+    insert: Option<Arc<Hook<V>>>,
+    hit: Option<Arc<Hook<V>>>,
+    evict: Option<Arc<Hook<V>>>,
+    expire: Option<Arc<Hook<V>>>,
+}
+
+impl<V> CacheHooks<V> {
+    /// No callbacks
+    pub fn new() -> Self {
+        Self {
+            insert: None,
+            hit: None,
+            evict: None,
+            expire: None,
+        }
+    }
+
+    /// Call `hook` when an entry is stored
+    pub fn on_insert(mut self, hook: impl Fn(&str, &V) + Send + Sync + 'static) -> Self {
+        self.insert = Some(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` when a lookup finds an unexpired entry
+    pub fn on_hit(mut self, hook: impl Fn(&str, &V) + Send + Sync + 'static) -> Self {
+        self.hit = Some(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` when a size or memory bound evicts an entry
+    pub fn on_evict(mut self, hook: impl Fn(&str, &V) + Send + Sync + 'static) -> Self {
+        self.evict = Some(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` when an expired entry is dropped, on lookup or by a sweep
+    pub fn on_expire(mut self, hook: impl Fn(&str, &V) + Send + Sync + 'static) -> Self {
+        self.expire = Some(Arc::new(hook));
+        self
+    }
+
+    /// Whether both hold the same callbacks
+    pub(crate) fn same_as(&self, other: &Self) -> bool {
+        fn same<V>(a: &Option<Arc<Hook<V>>>, b: &Option<Arc<Hook<V>>>) -> bool {
+            match (a, b) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+        }
+        same(&self.insert, &other.insert)
+            && same(&self.hit, &other.hit)
+            && same(&self.evict, &other.evict)
+            && same(&self.expire, &other.expire)
+    }
+}
+
+#[cfg(feature = "cache")]
+impl<V> CacheHooks<V> {
+    fn fire(hook: &Option<Arc<Hook<V>>>, key: &str, value: &V) {
+        if let Some(hook) = hook {
+            hook(key, value);
+        }
+    }
+
+    fn evicted(&self, evicted: Vec<(String, CacheEntry<V>)>) {
+        for (key, entry) in evicted {
+            Self::fire(&self.evict, &key, &entry.value);
+        }
+    }
+}
+
+impl<V> Default for CacheHooks<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Clone for CacheHooks<V> {
+    fn clone(&self) -> Self {
+        Self {
+            insert: self.insert.clone(),
+            hit: self.hit.clone(),
+            evict: self.evict.clone(),
+            expire: self.expire.clone(),
+        }
+    }
+}
+
+impl<V> fmt::Debug for CacheHooks<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CacheHooks")
+            .field("on_insert", &self.insert.is_some())
+            .field("on_hit", &self.hit.is_some())
+            .field("on_evict", &self.evict.is_some())
+            .field("on_expire", &self.expire.is_some())
+            .finish()
+    }
+}
+
+/// A check's cached verdict on a domain
+///
+/// Holds every category the check reported, the one it decided on first,
+/// with the list evidence behind each, so a cache hit reports the same
+/// threats as the check did. A verdict without categories is a pass.
+/// Serializes as a list of threats; the bare ids or `null` stored by
+/// earlier versions still read back, without evidence.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct CachedVerdict(Vec<CachedThreat>);
+
+/// A category of a [`CachedVerdict`] and its evidence
+///
+/// The check's name is the cache key's namespace, so it isn't repeated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedThreat {
+    kind: ThreatType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    queried_zone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    raw_ip: Option<IpAddr>,
+    /// `None` for the default confidence of the check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    confidence: Option<f32>,
+    /// `None` when unknown; hits then report the time of the lookup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    observed_at: Option<SystemTime>,
+}
+
+impl CachedThreat {
+    fn bare(kind: ThreatType) -> Self {
+        Self {
+            kind,
+            queried_zone: None,
+            raw_ip: None,
+            confidence: None,
+            observed_at: None,
+        }
+    }
+}
+
+impl CachedVerdict {
+    /// The verdict on a domain the check passed
+    pub fn pass() -> Self {
+        Self::default()
+    }
+
+    /// The verdict on a domain listed as `decided`, also reported as
+    /// `others`, without evidence
+    pub fn listed(decided: ThreatType, others: impl IntoIterator<Item = ThreatType>) -> Self {
+        let mut verdict = Self(vec![CachedThreat::bare(decided)]);
+        for other in others {
+            verdict.push(CachedThreat::bare(other));
+        }
+        verdict
+    }
+
+    /// The verdict on a domain listed as `decided`, keeping the evidence of
+    /// the `threats` the check reported
+    pub fn of_threats(decided: ThreatType, threats: &[ThreatInfo]) -> Self {
+        let evidence = |threat: &ThreatInfo| CachedThreat {
+            kind: threat.kind.clone(),
+            queried_zone: threat.queried_zone.clone(),
+            raw_ip: threat.raw_ip,
+            confidence: Some(threat.confidence),
+            observed_at: Some(threat.observed_at),
+        };
+        let mut verdict = Self(vec![
+            threats
+                .iter()
+                .find(|threat| threat.kind == decided)
+                .map(evidence)
+                .unwrap_or_else(|| CachedThreat::bare(decided)),
+        ]);
+        for threat in threats {
+            verdict.push(evidence(threat));
+        }
+        verdict
+    }
+
+    /// Add a category the verdict doesn't have yet
+    fn push(&mut self, threat: CachedThreat) {
+        if !self.0.iter().any(|known| known.kind == threat.kind) {
+            self.0.push(threat);
+        }
+    }
+
+    /// The category the check decided on, `None` for a pass
+    pub fn threat_type(&self) -> Option<&ThreatType> {
+        self.0.first().map(|threat| &threat.kind)
+    }
+
+    /// Every category the check reported, the deciding one first
+    pub fn categories(&self) -> impl Iterator<Item = &ThreatType> {
+        self.0.iter().map(|threat| &threat.kind)
+    }
+
+    /// The threats `check` reported, as stored
+    pub fn threats(&self, check: &str) -> Vec<ThreatInfo> {
+        self.0
+            .iter()
+            .map(|cached| {
+                let mut threat = ThreatInfo::new(cached.kind.clone(), check);
+                threat.queried_zone = cached.queried_zone.clone();
+                threat.raw_ip = cached.raw_ip;
+                if let Some(confidence) = cached.confidence {
+                    threat.confidence = confidence;
+                }
+                if let Some(observed_at) = cached.observed_at {
+                    threat.observed_at = observed_at;
+                }
+                threat
+            })
+            .collect()
+    }
+
+    /// Heap memory held by the verdict, for cache weighing
+    pub(crate) fn heap_size(&self) -> usize {
+        self.0.capacity() * size_of::<CachedThreat>()
+            + self
+                .0
+                .iter()
+                .filter_map(|threat| threat.queried_zone.as_ref())
+                .map(String::capacity)
+                .sum::<usize>()
+    }
+}
+
+impl From<Option<ThreatType>> for CachedVerdict {
+    fn from(threat_type: Option<ThreatType>) -> Self {
+        Self(threat_type.into_iter().map(CachedThreat::bare).collect())
+    }
+}
+
+impl<'de> Deserialize<'de> for CachedVerdict {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Threats(Vec<StoredThreat>),
+            Legacy(Option<ThreatType>),
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StoredThreat {
+            Evidence(CachedThreat),
+            Bare(ThreatType),
+        }
+
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Threats(threats) => Self(
+                threats
+                    .into_iter()
+                    .map(|threat| match threat {
+                        StoredThreat::Evidence(threat) => threat,
+                        StoredThreat::Bare(kind) => CachedThreat::bare(kind),
+                    })
+                    .collect(),
+            ),
+            Stored::Legacy(threat_type) => threat_type.into(),
+        })
+    }
+}
+
+/// Domain cache: threat verdicts keyed by [`cache_key`]
+pub type Cache = TtlCache<CachedVerdict>;
+
+/// Key of `check`'s cached verdict for `domain`, e.g. `surbl:example.com`
+///
+/// Each cacheable check keeps its verdicts under its own namespace, so
+/// they expire independently.
+pub fn cache_key(check: &str, domain: &str) -> String {
+    format!("{check}:{domain}")
+}
+
+/// Storage for the domain cache
+///
+/// [`Cache`] is the default, in-process implementation. Implement this to
+/// keep verdicts somewhere shared, such as Redis or an internal KV store,
+/// and pass it to [`MailGuardBuilder::cache_backend`](crate::MailGuardBuilder::cache_backend).
+/// Keys come from [`cache_key`]; values are the [`CachedVerdict`]s.
+///
+/// Errors are logged and treated as cache misses, so an unavailable
+/// backend slows checks down but doesn't fail them. The email cache always
+/// stays in process.
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// Look up an unexpired verdict
+    async fn get(&self, key: &str) -> Result<Option<CachedVerdict>>;
+
+    /// Store a verdict for `ttl`
+    async fn set(&self, key: &str, value: CachedVerdict, ttl: Duration) -> Result<()>;
+
+    /// Drop a verdict
+    async fn remove(&self, key: &str) -> Result<()>;
+
+    /// Drop every verdict
+    ///
+    /// Called from synchronous code such as
+    /// [`MailGuard::clear_cache`](crate::MailGuard::clear_cache); remote
+    /// backends may start the flush without waiting for it.
+    fn clear(&self);
+
+    /// Number of stored verdicts, if the backend can tell cheaply
+    fn size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Estimated memory held by the verdicts in bytes, for in-process
+    /// backends that track it
+    fn memory_usage(&self) -> Option<usize> {
+        None
+    }
+
+    /// Usage of each shard, for sharded in-process backends
+    fn shard_stats(&self) -> Vec<ShardStats> {
+        Vec::new()
+    }
+
+    /// Drop expired verdicts; backends that expire entries on their own
+    /// needn't do anything
+    fn cleanup_expired(&self) {}
+
+    /// Unexpired verdicts, for [`MailGuard::save_cache`](crate::MailGuard::save_cache)
+    ///
+    /// Backends that can't list their entries return none.
+    fn entries(&self) -> Vec<(String, CacheEntry)> {
+        Vec::new()
+    }
+}
+
+#[async_trait]
+impl CacheBackend for Cache {
+    async fn get(&self, key: &str) -> Result<Option<CachedVerdict>> {
+        Ok(TtlCache::get(self, key))
+    }
+
+    async fn set(&self, key: &str, value: CachedVerdict, ttl: Duration) -> Result<()> {
+        self.set_with_ttl(key.to_string(), value, ttl);
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        TtlCache::remove(self, key);
+        Ok(())
+    }
+
+    fn clear(&self) {
+        TtlCache::clear(self);
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(TtlCache::size(self))
+    }
+
+    fn memory_usage(&self) -> Option<usize> {
+        Some(TtlCache::memory_usage(self))
+    }
+
+    fn shard_stats(&self) -> Vec<ShardStats> {
+        TtlCache::shard_stats(self)
+    }
+
+    fn cleanup_expired(&self) {
+        TtlCache::cleanup_expired(self);
+    }
+
+    fn entries(&self) -> Vec<(String, CacheEntry)> {
+        TtlCache::entries(self)
+    }
+}
+
+/// Email cache: full statuses keyed by normalized address
+pub type EmailCache = TtlCache<EmailStatus>;
+
+/// Shards of an unbounded or large cache
+#[cfg(feature = "cache")]
+const SHARDS: usize = 16;
+
+/// Smallest share of a bounded cache's capacity given to one shard
+///
+/// Eviction is least-recently-used per shard, so small caches use fewer
+/// shards to stay close to exact LRU order.
+#[cfg(feature = "cache")]
+const MIN_SHARD_CAPACITY: usize = 1024;
+
+/// Smallest share of a memory bound given to one shard
+#[cfg(feature = "cache")]
+const MIN_SHARD_BYTES: usize = 1 << 20;
+
+/// Estimated bytes an entry takes besides its key's and value's heap data:
+/// the key and entry themselves plus the LRU list node and table slot
+#[cfg(feature = "cache")]
+const fn entry_overhead<V>() -> usize {
+    size_of::<String>() + size_of::<CacheEntry<V>>() + 4 * size_of::<usize>()
+}
+
+#[cfg(feature = "cache")]
+fn no_heap<V>(_value: &V) -> usize {
+    0
+}
+
+/// One independently locked part of a [`TtlCache`]
+#[cfg(feature = "cache")]
+#[derive(Debug)]
+struct Shard<V> {
+    entries: LruCache<String, CacheEntry<V>>,
+    /// Estimated memory held by `entries`
+    bytes: usize,
+    max_bytes: usize,
+    weigher: fn(&V) -> usize,
+}
+
+#[cfg(feature = "cache")]
+impl<V> Shard<V> {
+    fn weigh(&self, key: &str, entry: &CacheEntry<V>) -> usize {
+        entry_overhead::<V>() + key.len() + (self.weigher)(&entry.value)
+    }
+
+    /// Insert an entry, evicting least recently used ones while the shard
+    /// is over its entry or memory bound, and return the evicted entries
+    fn put(&mut self, key: String, entry: CacheEntry<V>) -> Vec<(String, CacheEntry<V>)> {
+        let mut evicted = Vec::new();
+        let replacing = self.entries.contains(&key);
+        self.bytes += self.weigh(&key, &entry);
+        if let Some((key, entry)) = self.entries.push(key, entry) {
+            self.bytes -= self.weigh(&key, &entry);
+            if !replacing {
+                evicted.push((key, entry));
+            }
+        }
+        while self.bytes > self.max_bytes {
+            let Some((key, entry)) = self.entries.pop_lru() else {
+                break;
+            };
+            self.bytes -= self.weigh(&key, &entry);
+            evicted.push((key, entry));
+        }
+        evicted
+    }
+
+    fn pop(&mut self, key: &str) -> Option<CacheEntry<V>> {
+        let entry = self.entries.pop(key)?;
+        self.bytes -= self.weigh(key, &entry);
+        Some(entry)
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
     }
 }
 
 #[cfg(feature = "cache")]
 /// 内存缓存 (需要 cache feature)
+///
+/// Entries are spread over independently locked shards by key, so
+/// concurrent checks rarely wait on each other, and a panic in one holder
+/// can't poison the others' access. With a size or memory bound set,
+/// inserting into a full shard evicts its least recently used entries.
 #[derive(Debug, Clone)]
-pub struct Cache {
-    inner: Arc<Mutex<HashMap<String, CacheEntry>>>,
+pub struct TtlCache<V> {
+    shards: Arc<[Mutex<Shard<V>>]>,
+    hasher: RandomState,
     default_ttl: Duration,
+    max_entries: Option<usize>,
+    max_bytes: Option<usize>,
+    weigher: fn(&V) -> usize,
+    /// Shard count chosen by the caller instead of from the bounds
+    shard_count: Option<usize>,
+    /// Sliding expiration's bound on an entry's lifetime
+    max_age: Option<Duration>,
+    hooks: CacheHooks<V>,
+    locks: Arc<KeyLocks>,
+}
+
+/// Usage of one shard of a [`TtlCache`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ShardStats {
+    /// Entries in the shard, including expired ones not swept yet
+    pub entries: usize,
+    /// Estimated memory they hold, in bytes
+    pub memory_bytes: usize,
 }
 
 #[cfg(feature = "cache")]
-impl Cache {
+impl<V: Clone> TtlCache<V> {
     pub fn new() -> Self {
-        Self {
-            inner: Arc::new(Mutex::new(HashMap::new())),
-            default_ttl: Duration::from_secs(300), // 5分钟默认TTL
-        }
+        Self::with_ttl(Duration::from_secs(300)) // 5分钟默认TTL
     }
 
     pub fn with_ttl(ttl: Duration) -> Self {
         Self {
-            inner: Arc::new(Mutex::new(HashMap::new())),
+            shards: Arc::new([]),
+            hasher: RandomState::new(),
             default_ttl: ttl,
+            max_entries: None,
+            max_bytes: None,
+            weigher: no_heap::<V>,
+            shard_count: None,
+            max_age: None,
+            hooks: CacheHooks::new(),
+            locks: Arc::default(),
         }
+        .rebuilt()
+    }
+
+    /// Spread the entries over `shards` independently locked shards
+    ///
+    /// By default a cache uses up to 16, fewer when a small bound would
+    /// make eviction order too coarse. Services doing tens of thousands of
+    /// checks per second across many threads may want more. A bound on
+    /// entries caps the count, since every shard holds at least one.
+    pub fn with_shards(mut self, shards: usize) -> Self {
+        self.shard_count = Some(shards.max(1));
+        self.rebuilt()
+    }
+
+    /// Hold at most `max_entries` entries, evicting the least recently used
+    ///
+    /// A bound of zero is treated as one.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries.max(1));
+        self.rebuilt()
+    }
+
+    /// Keep the estimated memory use under `max_bytes`, evicting the least
+    /// recently used entries
+    ///
+    /// The estimate covers the entries' keys and bookkeeping, plus whatever
+    /// the weigher set with [`with_weigher`](Self::with_weigher) counts for
+    /// their values. It is meant for sizing against container memory limits,
+    /// not as an exact measure.
+    pub fn with_max_memory_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self.rebuilt()
+    }
+
+    /// Count `weigher(value)` bytes of heap data for each entry's value
+    ///
+    /// Values are counted by their inline size only by default, which is
+    /// exact for the domain cache's verdicts.
+    pub fn with_weigher(mut self, weigher: fn(&V) -> usize) -> Self {
+        self.weigher = weigher;
+        self.rebuilt()
+    }
+
+    /// Report activity to `hooks`
+    pub fn with_hooks(mut self, hooks: CacheHooks<V>) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Keep entries that are read: each hit extends an entry's lifetime to
+    /// the default TTL from now, but never past `max_age` since it was
+    /// inserted
+    ///
+    /// Hot keys then stay cached until `max_age` forces a refresh, while
+    /// idle ones still expire after the default TTL. Hits never shorten an
+    /// entry's lifetime.
+    pub fn with_sliding_expiration(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Re-create the shards for the current bounds, keeping the entries
+    fn rebuilt(self) -> Self {
+        let by_entries = self
+            .max_entries
+            .map(|max| (max / MIN_SHARD_CAPACITY).clamp(1, SHARDS));
+        let by_bytes = self
+            .max_bytes
+            .map(|max| (max / MIN_SHARD_BYTES).clamp(1, SHARDS));
+        let count = match self.shard_count {
+            Some(count) => count.min(self.max_entries.unwrap_or(usize::MAX)),
+            None => by_entries
+                .into_iter()
+                .chain(by_bytes)
+                .min()
+                .unwrap_or(SHARDS),
+        };
+
+        let shards: Arc<[Mutex<Shard<V>>]> = (0..count)
+            .map(|index| {
+                let entries = match self.max_entries {
+                    Some(max) => {
+                        let capacity = share(max, count, index);
+                        LruCache::new(NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN))
+                    }
+                    None => LruCache::unbounded(),
+                };
+                Mutex::new(Shard {
+                    entries,
+                    bytes: 0,
+                    max_bytes: self
+                        .max_bytes
+                        .map_or(usize::MAX, |max| share(max, count, index)),
+                    weigher: self.weigher,
+                })
+            })
+            .collect();
+        let cache = Self {
+            shards,
+            hasher: self.hasher.clone(),
+            hooks: self.hooks.clone(),
+            locks: self.locks.clone(),
+            ..self
+        };
+        cache.restore_entries(self.entries());
+        cache
+    }
+
+    /// Lock the shard holding `key`
+    ///
+    /// A shard left poisoned by a panic is used as it is: at worst it holds
+    /// one entry of a half-finished update, which is still a valid entry.
+    fn shard(&self, key: &str) -> MutexGuard<'_, Shard<V>> {
+        let index = self.hasher.hash_one(key) as usize % self.shards.len();
+        lock(&self.shards[index])
     }
 
     /// 获取缓存条目
-    pub fn get(&self, key: &str) -> Option<Option<ThreatType>> {
-        let mut cache = self.inner.lock().unwrap();
-
-        if let Some(entry) = cache.get(key) {
-            if !entry.is_expired() {
-                return Some(entry.threat_type.clone());
-            } else {
-                // 移除过期条目
-                cache.remove(key);
+    pub fn get(&self, key: &str) -> Option<V> {
+        let mut cache = self.shard(key);
+
+        let entry = cache.entries.get_mut(key)?;
+        if !entry.is_expired() {
+            if let Some(max_age) = self.max_age {
+                let age = entry.timestamp.elapsed();
+                entry.ttl = entry.ttl.max((age + self.default_ttl).min(max_age));
             }
+            let value = entry.value.clone();
+            drop(cache);
+            CacheHooks::fire(&self.hooks.hit, key, &value);
+            return Some(value);
         }
 
+        // 移除过期条目
+        let expired = cache.pop(key);
+        drop(cache);
+        if let Some(entry) = expired {
+            CacheHooks::fire(&self.hooks.expire, key, &entry.value);
+        }
         None
     }
 
     /// 设置缓存条目
-    pub fn set(&self, key: String, threat_type: Option<ThreatType>) {
-        let entry = CacheEntry::new(threat_type, self.default_ttl);
-        let mut cache = self.inner.lock().unwrap();
-        cache.insert(key, entry);
+    pub fn set(&self, key: String, value: V) {
+        self.set_with_ttl(key, value, self.default_ttl);
+    }
+
+    /// Set an entry that expires after `ttl` instead of the default TTL
+    pub fn set_with_ttl(&self, key: String, value: V, ttl: Duration) {
+        CacheHooks::fire(&self.hooks.insert, &key, &value);
+        let entry = CacheEntry::new(value, ttl);
+        let evicted = self.shard(&key).put(key, entry);
+        self.hooks.evicted(evicted);
+    }
+
+    /// Get an entry, or insert the value `init` computes if it is missing
+    ///
+    /// Callers missing the same key at once wait for the first one's
+    /// `init` instead of running their own, so it runs at most once per
+    /// key until the entry expires. If it fails, the error goes to that
+    /// caller only and nothing is cached: the next waiter runs `init`
+    /// itself.
+    pub async fn get_or_try_insert_with<F, Fut, E>(
+        &self,
+        key: &str,
+        init: F,
+    ) -> std::result::Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = std::result::Result<V, E>>,
+    {
+        self.locks
+            .get_or_try_insert_with(
+                key,
+                || async { self.get(key) },
+                || async {
+                    let value = init().await?;
+                    self.set(key.to_string(), value.clone());
+                    Ok(value)
+                },
+            )
+            .await
+    }
+
+    /// Remove an entry
+    pub fn remove(&self, key: &str) {
+        self.shard(key).pop(key);
     }
 
     /// 清理过期条目
     pub fn cleanup_expired(&self) {
-        let mut cache = self.inner.lock().unwrap();
-        cache.retain(|_, entry| !entry.is_expired());
+        for shard in self.shards.iter() {
+            let mut cache = lock(shard);
+            let keys: Vec<String> = cache
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.is_expired())
+                .map(|(key, _)| key.clone())
+                .collect();
+            let expired: Vec<(String, CacheEntry<V>)> = keys
+                .into_iter()
+                .filter_map(|key| cache.pop(&key).map(|entry| (key, entry)))
+                .collect();
+            drop(cache);
+            for (key, entry) in expired {
+                CacheHooks::fire(&self.hooks.expire, &key, &entry.value);
+            }
+        }
+    }
+
+    /// Keep only the entries for which `keep` returns `true`
+    pub fn retain(&self, mut keep: impl FnMut(&str, &V) -> bool) {
+        for shard in self.shards.iter() {
+            let mut cache = lock(shard);
+            let dropped: Vec<String> = cache
+                .entries
+                .iter()
+                .filter(|(key, entry)| !keep(key, &entry.value))
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in dropped {
+                cache.pop(&key);
+            }
+        }
     }
 
     /// 获取缓存大小
     pub fn size(&self) -> usize {
-        let cache = self.inner.lock().unwrap();
-        cache.len()
+        self.shards
+            .iter()
+            .map(|shard| lock(shard).entries.len())
+            .sum()
+    }
+
+    /// Estimated memory held by the entries, in bytes
+    pub fn memory_usage(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).bytes).sum()
+    }
+
+    /// Entries and memory use of each shard, to spot uneven spreading
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        self.shards
+            .iter()
+            .map(|shard| {
+                let shard = lock(shard);
+                ShardStats {
+                    entries: shard.entries.len(),
+                    memory_bytes: shard.bytes,
+                }
+            })
+            .collect()
     }
 
     /// 清空缓存
     pub fn clear(&self) {
-        let mut cache = self.inner.lock().unwrap();
-        cache.clear();
+        for shard in self.shards.iter() {
+            lock(shard).clear();
+        }
     }
+
+    /// Unexpired entries, least recently used first within each shard
+    pub fn entries(&self) -> Vec<(String, CacheEntry<V>)> {
+        self.shards
+            .iter()
+            .flat_map(|shard| {
+                lock(shard)
+                    .entries
+                    .iter()
+                    .rev()
+                    .filter(|(_, entry)| !entry.is_expired())
+                    .map(|(key, entry)| (key.clone(), entry.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Insert entries as they are, keeping their creation times
+    fn restore_entries(&self, entries: Vec<(String, CacheEntry<V>)>) {
+        for (key, entry) in entries {
+            let evicted = self.shard(&key).put(key, entry);
+            self.hooks.evicted(evicted);
+        }
+    }
+}
+
+/// Share `index` of `total` split over `count` shards, spreading the
+/// remainder so the shares add up exactly
+#[cfg(feature = "cache")]
+fn share(total: usize, count: usize, index: usize) -> usize {
+    total / count + usize::from(index < total % count)
+}
+
+#[cfg(feature = "cache")]
+fn lock<V>(shard: &Mutex<Shard<V>>) -> MutexGuard<'_, Shard<V>> {
+    lock_recovering(shard)
 }
 
 #[cfg(feature = "cache")]
-impl Default for Cache {
+impl<V: Clone + Serialize + DeserializeOwned> TtlCache<V> {
+    /// Save the unexpired entries to a JSON snapshot file
+    pub fn save_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        write_snapshot(path.as_ref(), self.entries())
+    }
+
+    /// Add the entries of a snapshot file that haven't expired since, and
+    /// return how many were added
+    ///
+    /// Entries keep their remaining lifetime and replace entries under the
+    /// same key.
+    pub fn load_from(&self, path: impl AsRef<Path>) -> io::Result<usize> {
+        let entries = read_snapshot(path.as_ref())?;
+        let count = entries.len();
+        self.restore_entries(entries);
+        Ok(count)
+    }
+}
+
+#[cfg(feature = "cache")]
+impl<V: Clone> Default for TtlCache<V> {
     fn default() -> Self {
         Self::new()
     }
@@ -104,23 +1107,73 @@ impl Default for Cache {
 #[cfg(not(feature = "cache"))]
 /// 无操作缓存实现 (cache feature 未启用)
 #[derive(Debug, Clone)]
-pub struct Cache;
+pub struct TtlCache<V>(PhantomData<V>);
 
 #[cfg(not(feature = "cache"))]
-impl Cache {
+impl<V> TtlCache<V> {
     pub fn new() -> Self {
-        Cache
+        TtlCache(PhantomData)
     }
 
     pub fn with_ttl(_ttl: Duration) -> Self {
-        Cache
+        TtlCache(PhantomData)
     }
 
-    pub fn get(&self, _key: &str) -> Option<Option<ThreatType>> {
+    pub fn with_max_entries(self, _max_entries: usize) -> Self {
+        self
+    }
+
+    pub fn with_max_memory_bytes(self, _max_bytes: usize) -> Self {
+        self
+    }
+
+    pub fn with_weigher(self, _weigher: fn(&V) -> usize) -> Self {
+        self
+    }
+
+    pub fn with_shards(self, _shards: usize) -> Self {
+        self
+    }
+
+    pub fn with_hooks(self, _hooks: CacheHooks<V>) -> Self {
+        self
+    }
+
+    pub fn with_sliding_expiration(self, _max_age: Duration) -> Self {
+        self
+    }
+
+    pub fn get(&self, _key: &str) -> Option<V> {
         None
     }
 
-    pub fn set(&self, _key: String, _threat_type: Option<ThreatType>) {
+    pub fn set(&self, _key: String, _value: V) {
+        // 无操作
+    }
+
+    pub fn set_with_ttl(&self, _key: String, _value: V, _ttl: Duration) {
+        // 无操作
+    }
+
+    /// Without the `cache` feature nothing is stored, so `init` runs on
+    /// every call
+    pub async fn get_or_try_insert_with<F, Fut, E>(
+        &self,
+        _key: &str,
+        init: F,
+    ) -> std::result::Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = std::result::Result<V, E>>,
+    {
+        init().await
+    }
+
+    pub fn remove(&self, _key: &str) {
+        // 无操作
+    }
+
+    pub fn retain(&self, _keep: impl FnMut(&str, &V) -> bool) {
         // 无操作
     }
 
@@ -132,13 +1185,36 @@ impl Cache {
         0
     }
 
+    pub fn memory_usage(&self) -> usize {
+        0
+    }
+
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        Vec::new()
+    }
+
     pub fn clear(&self) {
         // 无操作
     }
+
+    pub fn entries(&self) -> Vec<(String, CacheEntry<V>)> {
+        Vec::new()
+    }
+}
+
+#[cfg(not(feature = "cache"))]
+impl<V: Serialize + DeserializeOwned> TtlCache<V> {
+    pub fn save_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        write_snapshot::<V>(path.as_ref(), Vec::new())
+    }
+
+    pub fn load_from(&self, path: impl AsRef<Path>) -> io::Result<usize> {
+        read_snapshot::<V>(path.as_ref()).map(|_| 0)
+    }
 }
 
 #[cfg(not(feature = "cache"))]
-impl Default for Cache {
+impl<V> Default for TtlCache<V> {
     fn default() -> Self {
         Self::new()
     }
@@ -0,0 +1,21 @@
+//! Canonical JSON serialization
+//!
+//! Produces byte-for-byte stable JSON for results and reports, so verdict
+//! files from different runs can be diffed directly. Object keys are sorted
+//! recursively; array order is kept as produced, which is already stable
+//! for every type in this crate.
+
+use serde::Serialize;
+
+/// Serialize a value as compact JSON with recursively sorted object keys
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    // `serde_json::Map` is a BTreeMap, so going through `Value` sorts keys
+    let value = serde_json::to_value(value)?;
+    serde_json::to_string(&value)
+}
+
+/// Serialize a value as pretty-printed JSON with recursively sorted object keys
+pub fn to_canonical_json_pretty<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    let value = serde_json::to_value(value)?;
+    serde_json::to_string_pretty(&value)
+}
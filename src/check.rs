@@ -0,0 +1,499 @@
+//! Pluggable checks making up the detection pipeline
+//!
+//! A [`MailGuard`](crate::MailGuard) runs a domain through an ordered list of
+//! [`Check`]s. The first check that reaches a verdict ([`CheckOutcome::Safe`]
+//! or [`CheckOutcome::Threat`]) decides the result; checks that have no
+//! opinion return [`CheckOutcome::Pass`]. If every check passes, the domain
+//! is safe.
+//!
+//! The built-in pipeline is [`AllowlistCheck`], [`BlocklistCheck`],
+//! [`TrustedProviderCheck`] and [`SurblCheck`], with [`RankCheck`] before
+//! SURBL when
+//! [`MailGuardConfig::trusted_rank`](crate::MailGuardConfig::trusted_rank) is
+//! set and [`MxFingerprintCheck`] after it when
+//! [`MailGuardConfig::detect_disposable_mx`](crate::MailGuardConfig::detect_disposable_mx)
+//! is set. Add your own checks, e.g. a proprietary reputation
+//! source, with [`MailGuardBuilder::with_check`](crate::MailGuardBuilder::with_check).
+//!
+//! By default the remaining checks are skipped once one reaches a verdict.
+//! Set [`MailGuardConfig::short_circuit`](crate::MailGuardConfig::short_circuit)
+//! to `false` to run every check and report all outcomes in
+//! `check_results`, and use
+//! [`MailGuardConfig::check_order`](crate::MailGuardConfig::check_order) to
+//! reorder checks by name.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mailguard_rs::{
+//!     Fixture, MailGuard, ThreatType,
+//!     check::{Check, CheckOutcome, async_trait},
+//!     dns::DnsClient,
+//!     error::Result,
+//! };
+//!
+//! struct InternalReputation;
+//!
+//! #[async_trait]
+//! impl Check for InternalReputation {
+//!     fn name(&self) -> &str {
+//!         "internal_reputation"
+//!     }
+//!
+//!     async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+//!         if domain.ends_with(".bad.test") {
+//!             return Ok(CheckOutcome::Threat(Some(ThreatType::Spam)));
+//!         }
+//!         Ok(CheckOutcome::Pass)
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     let detector = MailGuard::builder()
+//!         .dns_client(DnsClient::playback(Fixture::new()))
+//!         .with_check(InternalReputation)
+//!         .build()?;
+//!     let status = detector.check_domain("mail.bad.test").await?;
+//!     assert_eq!(status.threat_type, Some(ThreatType::Spam));
+//!     Ok(())
+//! }
+//! ```
+
+use std::{collections::HashMap, sync::Arc};
+
+pub use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    coverage::{CheckCoverage, SURBL_CATEGORIES},
+    detector::by_severity,
+    dns::{DnsClient, SURBL_ZONE},
+    error::Result,
+    lists::{DomainList, SharedDomainList},
+    rank::DomainRanks,
+    suffix::lookup_candidates,
+    threat::{ThreatInfo, ThreatType},
+};
+
+/// Name of [`AllowlistCheck`]
+pub const ALLOWLIST: &str = "allowlist";
+/// Name of [`BlocklistCheck`]
+pub const BLOCKLIST: &str = "blocklist";
+/// Name of [`SurblCheck`]
+pub const SURBL: &str = "surbl";
+/// Name of [`RankCheck`]
+pub const RANK: &str = "rank";
+/// Name of [`MxFingerprintCheck`]
+pub const MX_FINGERPRINT: &str = "mx_fingerprint";
+/// Name of [`TrustedProviderCheck`]
+pub const TRUSTED_PROVIDER: &str = "trusted_provider";
+
+/// MX backends shared by many throwaway domains
+///
+/// Entries also cover their subdomains (`mail2.mailinator.com`). Add more
+/// through [`MailGuardConfig::disposable_mx_hosts`](crate::MailGuardConfig::disposable_mx_hosts).
+pub const DISPOSABLE_MX_HOSTS: &[&str] = &["mailinator.com", "guerrillamail.com", "yopmail.com"];
+
+/// Major mail providers answered as safe without a DNS query
+///
+/// The default of [`MailGuardConfig::trusted_providers`](crate::MailGuardConfig::trusted_providers).
+pub const TRUSTED_PROVIDERS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "outlook.com",
+    "hotmail.com",
+    "live.com",
+    "msn.com",
+    "yahoo.com",
+    "ymail.com",
+    "icloud.com",
+    "me.com",
+    "mac.com",
+    "aol.com",
+    "mail.com",
+    "email.com",
+    "proton.me",
+    "protonmail.com",
+    "gmx.com",
+    "gmx.de",
+    "web.de",
+    "mail.ru",
+    "yandex.ru",
+    "qq.com",
+    "163.com",
+    "126.com",
+];
+
+/// What a check concluded about a domain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckOutcome {
+    /// No opinion; later checks decide
+    Pass,
+    /// The domain is safe
+    Safe,
+    /// The domain is a threat
+    Threat(Option<ThreatType>),
+}
+
+/// Outcome of one check that ran for a domain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckResult {
+    /// [`Check::name`] of the check
+    pub check: String,
+    /// What it concluded
+    pub outcome: CheckOutcome,
+}
+
+/// A single step of the detection pipeline
+///
+/// Checks receive the lowercased, validated domain. Returning an error aborts
+/// the whole check.
+///
+/// Built-in checks are named [`ALLOWLIST`], [`BLOCKLIST`], [`SURBL`],
+/// [`TRUSTED_PROVIDER`], [`RANK`] and [`MX_FINGERPRINT`].
+#[async_trait]
+pub trait Check: Send + Sync {
+    /// Short identifier, used in logs
+    ///
+    /// The name also keys the check's cached verdicts (see
+    /// [`cache_key`](crate::cache::cache_key)) and is how
+    /// [`check_order`](crate::MailGuardConfig::check_order), ensemble
+    /// weights and threat aggregation precedence refer to the check.
+    /// Renaming a check silently invalidates all of them.
+    fn name(&self) -> &str;
+
+    /// Judge a domain
+    async fn check(&self, domain: &str) -> Result<CheckOutcome>;
+
+    /// Judge a domain, also returning every threat found with its evidence
+    ///
+    /// Defaults to [`check`](Self::check), with the outcome's category as
+    /// the only threat. Lookups that learn more, such as several
+    /// categories in one list answer, override this.
+    async fn check_threats(&self, domain: &str) -> Result<(CheckOutcome, Vec<ThreatInfo>)> {
+        let outcome = self.check(domain).await?;
+        let threats = match &outcome {
+            CheckOutcome::Threat(Some(kind)) => vec![ThreatInfo::new(kind.clone(), self.name())],
+            _ => Vec::new(),
+        };
+        Ok((outcome, threats))
+    }
+
+    /// Whether the outcome may be cached
+    ///
+    /// A cacheable check's outcome is stored in the detector's cache under
+    /// [`cache_key`](crate::cache::cache_key) for its name, and a cache hit
+    /// skips the check. Safe outcomes and threats without a type aren't
+    /// stored. Cheap, local checks should return `false`.
+    fn cacheable(&self) -> bool {
+        false
+    }
+
+    /// What the check can detect, for [`MailGuard::coverage`](crate::MailGuard::coverage)
+    fn coverage(&self) -> CheckCoverage {
+        CheckCoverage::new(self.name(), "custom check")
+    }
+}
+
+/// Reports domains on [`MailGuardConfig::allowlist`](crate::MailGuardConfig::allowlist) as safe
+#[derive(Debug, Clone)]
+pub struct AllowlistCheck {
+    list: SharedDomainList,
+}
+
+impl AllowlistCheck {
+    /// Check against `list`; pass a [`SharedDomainList`] to change the
+    /// rules while the check is in use
+    pub fn new(list: impl Into<SharedDomainList>) -> Self {
+        Self { list: list.into() }
+    }
+}
+
+#[async_trait]
+impl Check for AllowlistCheck {
+    fn name(&self) -> &str {
+        ALLOWLIST
+    }
+
+    async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+        if self.list.matches(domain) {
+            tracing::debug!("Domain {domain} is allowlisted");
+            return Ok(CheckOutcome::Safe);
+        }
+        Ok(CheckOutcome::Pass)
+    }
+
+    fn coverage(&self) -> CheckCoverage {
+        CheckCoverage::new(
+            self.name(),
+            format!("local list, {} rule(s)", self.list.len()),
+        )
+    }
+}
+
+/// Reports domains on [`MailGuardConfig::blocklist`](crate::MailGuardConfig::blocklist) as threats
+#[derive(Debug, Clone)]
+pub struct BlocklistCheck {
+    list: SharedDomainList,
+}
+
+impl BlocklistCheck {
+    /// Check against `list`; pass a [`SharedDomainList`] to change the
+    /// rules while the check is in use
+    pub fn new(list: impl Into<SharedDomainList>) -> Self {
+        Self { list: list.into() }
+    }
+}
+
+#[async_trait]
+impl Check for BlocklistCheck {
+    fn name(&self) -> &str {
+        BLOCKLIST
+    }
+
+    async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+        if self.list.matches(domain) {
+            tracing::debug!("Domain {domain} is blocklisted");
+            return Ok(CheckOutcome::Threat(None));
+        }
+        Ok(CheckOutcome::Pass)
+    }
+
+    fn coverage(&self) -> CheckCoverage {
+        CheckCoverage::new(
+            self.name(),
+            format!("local list, {} rule(s)", self.list.len()),
+        )
+    }
+}
+
+/// Reports domains ranked high on a top-sites list as safe
+#[derive(Debug, Clone)]
+pub struct RankCheck {
+    ranks: Arc<DomainRanks>,
+    trusted_rank: u32,
+}
+
+impl RankCheck {
+    /// Trust domains ranked `trusted_rank` or better
+    pub fn new(ranks: Arc<DomainRanks>, trusted_rank: u32) -> Self {
+        Self {
+            ranks,
+            trusted_rank,
+        }
+    }
+}
+
+#[async_trait]
+impl Check for RankCheck {
+    fn name(&self) -> &str {
+        RANK
+    }
+
+    async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+        match self.ranks.rank(domain) {
+            Some(rank) if rank <= self.trusted_rank => {
+                tracing::debug!("Domain {domain} is trusted by rank {rank}");
+                Ok(CheckOutcome::Safe)
+            }
+            _ => Ok(CheckOutcome::Pass),
+        }
+    }
+
+    fn coverage(&self) -> CheckCoverage {
+        CheckCoverage::new(
+            self.name(),
+            format!(
+                "top-sites list, {} domain(s), trusting ranks up to {}",
+                self.ranks.len(),
+                self.trusted_rank
+            ),
+        )
+    }
+}
+
+/// Reports major mail providers as safe, answering from memory
+///
+/// Most traffic comes from a handful of providers; trusting them up front
+/// saves a SURBL query per address.
+#[derive(Debug, Clone)]
+pub struct TrustedProviderCheck {
+    providers: DomainList,
+}
+
+impl TrustedProviderCheck {
+    /// Trust the domains on `providers`
+    ///
+    /// Use [`TRUSTED_PROVIDERS`] for the built-in list; rules follow the
+    /// syntax of [`DomainList`].
+    pub fn new(providers: DomainList) -> Self {
+        Self { providers }
+    }
+}
+
+#[async_trait]
+impl Check for TrustedProviderCheck {
+    fn name(&self) -> &str {
+        TRUSTED_PROVIDER
+    }
+
+    async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+        if self.providers.matches(domain) {
+            tracing::debug!("Domain {domain} is a trusted provider");
+            return Ok(CheckOutcome::Safe);
+        }
+        Ok(CheckOutcome::Pass)
+    }
+
+    fn coverage(&self) -> CheckCoverage {
+        CheckCoverage::new(
+            self.name(),
+            format!("trusted providers, {} rule(s)", self.providers.len()),
+        )
+    }
+}
+
+/// Looks the domain up in the SURBL DNS zone
+#[derive(Clone)]
+pub struct SurblCheck {
+    dns_client: DnsClient,
+    octet_overrides: HashMap<u8, ThreatType>,
+    severity_overrides: HashMap<ThreatType, u8>,
+    parent_depth: usize,
+}
+
+impl SurblCheck {
+    /// Create a SURBL check
+    ///
+    /// `octet_overrides` maps return codes this crate doesn't know to threat
+    /// types (see [`MailGuardConfig::octet_overrides`](crate::MailGuardConfig::octet_overrides)).
+    pub fn new(dns_client: DnsClient, octet_overrides: HashMap<u8, ThreatType>) -> Self {
+        Self {
+            dns_client,
+            octet_overrides,
+            severity_overrides: HashMap::new(),
+            parent_depth: 0,
+        }
+    }
+
+    /// Rank the categories of a listing by `overrides` where they have one
+    ///
+    /// See [`MailGuardConfig::severity_overrides`](crate::MailGuardConfig::severity_overrides).
+    pub fn with_severity_overrides(mut self, overrides: HashMap<ThreatType, u8>) -> Self {
+        self.severity_overrides = overrides;
+        self
+    }
+
+    /// Also query up to `depth` parent domains when the domain isn't listed
+    ///
+    /// See [`MailGuardConfig::parent_lookup_depth`](crate::MailGuardConfig::parent_lookup_depth).
+    pub fn with_parent_depth(mut self, depth: usize) -> Self {
+        self.parent_depth = depth;
+        self
+    }
+
+    /// Map an unknown return code through the overrides
+    fn apply_octet_override(&self, threat_type: ThreatType) -> ThreatType {
+        match threat_type {
+            ThreatType::Unknown(octet) => self
+                .octet_overrides
+                .get(&octet)
+                .cloned()
+                .unwrap_or(threat_type),
+            known => known,
+        }
+    }
+}
+
+#[async_trait]
+impl Check for SurblCheck {
+    fn name(&self) -> &str {
+        SURBL
+    }
+
+    async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+        Ok(self.check_threats(domain).await?.0)
+    }
+
+    /// Reports every category of the listing, most severe first, with the
+    /// zone's answer
+    async fn check_threats(&self, domain: &str) -> Result<(CheckOutcome, Vec<ThreatInfo>)> {
+        for candidate in lookup_candidates(domain, self.parent_depth) {
+            let mut threats = self.dns_client.query_surbl_threats(candidate).await?;
+            if threats.is_empty() {
+                continue;
+            }
+            for threat in &mut threats {
+                threat.kind = self.apply_octet_override(threat.kind.clone());
+            }
+            by_severity(&mut threats, &self.severity_overrides);
+            let outcome = CheckOutcome::Threat(Some(threats[0].kind.clone()));
+            return Ok((outcome, threats));
+        }
+        Ok((CheckOutcome::Pass, Vec::new()))
+    }
+
+    fn cacheable(&self) -> bool {
+        true
+    }
+
+    fn coverage(&self) -> CheckCoverage {
+        let mut categories = SURBL_CATEGORIES.to_vec();
+        for threat_type in self.octet_overrides.values() {
+            if !categories.contains(threat_type) {
+                categories.push(threat_type.clone());
+            }
+        }
+        CheckCoverage::new(self.name(), format!("DNS zone {SURBL_ZONE}"))
+            .with_categories(categories)
+    }
+}
+
+/// Flags domains whose MX hosts belong to a known disposable-mail backend
+///
+/// Catches fresh throwaway domains that no list has picked up yet, since
+/// they usually point at the same few mail servers.
+#[derive(Clone)]
+pub struct MxFingerprintCheck {
+    dns_client: DnsClient,
+    hosts: DomainList,
+}
+
+impl MxFingerprintCheck {
+    /// Create an MX check matching against `hosts`
+    ///
+    /// Use [`DISPOSABLE_MX_HOSTS`] for the built-in list; rules follow the
+    /// syntax of [`DomainList`].
+    pub fn new(dns_client: DnsClient, hosts: DomainList) -> Self {
+        Self { dns_client, hosts }
+    }
+}
+
+#[async_trait]
+impl Check for MxFingerprintCheck {
+    fn name(&self) -> &str {
+        MX_FINGERPRINT
+    }
+
+    async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+        let hosts = self.dns_client.lookup_mx(domain).await?;
+        if let Some(host) = hosts.iter().find(|host| self.hosts.matches(host)) {
+            tracing::info!("Domain {domain} uses disposable mail backend {host}");
+            return Ok(CheckOutcome::Threat(Some(ThreatType::Disposable)));
+        }
+        Ok(CheckOutcome::Pass)
+    }
+
+    fn cacheable(&self) -> bool {
+        true
+    }
+
+    fn coverage(&self) -> CheckCoverage {
+        CheckCoverage::new(
+            self.name(),
+            format!("MX hosts, {} known backend(s)", self.hosts.len()),
+        )
+        .with_categories([ThreatType::Disposable])
+    }
+}
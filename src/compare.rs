@@ -0,0 +1,96 @@
+//! Comparison of MailGuard verdicts against an external system
+//!
+//! Feed pairs of verdicts for the same inputs into [`VerdictComparison`] to
+//! measure how often MailGuard agrees with an existing vendor before
+//! migrating off it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::detector::{DomainStatus, EmailStatus};
+
+/// Agreement statistics between our verdicts and an external system's
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerdictComparison {
+    /// Inputs both systems flagged as threats
+    pub both_threat: usize,
+    /// Inputs both systems considered safe
+    pub both_safe: usize,
+    /// Inputs only MailGuard flagged
+    pub only_ours: Vec<String>,
+    /// Inputs only the external system flagged
+    pub only_external: Vec<String>,
+}
+
+impl VerdictComparison {
+    /// Create an empty comparison
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one pair of verdicts for the same input
+    pub fn record(&mut self, input: &str, ours: bool, external: bool) {
+        match (ours, external) {
+            (true, true) => self.both_threat += 1,
+            (false, false) => self.both_safe += 1,
+            (true, false) => self.only_ours.push(input.to_string()),
+            (false, true) => self.only_external.push(input.to_string()),
+        }
+    }
+
+    /// Record an email verdict against the external system's verdict
+    pub fn record_email(&mut self, status: &EmailStatus, external_is_threat: bool) {
+        self.record(&status.email, status.is_threat, external_is_threat);
+    }
+
+    /// Record a domain verdict against the external system's verdict
+    pub fn record_domain(&mut self, status: &DomainStatus, external_is_threat: bool) {
+        self.record(&status.domain, status.is_threat, external_is_threat);
+    }
+
+    /// Total number of compared inputs
+    pub fn total(&self) -> usize {
+        self.agreements() + self.disagreements()
+    }
+
+    /// Number of inputs with the same verdict from both systems
+    pub fn agreements(&self) -> usize {
+        self.both_threat + self.both_safe
+    }
+
+    /// Number of inputs with different verdicts
+    pub fn disagreements(&self) -> usize {
+        self.only_ours.len() + self.only_external.len()
+    }
+
+    /// Fraction of inputs with the same verdict (0.0 - 1.0)
+    ///
+    /// Returns 1.0 for an empty comparison.
+    pub fn agreement_rate(&self) -> f64 {
+        match self.total() {
+            0 => 1.0,
+            total => self.agreements() as f64 / total as f64,
+        }
+    }
+
+    /// Cohen's kappa, agreement corrected for chance (-1.0 - 1.0)
+    ///
+    /// Returns 1.0 when the expected chance agreement is already perfect
+    /// (for example, both systems flagged nothing).
+    pub fn cohen_kappa(&self) -> f64 {
+        let total = self.total() as f64;
+        if total == 0.0 {
+            return 1.0;
+        }
+
+        let ours_threat = (self.both_threat + self.only_ours.len()) as f64 / total;
+        let external_threat = (self.both_threat + self.only_external.len()) as f64 / total;
+        let expected =
+            ours_threat * external_threat + (1.0 - ours_threat) * (1.0 - external_threat);
+
+        if expected >= 1.0 {
+            return 1.0;
+        }
+
+        (self.agreement_rate() - expected) / (1.0 - expected)
+    }
+}
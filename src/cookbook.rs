@@ -0,0 +1,221 @@
+//! Recipes for common deployments
+//!
+//! Every recipe below is compiled, and where it doesn't need a network
+//! listener also run, by `cargo test`, so they keep working as the crate
+//! evolves.
+//!
+//! # Signup guard in an axum app
+//!
+//! Share one [`MailGuard`](crate::MailGuard) through the router state and
+//! reject disposable or malicious addresses before creating the account.
+//! Lookup failures are let through; the address can be re-checked later.
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//!
+//! use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
+//! use mailguard_rs::{MailGuard, MailGuardConfig};
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Signup {
+//!     email: String,
+//! }
+//!
+//! async fn signup(
+//!     State(guard): State<Arc<MailGuard>>,
+//!     Json(form): Json<Signup>,
+//! ) -> Result<StatusCode, (StatusCode, String)> {
+//!     match guard.check_email(&form.email).await {
+//!         Ok(status) if status.is_threat => Err((
+//!             StatusCode::UNPROCESSABLE_ENTITY,
+//!             "Please use a permanent email address".to_string(),
+//!         )),
+//!         Ok(status) if status.typo_of.is_some() => Err((
+//!             StatusCode::UNPROCESSABLE_ENTITY,
+//!             format!("Did you mean {}?", status.typo_of.unwrap_or_default()),
+//!         )),
+//!         Err(mailguard_rs::MailGuardError::InvalidEmail { .. }) => {
+//!             Err((StatusCode::BAD_REQUEST, "Invalid email address".to_string()))
+//!         }
+//!         // Accept on DNS failures rather than blocking signups
+//!         Ok(_) | Err(_) => Ok(StatusCode::CREATED),
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let guard = MailGuard::try_with_config(MailGuardConfig {
+//!         detect_typos: true,
+//!         ..Default::default()
+//!     })?;
+//!
+//!     let app = Router::new()
+//!         .route("/signup", post(signup))
+//!         .with_state(Arc::new(guard));
+//!
+//!     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
+//!     axum::serve(listener, app).await?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! # Batch hygiene job
+//!
+//! Periodically re-check a customer list and write a report that can be
+//! diffed against the previous run. Each distinct domain is queried once, so
+//! large lists dominated by a few providers stay cheap. The recipe runs
+//! against a recorded [`Fixture`](crate::Fixture); drop the `dns_client` line
+//! to query the live zone.
+//!
+//! ```rust
+//! use std::{collections::BTreeMap, net::IpAddr};
+//!
+//! use mailguard_rs::{
+//!     Fixture, FixtureAnswer, MailGuard, dns::DnsClient, parse_address_list,
+//!     to_canonical_json_pretty,
+//! };
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let mut fixture = Fixture::new();
+//!     fixture.insert(
+//!         "tempmail.test.tempmail.so.multi.surbl.org",
+//!         FixtureAnswer::Records {
+//!             ips: vec![IpAddr::from([127, 0, 0, 2])],
+//!             ttl_secs: 300,
+//!         },
+//!     );
+//!
+//!     let guard = MailGuard::builder()
+//!         .dns_client(DnsClient::playback(fixture))
+//!         .build()?;
+//!
+//!     // Usually read from an export of the users table
+//!     let export = "alice@example.com, bob@tempmail.test\nnot an address\ncarol@example.com";
+//!     let parsed = parse_address_list(export);
+//!
+//!     let mut flagged = BTreeMap::new();
+//!     for status in guard
+//!         .check_emails_batch(&parsed.as_strs())
+//!         .await
+//!         .into_iter()
+//!         .flatten()
+//!         .filter(|status| status.is_threat)
+//!     {
+//!         flagged.insert(status.email.clone(), status);
+//!     }
+//!
+//!     let report = to_canonical_json_pretty(&flagged)?;
+//!     assert!(report.contains("bob@tempmail.test"));
+//!     assert_eq!(parsed.errors.len(), 1);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! # Sidecar service
+//!
+//! Run the detector as a small HTTP service next to applications written in
+//! other languages. Keeping one long-lived process means the cache is shared
+//! by every caller.
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//!
+//! use axum::{
+//!     Json, Router,
+//!     extract::{Path, State},
+//!     http::StatusCode,
+//!     routing::get,
+//! };
+//! use mailguard_rs::{EmailStatus, MailGuard, MailGuardError};
+//!
+//! async fn check(
+//!     State(guard): State<Arc<MailGuard>>,
+//!     Path(email): Path<String>,
+//! ) -> Result<Json<EmailStatus>, (StatusCode, String)> {
+//!     guard.check_email(&email).await.map(Json).map_err(|err| {
+//!         let status = match err.root_cause() {
+//!             MailGuardError::InvalidEmail { .. } | MailGuardError::InvalidDomain { .. } => {
+//!                 StatusCode::BAD_REQUEST
+//!             }
+//!             MailGuardError::RetryAfter(_) => StatusCode::TOO_MANY_REQUESTS,
+//!             _ => StatusCode::BAD_GATEWAY,
+//!         };
+//!         (status, err.to_string())
+//!     })
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let app = Router::new()
+//!         .route("/v1/email/{email}", get(check))
+//!         .with_state(Arc::new(MailGuard::new()));
+//!
+//!     let listener = tokio::net::TcpListener::bind("127.0.0.1:8025").await?;
+//!     axum::serve(listener, app).await?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! # MTA policy hook
+//!
+//! A Postfix [policy delegation] server: Postfix sends `name=value`
+//! attributes terminated by an empty line and expects an `action=` reply.
+//! Enable it with `check_policy_service inet:127.0.0.1:10040` in
+//! `smtpd_sender_restrictions`.
+//!
+//! [policy delegation]: https://www.postfix.org/SMTPD_POLICY_README.html
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//!
+//! use mailguard_rs::MailGuard;
+//! use tokio::{
+//!     io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+//!     net::{TcpListener, TcpStream},
+//! };
+//!
+//! async fn decide(guard: &MailGuard, sender: &str) -> String {
+//!     match guard.check_email(sender).await {
+//!         Ok(status) if status.is_threat => {
+//!             "action=REJECT Sender domain is listed as disposable or malicious".to_string()
+//!         }
+//!         // Never bounce mail because a lookup failed or the sender is empty
+//!         _ => "action=DUNNO".to_string(),
+//!     }
+//! }
+//!
+//! async fn serve(guard: Arc<MailGuard>, stream: TcpStream) -> std::io::Result<()> {
+//!     let (reader, mut writer) = stream.into_split();
+//!     let mut lines = BufReader::new(reader).lines();
+//!     let mut sender = String::new();
+//!
+//!     while let Some(line) = lines.next_line().await? {
+//!         if let Some(value) = line.strip_prefix("sender=") {
+//!             sender = value.to_string();
+//!         } else if line.is_empty() {
+//!             let action = decide(&guard, &sender).await;
+//!             writer.write_all(format!("{action}\n\n").as_bytes()).await?;
+//!             sender.clear();
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> std::io::Result<()> {
+//!     let guard = Arc::new(MailGuard::new());
+//!     let listener = TcpListener::bind("127.0.0.1:10040").await?;
+//!
+//!     loop {
+//!         let (stream, _) = listener.accept().await?;
+//!         let guard = guard.clone();
+//!         tokio::spawn(async move {
+//!             if let Err(err) = serve(guard, stream).await {
+//!                 eprintln!("policy connection failed: {err}");
+//!             }
+//!         });
+//!     }
+//! }
+//! ```
@@ -0,0 +1,103 @@
+//! What a detector configuration can and cannot detect
+//!
+//! [`MailGuard::coverage`](crate::MailGuard::coverage) lists every check in
+//! the pipeline with the threat categories it can report, and points out
+//! known gaps, so operators can tell what their setup misses before a bad
+//! signup gets through.
+
+use serde::{Deserialize, Serialize};
+
+use crate::threat::ThreatType;
+
+/// Categories a check can assign, listed in reports
+pub const KNOWN_CATEGORIES: [ThreatType; 9] = [
+    ThreatType::Spam,
+    ThreatType::Phishing,
+    ThreatType::Malware,
+    ThreatType::Botnet,
+    ThreatType::Cracked,
+    ThreatType::Redirector,
+    ThreatType::AbusedLegit,
+    ThreatType::Pup,
+    ThreatType::Disposable,
+];
+
+/// Categories encoded in SURBL return codes
+pub const SURBL_CATEGORIES: [ThreatType; 8] = [
+    ThreatType::Spam,
+    ThreatType::Phishing,
+    ThreatType::Malware,
+    ThreatType::Botnet,
+    ThreatType::Cracked,
+    ThreatType::Redirector,
+    ThreatType::AbusedLegit,
+    ThreatType::Pup,
+];
+
+/// What a single check can detect
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckCoverage {
+    /// [`Check::name`](crate::Check::name) of the check
+    pub check: String,
+    /// Threat categories the check can report; empty if its threats are
+    /// uncategorized or it only reports safe domains
+    pub categories: Vec<ThreatType>,
+    /// Where the check's data comes from, e.g. a DNS zone or a list size
+    pub source: String,
+}
+
+impl CheckCoverage {
+    /// Coverage of a check without categories
+    pub fn new(check: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            check: check.into(),
+            categories: Vec::new(),
+            source: source.into(),
+        }
+    }
+
+    /// Set the categories the check can report
+    pub fn with_categories(mut self, categories: impl IntoIterator<Item = ThreatType>) -> Self {
+        self.categories = categories.into_iter().collect();
+        self
+    }
+}
+
+/// Coverage of a whole detector
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageReport {
+    /// Checks in the order they run
+    pub checks: Vec<CheckCoverage>,
+    /// Known categories that no check can report
+    pub missing_categories: Vec<ThreatType>,
+    /// Human-readable descriptions of what the configuration can't detect
+    pub gaps: Vec<String>,
+}
+
+impl CoverageReport {
+    /// Build a report from the pipeline's checks and configuration gaps
+    pub fn new(checks: Vec<CheckCoverage>, mut gaps: Vec<String>) -> Self {
+        let missing_categories: Vec<ThreatType> = KNOWN_CATEGORIES
+            .into_iter()
+            .filter(|category| {
+                !checks
+                    .iter()
+                    .any(|check| check.categories.contains(category))
+            })
+            .collect();
+
+        if !missing_categories.is_empty() {
+            let names: Vec<&str> = missing_categories
+                .iter()
+                .map(ThreatType::description)
+                .collect();
+            gaps.push(format!("No check reports: {}", names.join(", ")));
+        }
+
+        Self {
+            checks,
+            missing_categories,
+            gaps,
+        }
+    }
+}
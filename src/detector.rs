@@ -1,41 +1,159 @@
-use std::time::Duration;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    sync::{Arc, Mutex, OnceLock, RwLock},
+    time::{Duration, Instant, SystemTime},
+};
 
-use regex::Regex;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use tokio::{
+    runtime::Handle,
+    sync::Semaphore,
+    task::{AbortHandle, JoinSet},
+};
 
 use crate::{
-    cache::Cache,
-    dns::DnsClient,
+    address::EmailAddress,
+    aggregation::ThreatAggregation,
+    cache::{
+        Cache, CacheBackend, CacheHooks, CacheRecord, CachedVerdict, EmailCache, KeyLocks,
+        ShardStats, TtlCache, cache_key, from_records, to_records,
+    },
+    check::{
+        AllowlistCheck, BlocklistCheck, Check, CheckOutcome, CheckResult, DISPOSABLE_MX_HOSTS,
+        MxFingerprintCheck, RankCheck, SURBL, SurblCheck, TRUSTED_PROVIDERS, TrustedProviderCheck,
+    },
+    coverage::CoverageReport,
+    dns::{DnsClient, QueryType, ascii_domain},
+    ensemble::{Ensemble, EnsembleVote},
     error::{MailGuardError, Result},
-    threat::ThreatType,
+    lists::{DomainList, SharedDomainList},
+    normalize::{Subaddress, normalize_email, parse_subaddress},
+    rank::DomainRanks,
+    reason::{Reason, explain},
+    retry::{RetryKind, RetryPolicy},
+    scan::{ScanMatch, extract_emails},
+    summary::BatchSummary,
+    suspicion::{LocalPartSuspicion, SuspicionSignal, score_local_part},
+    threat::{LOW_CONFIDENCE, MAX_SEVERITY, ThreatInfo, ThreatType, confidence_of},
+    typo,
+    validator::EmailValidator,
 };
 
+/// Version of this crate, recorded in every status
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Entry of `sources_checked` standing for a cached verdict
+pub const CACHE_SOURCE: &str = "cache";
+
+/// Checks [`MailGuard::preload`] runs at once
+pub const PRELOAD_CONCURRENCY: usize = 32;
+
 /// Email detection status
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EmailStatus {
     /// Email address
     pub email: String,
     /// Domain
     pub domain: String,
+    /// Canonical form of the address (see [`normalize_email`])
+    pub normalized_email: String,
+    /// Subaddress tag, if the local part uses one (`user+tag@`)
+    pub subaddress: Option<Subaddress>,
+    /// How machine-generated the local part looks, when
+    /// [`MailGuardConfig::score_local_parts`] is set
+    pub local_part_suspicion: Option<LocalPartSuspicion>,
     /// Whether it's a temporary email or malicious domain
     pub is_threat: bool,
     /// Threat type (if exists)
     pub threat_type: Option<ThreatType>,
+    /// Every threat reported for the domain, with its evidence; see
+    /// [`DomainStatus::threats`]
+    #[serde(default)]
+    pub threats: Vec<ThreatInfo>,
+    /// How much the verdict can be trusted, from 0 to 1; see
+    /// [`DomainStatus::confidence`]
+    ///
+    /// A domain with nothing against it whose local part looks
+    /// machine-generated gets [`LOW_CONFIDENCE`].
+    #[serde(default)]
+    pub confidence: f32,
     /// Whether from cache
     pub from_cache: bool,
+    /// Major provider the domain likely mistypes or imitates (e.g. `gmail.com`
+    /// for `gmial.com`), when typo detection is enabled
+    pub typo_of: Option<String>,
+    /// Rank of the domain on the configured top-sites list, `None` if it is
+    /// unranked or no list is configured
+    pub rank: Option<u32>,
+    /// Outcomes of the checks that ran, in order
+    pub check_results: Vec<CheckResult>,
+    /// Breakdown of the ensemble vote, if one is configured and its members
+    /// ran
+    pub ensemble: Option<EnsembleVote>,
+    /// Evidence behind the verdict, in check order
+    pub reasons: Vec<Reason>,
+    /// When the status was produced
+    ///
+    /// Statuses from the email cache keep the time of the original check.
+    pub checked_at: SystemTime,
+    /// Names of the checks that ran, followed by [`CACHE_SOURCE`] when a
+    /// cached verdict stood in for the rest
+    pub sources_checked: Vec<String>,
+    /// Version of this crate that produced the status ([`CRATE_VERSION`])
+    pub crate_version: String,
 }
 
 /// Domain detection status
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DomainStatus {
     /// Domain
     pub domain: String,
     /// Whether it's a malicious domain
     pub is_threat: bool,
-    /// Threat type (if exists)
+    /// Threat type (if exists), from the check that decided
     pub threat_type: Option<ThreatType>,
+    /// Every threat the checks that ran reported, with the list and answer
+    /// behind it, most severe first
+    ///
+    /// Sources can disagree, and one list answer can carry several
+    /// categories; with [`MailGuardConfig::short_circuit`] off, every check
+    /// gets its say. See [`DomainStatus::most_severe_threat`].
+    #[serde(default)]
+    pub threats: Vec<ThreatInfo>,
+    /// How much the verdict can be trusted, from 0 to 1: the highest
+    /// [`confidence_of`] the checks that reported a threat, `0` if none did
+    ///
+    /// Lets risk engines weigh a direct listing above a heuristic match.
+    #[serde(default)]
+    pub confidence: f32,
     /// Whether from cache
     pub from_cache: bool,
+    /// Major provider the domain likely mistypes or imitates, when typo
+    /// detection is enabled
+    pub typo_of: Option<String>,
+    /// Rank of the domain on the configured top-sites list, `None` if it is
+    /// unranked or no list is configured
+    pub rank: Option<u32>,
+    /// Outcomes of the checks that ran, in order
+    pub check_results: Vec<CheckResult>,
+    /// Breakdown of the ensemble vote, if one is configured and its members
+    /// ran
+    pub ensemble: Option<EnsembleVote>,
+    /// Evidence behind the verdict, in check order
+    pub reasons: Vec<Reason>,
+    /// When the status was produced
+    ///
+    /// Statuses from the email cache keep the time of the original check.
+    pub checked_at: SystemTime,
+    /// Names of the checks that ran, followed by [`CACHE_SOURCE`] when a
+    /// cached verdict stood in for the rest
+    pub sources_checked: Vec<String>,
+    /// Version of this crate that produced the status ([`CRATE_VERSION`])
+    pub crate_version: String,
 }
 
 /// Email detector configuration
@@ -46,7 +164,177 @@ pub struct MailGuardConfig {
     /// 是否启用缓存
     pub enable_cache: bool,
     /// 缓存 TTL
+    ///
+    /// Applies to safe verdicts, and to threats when the TTLs below are
+    /// `None`.
     pub cache_ttl: Duration,
+    /// TTL of cached threat verdicts
+    ///
+    /// Listings change slowly, so they can be trusted longer than safe
+    /// verdicts, which should notice a newly listed domain soon.
+    pub threat_cache_ttl: Option<Duration>,
+    /// TTL of cached verdicts from return codes the crate doesn't recognize
+    /// ([`ThreatType::Unknown`]), which are kept briefly in case they were
+    /// a provider glitch
+    pub uncertain_cache_ttl: Option<Duration>,
+    /// Cache TTLs by check name, overriding the ones above for every
+    /// verdict of that check
+    ///
+    /// Each check's outcome is cached on its own, under
+    /// [`cache_key`](crate::cache::cache_key), e.g. `surbl:example.com` and
+    /// `mx_fingerprint:example.com`.
+    pub check_cache_ttls: HashMap<String, Duration>,
+    /// How long a DNS failure for a domain is cached, `None` to retry on
+    /// every check
+    ///
+    /// During a resolver outage every check of a failing domain would hit
+    /// the resolver again; with this set, repeat checks fail right away with
+    /// [`MailGuardError::CachedFailure`] until the TTL passes. Only
+    /// transient failures (DNS errors and throttling) are cached. Needs
+    /// `enable_cache` and the `cache` feature.
+    pub failure_cache_ttl: Option<Duration>,
+    /// TTL of the email-level cache, `None` to disable it
+    ///
+    /// This second cache is keyed by normalized address and stores the full
+    /// [`EmailStatus`], for workloads that check the same addresses again
+    /// and again. It is independent of `enable_cache` and, like the domain
+    /// cache, needs the `cache` feature.
+    pub email_cache_ttl: Option<Duration>,
+    /// Most entries each cache holds, `None` for no limit
+    ///
+    /// Applies to the domain and the email cache alike. A full cache evicts
+    /// its least recently used entry, so a flood of unique domains can't
+    /// grow memory without bound.
+    pub cache_max_entries: Option<usize>,
+    /// Spread cache TTLs randomly by up to this fraction either way, e.g.
+    /// `0.1` for ±10%
+    ///
+    /// Entries stored by one large batch otherwise all expire together, and
+    /// the next batch sends the whole lot to SURBL at once.
+    pub cache_ttl_jitter: f64,
+    /// Approximate memory each cache may use in bytes, `None` for no limit
+    ///
+    /// Entry counts translate poorly to container memory limits, since
+    /// email-cache entries are much larger than domain verdicts. Each entry
+    /// is weighed when stored, and a cache over its budget evicts its least
+    /// recently used entries. See [`MailGuard::cache_memory_usage`].
+    pub cache_max_memory_bytes: Option<usize>,
+    /// Independently locked shards per cache, `None` to size them from the
+    /// bounds above (at most 16)
+    ///
+    /// More shards spread lock contention in services doing tens of
+    /// thousands of checks per second; see [`MailGuard::cache_shard_stats`].
+    pub cache_shards: Option<usize>,
+    /// Sliding expiration for the built-in caches: reading an entry keeps
+    /// it for another `cache_ttl` (`email_cache_ttl` for addresses), up to
+    /// this age, `None` to let entries expire on schedule
+    ///
+    /// Keeps hot domains such as gmail.com from being re-queried every
+    /// `cache_ttl`, while idle ones still expire.
+    pub cache_max_age: Option<Duration>,
+    /// Callbacks for activity in the built-in domain cache
+    ///
+    /// Not called for a [`MailGuardBuilder::cache_backend`], which can
+    /// report its own activity.
+    pub cache_hooks: CacheHooks,
+    /// Sweep expired entries out of the caches this often, `None` to only
+    /// drop them when they are read or on [`MailGuard::cleanup_cache`]
+    ///
+    /// The sweep runs as a Tokio task spawned when the detector is created,
+    /// and stops when it is dropped.
+    pub cache_sweep_interval: Option<Duration>,
+    /// Domains always reported as safe, without a DNS query
+    ///
+    /// Entries also cover their subdomains and may use `*` wildcards or a
+    /// `regex:` prefix (see [`DomainList`]). Takes precedence over `blocklist`.
+    pub allowlist: HashSet<String>,
+    /// Domains always reported as threats, without a DNS query
+    ///
+    /// Same rule syntax as `allowlist`.
+    pub blocklist: HashSet<String>,
+    /// Major mail providers reported as safe, without a DNS query
+    ///
+    /// Defaults to [`TRUSTED_PROVIDERS`]; replace it to trust other
+    /// providers, or clear it to query SURBL for every domain. Same rule
+    /// syntax as `allowlist`, but checked after `blocklist`.
+    pub trusted_providers: HashSet<String>,
+    /// Flag domains that look like typos of major mail providers
+    pub detect_typos: bool,
+    /// Score local parts for signs of being machine-generated (see
+    /// [`EmailStatus::local_part_suspicion`]); never affects `is_threat`
+    pub score_local_parts: bool,
+    /// Address syntax rules replacing the built-in RFC 5322 parser
+    ///
+    /// Addresses it rejects fail with [`MailGuardError::InvalidEmail`]; the
+    /// domain is validated either way.
+    pub email_validator: Option<EmailValidator>,
+    /// Top-sites list whose ranks are reported in every status
+    pub domain_ranks: Option<Arc<DomainRanks>>,
+    /// Report domains ranked this high or better on `domain_ranks` as safe,
+    /// without a DNS query
+    pub trusted_rank: Option<u32>,
+    /// Threat types for return codes this crate doesn't know yet
+    ///
+    /// Keyed by the last octet of the `127.0.0.x` answer. Only applied to
+    /// answers that would otherwise be [`ThreatType::Unknown`].
+    pub octet_overrides: HashMap<u8, ThreatType>,
+    /// Record types queried to decide whether a domain is listed
+    pub query_type: QueryType,
+    /// Parent domains queried on SURBL when the exact domain isn't listed
+    ///
+    /// With `2`, `user@a.b.evil.com` also queries `b.evil.com` and then
+    /// `evil.com`, stopping at the first listing. Lookups never go above
+    /// the registrable domain (see [`suffix`](crate::suffix)). The default
+    /// of `0` queries only the exact domain.
+    pub parent_lookup_depth: usize,
+    /// Flag domains whose MX hosts belong to a known disposable-mail backend
+    ///
+    /// Costs one MX query per domain that SURBL doesn't decide. Matches are
+    /// reported as [`ThreatType::Disposable`].
+    pub detect_disposable_mx: bool,
+    /// MX hosts flagged in addition to [`DISPOSABLE_MX_HOSTS`]
+    ///
+    /// Same rule syntax as `allowlist`.
+    pub disposable_mx_hosts: HashSet<String>,
+    /// Names of checks to run first, in this order
+    ///
+    /// Checks not named here run afterwards in their default order: the
+    /// built-in checks, then custom checks in the order they were added.
+    pub check_order: Vec<String>,
+    /// Stop at the first check that reaches a verdict
+    ///
+    /// When `false`, every check runs and all outcomes are reported in
+    /// `check_results`; the first verdict in order still decides. Cached
+    /// results are reused either way.
+    pub short_circuit: bool,
+    /// Lowest [`severity`](Self::severity) that makes `is_threat` true
+    ///
+    /// Listings below it are still reported in `threat_type`, but don't
+    /// count as threats; e.g. `3` lets Spam (2) and PUP (1) listings through
+    /// while Phishing and Malware still block. Threats without a type, such
+    /// as blocklist hits, always block. The default of `1` blocks everything.
+    pub min_blocking_severity: u8,
+    /// Severity levels replacing [`ThreatType::severity_level`] for some
+    /// categories, e.g. `Spam => 4` for an organization that treats spam
+    /// listings as serious
+    ///
+    /// Used wherever severity counts: against `min_blocking_severity` and
+    /// to order `threats`. Levels must be between 1 and [`MAX_SEVERITY`].
+    pub severity_overrides: HashMap<ThreatType, u8>,
+    /// Let the named checks vote instead of any one of them deciding
+    ///
+    /// The vote is taken once the last member has run and then counts as
+    /// that check's outcome; checks that are not members still decide on
+    /// their own, in order. Member outcomes stay listed in `check_results`.
+    pub ensemble: Option<Ensemble>,
+    /// How `threat_type` is chosen when several checks report a threat
+    /// (see [`aggregation`](crate::aggregation)); only matters with
+    /// `short_circuit` off
+    pub threat_aggregation: ThreatAggregation,
+    /// Retry checks that fail transiently (see [`retry`](crate::retry))
+    ///
+    /// Without a policy, the first error of any check fails the lookup.
+    pub retry_policy: Option<RetryPolicy>,
 }
 
 impl Default for MailGuardConfig {
@@ -55,17 +343,407 @@ impl Default for MailGuardConfig {
             dns_timeout: Duration::from_secs(5),
             enable_cache: true,
             cache_ttl: Duration::from_secs(300), // 5分钟
+            threat_cache_ttl: Some(Duration::from_secs(3600)),
+            uncertain_cache_ttl: Some(Duration::from_secs(60)),
+            check_cache_ttls: HashMap::new(),
+            failure_cache_ttl: None,
+            email_cache_ttl: None,
+            cache_max_entries: Some(100_000),
+            cache_ttl_jitter: 0.0,
+            cache_max_memory_bytes: None,
+            cache_shards: None,
+            cache_max_age: None,
+            cache_hooks: CacheHooks::new(),
+            cache_sweep_interval: None,
+            allowlist: HashSet::new(),
+            blocklist: HashSet::new(),
+            trusted_providers: TRUSTED_PROVIDERS
+                .iter()
+                .map(|provider| provider.to_string())
+                .collect(),
+            detect_typos: false,
+            score_local_parts: false,
+            email_validator: None,
+            domain_ranks: None,
+            trusted_rank: None,
+            octet_overrides: HashMap::new(),
+            parent_lookup_depth: 0,
+            query_type: QueryType::default(),
+            detect_disposable_mx: false,
+            disposable_mx_hosts: HashSet::new(),
+            check_order: Vec::new(),
+            short_circuit: true,
+            min_blocking_severity: 1,
+            severity_overrides: HashMap::new(),
+            ensemble: None,
+            threat_aggregation: ThreatAggregation::default(),
+            retry_policy: None,
+        }
+    }
+}
+
+impl MailGuardConfig {
+    /// Severity of `threat_type` under this configuration: its entry in
+    /// `severity_overrides`, or [`ThreatType::severity_level`]
+    pub fn severity(&self, threat_type: &ThreatType) -> u8 {
+        severity(threat_type, &self.severity_overrides)
+    }
+
+    /// Reject contradictory or unusable settings
+    ///
+    /// Checks for a zero DNS timeout, zero cache TTLs with caching enabled,
+    /// invalid list patterns, domains present in both the allowlist and the
+    /// blocklist, overrides for return codes that aren't listings, and
+    /// severity levels out of range.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| Err(MailGuardError::InvalidConfig(reason));
+
+        if self.dns_timeout.is_zero() {
+            return invalid("dns_timeout must be greater than zero".to_string());
+        }
+
+        if self.enable_cache && self.cache_ttl.is_zero() {
+            return invalid(
+                "cache_ttl must be greater than zero when caching is enabled".to_string(),
+            );
+        }
+
+        if self.threat_cache_ttl.is_some_and(|ttl| ttl.is_zero()) {
+            return invalid("threat_cache_ttl must be greater than zero".to_string());
+        }
+
+        if self.uncertain_cache_ttl.is_some_and(|ttl| ttl.is_zero()) {
+            return invalid("uncertain_cache_ttl must be greater than zero".to_string());
+        }
+
+        if let Some(check) = self
+            .check_cache_ttls
+            .iter()
+            .find_map(|(check, ttl)| ttl.is_zero().then_some(check))
+        {
+            return invalid(format!(
+                "check_cache_ttls entry for {check} must be greater than zero"
+            ));
+        }
+
+        if self.failure_cache_ttl.is_some_and(|ttl| ttl.is_zero()) {
+            return invalid("failure_cache_ttl must be greater than zero".to_string());
+        }
+
+        if self.email_cache_ttl.is_some_and(|ttl| ttl.is_zero()) {
+            return invalid("email_cache_ttl must be greater than zero".to_string());
+        }
+
+        if self
+            .cache_sweep_interval
+            .is_some_and(|interval| interval.is_zero())
+        {
+            return invalid("cache_sweep_interval must be greater than zero".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.cache_ttl_jitter) {
+            return invalid("cache_ttl_jitter must be between 0.0 and 1.0".to_string());
+        }
+
+        if self.cache_max_entries == Some(0) {
+            return invalid("cache_max_entries must be greater than zero".to_string());
+        }
+
+        if self.cache_max_memory_bytes == Some(0) {
+            return invalid("cache_max_memory_bytes must be greater than zero".to_string());
+        }
+
+        if self.cache_shards == Some(0) {
+            return invalid("cache_shards must be greater than zero".to_string());
+        }
+
+        if self.cache_max_age.is_some_and(|age| age.is_zero()) {
+            return invalid("cache_max_age must be greater than zero".to_string());
+        }
+
+        DomainList::try_new(&self.allowlist)?;
+        DomainList::try_new(&self.blocklist)?;
+        DomainList::try_new(&self.trusted_providers)?;
+        DomainList::try_new(&self.disposable_mx_hosts)?;
+
+        let allowed: HashSet<String> = self
+            .allowlist
+            .iter()
+            .map(|domain| domain.trim().to_lowercase())
+            .collect();
+        let mut overlap: Vec<&str> = self
+            .blocklist
+            .iter()
+            .map(|domain| domain.trim())
+            .filter(|domain| allowed.contains(&domain.to_lowercase()))
+            .collect();
+        if !overlap.is_empty() {
+            overlap.sort_unstable();
+            return invalid(format!(
+                "domains in both allowlist and blocklist: {}",
+                overlap.join(", ")
+            ));
+        }
+
+        if let Some(octet) = self.octet_overrides.keys().find(|octet| **octet <= 1) {
+            return invalid(format!(
+                "octet_overrides cannot remap 127.0.0.{octet}, it is not a listing"
+            ));
+        }
+
+        if self.min_blocking_severity > MAX_SEVERITY {
+            return invalid(format!(
+                "min_blocking_severity must be at most {MAX_SEVERITY}, or nothing would block"
+            ));
+        }
+
+        let mut out_of_range: Vec<String> = self
+            .severity_overrides
+            .iter()
+            .filter(|(_, level)| !(1..=MAX_SEVERITY).contains(*level))
+            .map(|(threat_type, level)| format!("{threat_type} => {level}"))
+            .collect();
+        if !out_of_range.is_empty() {
+            out_of_range.sort_unstable();
+            return invalid(format!(
+                "severity_overrides must be between 1 and {MAX_SEVERITY}: {}",
+                out_of_range.join(", ")
+            ));
+        }
+
+        if self.trusted_rank.is_some() && self.domain_ranks.is_none() {
+            return invalid("trusted_rank needs domain_ranks".to_string());
+        }
+
+        if let Some(ensemble) = &self.ensemble {
+            ensemble.validate()?;
+        }
+
+        if let Some(policy) = &self.retry_policy {
+            policy.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// How long a check's cached verdict is kept: `None` for a pass, or
+    /// the threat type it reported
+    ///
+    /// An entry in `check_cache_ttls` for the check wins over the TTLs by
+    /// verdict.
+    pub fn verdict_ttl(&self, check: &str, verdict: Option<&ThreatType>) -> Duration {
+        if let Some(ttl) = self.check_cache_ttls.get(check) {
+            return *ttl;
+        }
+        match verdict {
+            None => self.cache_ttl,
+            Some(ThreatType::Unknown(_)) => self.uncertain_cache_ttl.unwrap_or(self.cache_ttl),
+            Some(_) => self.threat_cache_ttl.unwrap_or(self.cache_ttl),
         }
     }
 }
 
 /// 主要的邮箱检测器
 pub struct MailGuard {
-    dns_client: DnsClient,
-    cache: Option<Cache>,
-    email_regex: Regex,
-    #[allow(dead_code)]
+    /// Shared with the cache sweeper, which only holds a weak reference
+    pipeline: Arc<RwLock<Arc<Pipeline>>>,
+    /// Client passed in by the caller, kept across reloads
+    dns_client: Option<DnsClient>,
+    /// Domain cache passed in by the caller, kept across reloads
+    cache_backend: Option<Arc<dyn CacheBackend>>,
+    extra_checks: Vec<Arc<dyn Check>>,
+    /// Task sweeping expired cache entries, if `cache_sweep_interval` is set
+    sweeper: Mutex<Option<AbortHandle>>,
+}
+
+/// Everything a configuration determines, swapped as a whole on reload
+///
+/// Each check holds on to the pipeline it started with, so a reload never
+/// changes the rules under a running check.
+struct Pipeline {
+    cache: Option<Arc<dyn CacheBackend>>,
+    /// Held while a check computes a verdict missing from `cache`
+    cache_locks: Arc<KeyLocks>,
+    email_cache: Option<EmailCache>,
+    /// Recent transient failures by domain
+    failure_cache: Option<TtlCache<MailGuardError>>,
+    checks: Vec<Arc<dyn Check>>,
+    allowlist: SharedDomainList,
+    blocklist: SharedDomainList,
+    /// Providers never reported as typos
+    trusted_providers: DomainList,
+    config: MailGuardConfig,
+}
+
+/// Builder for a [`MailGuard`] with custom checks
+///
+/// ```rust
+/// use mailguard_rs::{Fixture, MailGuard, MailGuardConfig, dns::DnsClient};
+///
+/// let detector = MailGuard::builder()
+///     .config(MailGuardConfig {
+///         detect_typos: true,
+///         ..Default::default()
+///     })
+///     .dns_client(DnsClient::playback(Fixture::new()))
+///     .build()?;
+/// # Ok::<(), mailguard_rs::MailGuardError>(())
+/// ```
+#[derive(Default)]
+pub struct MailGuardBuilder {
     config: MailGuardConfig,
+    dns_client: Option<DnsClient>,
+    cache_backend: Option<Arc<dyn CacheBackend>>,
+    checks: Vec<Box<dyn Check>>,
+}
+
+impl MailGuardBuilder {
+    /// Start from the default configuration and the built-in checks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use a custom configuration
+    pub fn config(mut self, config: MailGuardConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Query through the given DNS client instead of one built from the
+    /// configuration
+    pub fn dns_client(mut self, dns_client: DnsClient) -> Self {
+        self.dns_client = Some(dns_client);
+        self
+    }
+
+    /// Keep domain verdicts in `backend` instead of the in-memory [`Cache`]
+    ///
+    /// Only used while `enable_cache` is set; entries are stored for
+    /// `cache_ttl`.
+    pub fn cache_backend(mut self, backend: impl CacheBackend + 'static) -> Self {
+        self.cache_backend = Some(Arc::new(backend));
+        self
+    }
+
+    /// Append a check, run after the built-in checks
+    pub fn with_check(mut self, check: impl Check + 'static) -> Self {
+        self.checks.push(Box::new(check));
+        self
+    }
+
+    /// Build the detector, failing if the configuration is invalid or
+    /// `check_order` names a check that doesn't exist
+    pub fn build(self) -> Result<MailGuard> {
+        let extra_checks: Vec<Arc<dyn Check>> = self.checks.into_iter().map(Arc::from).collect();
+        let pipeline = Pipeline::build(
+            self.config,
+            self.dns_client.as_ref(),
+            self.cache_backend.as_ref(),
+            &extra_checks,
+            None,
+        )?;
+        Ok(MailGuard::start(
+            pipeline,
+            self.dns_client,
+            self.cache_backend,
+            extra_checks,
+        ))
+    }
+}
+
+impl Drop for MailGuard {
+    fn drop(&mut self) {
+        let sweeper = self.sweeper.get_mut().unwrap_or_else(|e| e.into_inner());
+        if let Some(sweeper) = sweeper.take() {
+            sweeper.abort();
+        }
+    }
+}
+
+/// Detector behind [`MailGuard::global`]
+static GLOBAL: OnceLock<MailGuard> = OnceLock::new();
+
+fn default_dns_client(config: &MailGuardConfig) -> DnsClient {
+    DnsClient::with_timeout(config.dns_timeout).with_query_type(config.query_type)
+}
+
+/// The outcome a cached verdict of `check` stands for, and the threats it
+/// reported
+fn cached_outcome(check: &str, cached: &CachedVerdict) -> (CheckOutcome, Vec<ThreatInfo>) {
+    let outcome = match cached.threat_type() {
+        Some(threat_type) => CheckOutcome::Threat(Some(threat_type.clone())),
+        None => CheckOutcome::Pass,
+    };
+    (outcome, cached.threats(check))
+}
+
+/// Look a domain up in the cache, counting backend errors as misses
+async fn cache_lookup(cache: &dyn CacheBackend, key: &str) -> Option<CachedVerdict> {
+    match cache.get(key).await {
+        Ok(cached) => cached,
+        Err(err) => {
+            tracing::warn!("Cache lookup for {key} failed: {err}");
+            None
+        }
+    }
+}
+
+fn bounded<V: Clone>(mut cache: TtlCache<V>, config: &MailGuardConfig) -> TtlCache<V> {
+    if let Some(max_entries) = config.cache_max_entries {
+        cache = cache.with_max_entries(max_entries);
+    }
+    if let Some(max_bytes) = config.cache_max_memory_bytes {
+        cache = cache.with_max_memory_bytes(max_bytes);
+    }
+    if let Some(shards) = config.cache_shards {
+        cache = cache.with_shards(shards);
+    }
+    if let Some(max_age) = config.cache_max_age {
+        cache = cache.with_sliding_expiration(max_age);
+    }
+    cache
+}
+
+/// `ttl` moved randomly by up to `jitter` of itself either way
+fn jittered(ttl: Duration, jitter: f64) -> Duration {
+    if jitter == 0.0 {
+        return ttl;
+    }
+    ttl.mul_f64(1.0 + rand::thread_rng().gen_range(-jitter..=jitter))
+}
+
+/// Rough heap size of a cached status, for the email cache's memory bound
+fn status_heap_size(status: &EmailStatus) -> usize {
+    let strings = status.email.len()
+        + status.domain.len()
+        + status.normalized_email.len()
+        + status.typo_of.as_ref().map_or(0, String::len)
+        + status.crate_version.len()
+        + status.subaddress.as_ref().map_or(0, |subaddress| {
+            subaddress.base_address.len() + subaddress.tag.len()
+        });
+    let results: usize = status
+        .check_results
+        .iter()
+        .map(|result| size_of::<CheckResult>() + result.check.len())
+        .sum();
+    let votes = status.ensemble.as_ref().map_or(0, |ensemble| {
+        ensemble
+            .votes
+            .iter()
+            .map(|vote| size_of_val(vote) + vote.check.len())
+            .sum()
+    });
+    let sources: usize = status
+        .sources_checked
+        .iter()
+        .map(|source| size_of::<String>() + source.len())
+        .sum();
+    let signals = status.local_part_suspicion.as_ref().map_or(0, |suspicion| {
+        suspicion.signals.len() * size_of::<SuspicionSignal>()
+    });
+
+    strings + results + votes + sources + signals + status.reasons.len() * size_of::<Reason>()
 }
 
 impl MailGuard {
@@ -75,141 +753,1258 @@ impl MailGuard {
     }
 
     /// 使用自定义配置创建检测器
+    ///
+    /// An invalid configuration (see [`MailGuardConfig::validate`]) is logged
+    /// as an error; use [`MailGuard::try_with_config`] to reject it instead.
     pub fn with_config(config: MailGuardConfig) -> Self {
-        let dns_client = DnsClient::with_timeout(config.dns_timeout);
-        let cache = if config.enable_cache {
-            Some(Cache::with_ttl(config.cache_ttl))
-        } else {
-            None
-        };
+        Self::logging_errors(config, None)
+    }
 
-        // 邮箱格式验证正则表达式
-        let email_regex = Regex::new(
-            r"^[a-zA-Z0-9.!#$%&'*+/=?^_`{|}~-]+@[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?(?:\.[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?)*$"
-        ).expect("Invalid email regex");
+    /// Create a detector, failing if the configuration is invalid
+    pub fn try_with_config(config: MailGuardConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self::with_config(config))
+    }
 
-        Self {
-            dns_client,
-            cache,
-            email_regex,
-            config,
+    /// Create a detector that queries through the given DNS client
+    ///
+    /// Use with [`DnsClient::playback`] to run checks against recorded
+    /// answers. `config.dns_timeout` and `config.query_type` are ignored; the
+    /// client's own settings apply.
+    pub fn with_dns_client(config: MailGuardConfig, dns_client: DnsClient) -> Self {
+        Self::logging_errors(config, Some(dns_client))
+    }
+
+    /// Create a detector with the built-in checks, logging configuration
+    /// errors instead of failing
+    fn logging_errors(config: MailGuardConfig, dns_client: Option<DnsClient>) -> Self {
+        if let Err(err) = config.validate() {
+            tracing::error!("{err}");
+        }
+        let client = dns_client
+            .clone()
+            .unwrap_or_else(|| default_dns_client(&config));
+        let (pipeline, unknown) = Pipeline::assemble(config, client, None, &[], None);
+        for name in unknown {
+            tracing::error!("check_order names unknown check {name}");
         }
+        for name in pipeline.unknown_ensemble_members() {
+            tracing::error!("ensemble weights name unknown check {name}");
+        }
+        for name in pipeline.unknown_precedence_checks() {
+            tracing::error!("threat_aggregation precedence names unknown check {name}");
+        }
+        Self::start(pipeline, dns_client, None, Vec::new())
     }
 
-    /// 检查单个邮箱地址
-    pub async fn check_email(&self, email: &str) -> Result<EmailStatus> {
-        // 验证邮箱格式
-        if !self.email_regex.is_match(email) {
-            return Err(MailGuardError::InvalidEmail(email.to_string()));
+    /// Wrap a pipeline and start the cache sweeper it asks for
+    fn start(
+        pipeline: Pipeline,
+        dns_client: Option<DnsClient>,
+        cache_backend: Option<Arc<dyn CacheBackend>>,
+        extra_checks: Vec<Arc<dyn Check>>,
+    ) -> Self {
+        let interval = pipeline.config.cache_sweep_interval;
+        let detector = Self {
+            pipeline: Arc::new(RwLock::new(Arc::new(pipeline))),
+            dns_client,
+            cache_backend,
+            extra_checks,
+            sweeper: Mutex::new(None),
+        };
+        detector.restart_sweeper(interval);
+        detector
+    }
+
+    /// Replace the sweeper with one running every `interval`, or none
+    ///
+    /// Needs a Tokio runtime to spawn onto; without one, expired entries
+    /// are only dropped lazily.
+    fn restart_sweeper(&self, interval: Option<Duration>) {
+        let mut sweeper = self.sweeper.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = sweeper.take() {
+            previous.abort();
         }
+        let Some(interval) = interval else {
+            return;
+        };
+        let Ok(runtime) = Handle::try_current() else {
+            tracing::warn!(
+                "cache_sweep_interval is set, but there is no Tokio runtime to sweep on"
+            );
+            return;
+        };
 
-        // 提取域名
-        let domain = self.extract_domain(email)?;
+        let pipeline = Arc::downgrade(&self.pipeline);
+        let task = runtime.spawn(async move {
+            let mut ticks =
+                tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            loop {
+                ticks.tick().await;
+                // The detector is gone
+                let Some(pipeline) = pipeline.upgrade() else {
+                    break;
+                };
+                let current = pipeline.read().unwrap_or_else(|e| e.into_inner()).clone();
+                current.cleanup_expired();
+            }
+        });
+        *sweeper = Some(task.abort_handle());
+    }
 
-        // 检查域名
-        let domain_status = self.check_domain(&domain).await?;
-
-        Ok(EmailStatus {
-            email: email.to_string(),
-            domain: domain_status.domain,
-            is_threat: domain_status.is_threat,
-            threat_type: domain_status.threat_type,
-            from_cache: domain_status.from_cache,
-        })
+    /// Start building a detector with custom checks
+    pub fn builder() -> MailGuardBuilder {
+        MailGuardBuilder::new()
     }
 
-    /// 检查域名
-    pub async fn check_domain(&self, domain: &str) -> Result<DomainStatus> {
-        // 验证域名格式
-        self.dns_client.validate_domain(domain)?;
+    /// The process-wide detector used by [`crate::check_email`] and the
+    /// other free functions
+    ///
+    /// Created with the default configuration on first use unless
+    /// [`MailGuard::set_global`] installed one before. Sharing it keeps one
+    /// resolver and one cache for the whole process; adjust it later with
+    /// [`MailGuard::reload`].
+    pub fn global() -> &'static MailGuard {
+        GLOBAL.get_or_init(MailGuard::new)
+    }
 
-        let domain = domain.to_lowercase();
+    /// Install the process-wide detector, e.g. configured once at startup
+    ///
+    /// Fails with [`MailGuardError::InvalidConfig`] if the global detector
+    /// was already installed or used.
+    pub fn set_global(detector: MailGuard) -> Result<()> {
+        GLOBAL.set(detector).map_err(|_| {
+            MailGuardError::InvalidConfig("the global detector is already initialized".to_string())
+        })
+    }
 
-        // 检查缓存
-        if let Some(cache) = &self.cache
-            && let Some(cached_threat) = cache.get(&domain)
-        {
-            return Ok(DomainStatus {
-                domain: domain.clone(),
-                is_threat: cached_threat.is_some(),
-                threat_type: cached_threat,
-                from_cache: true,
-            });
+    /// Switch to a new configuration without dropping in-flight checks
+    ///
+    /// Lists, provider settings and policy are swapped atomically: checks
+    /// already running finish under the old configuration, later ones use
+    /// the new one. Custom checks, a DNS client and a cache backend passed in
+    /// at construction are kept. Rules added at runtime are replaced by the
+    /// new lists.
+    ///
+    /// The domain cache survives unless `enable_cache` or `cache_ttl`
+    /// change, so a reload doesn't start cold; cached addresses are dropped,
+    /// since they embed verdicts of the old lists. Fails like
+    /// [`MailGuardBuilder::build`], leaving the current configuration in
+    /// place.
+    pub fn reload(&self, config: MailGuardConfig) -> Result<()> {
+        let current = self.pipeline();
+        let pipeline = Pipeline::build(
+            config,
+            self.dns_client.as_ref(),
+            self.cache_backend.as_ref(),
+            &self.extra_checks,
+            Some(&current),
+        )?;
+        let email_cache = pipeline.email_cache.clone();
+        let interval = pipeline.config.cache_sweep_interval;
+
+        *self.pipeline.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(pipeline);
+        if let Some(cache) = email_cache {
+            cache.clear();
         }
+        if interval != current.config.cache_sweep_interval {
+            self.restart_sweeper(interval);
+        }
+        Ok(())
+    }
 
-        // 执行 DNS 查询
-        let threat_type = self.dns_client.query_surbl(&domain).await?;
+    /// The pipeline checks started now run with
+    fn pipeline(&self) -> Arc<Pipeline> {
+        self.pipeline
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
 
-        // 更新缓存
-        if let Some(cache) = &self.cache {
-            cache.set(domain.clone(), threat_type.clone());
-        }
+    /// 检查单个邮箱地址
+    pub async fn check_email(&self, email: &str) -> Result<EmailStatus> {
+        self.pipeline()
+            .check_email_once(email, &mut HashMap::new())
+            .await
+    }
 
-        Ok(DomainStatus {
-            domain,
-            is_threat: threat_type.is_some(),
-            threat_type,
-            from_cache: false,
-        })
+    /// 检查域名
+    pub async fn check_domain(&self, domain: &str) -> Result<DomainStatus> {
+        self.pipeline().check_domain(domain).await
     }
 
     /// 批量检查邮箱
+    ///
+    /// Each distinct domain is checked once and its result shared by every
+    /// address at that domain.
     pub async fn check_emails_batch(&self, emails: &[&str]) -> Vec<Result<EmailStatus>> {
-        let mut results = Vec::with_capacity(emails.len());
+        self.check_emails(emails.iter().copied())
+            .await
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect()
+    }
+
+    /// 批量检查域名
+    ///
+    /// Repeated domains (compared case-insensitively) are checked once.
+    pub async fn check_domains_batch(&self, domains: &[&str]) -> Vec<Result<DomainStatus>> {
+        self.check_domains(domains.iter().copied())
+            .await
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect()
+    }
+
+    /// Check owned or borrowed addresses from any iterator, pairing each
+    /// result with its input
+    ///
+    /// Takes e.g. a `Vec<String>` straight from a database query. Distinct
+    /// domains are checked once, as in [`MailGuard::check_emails_batch`].
+    pub async fn check_emails<I, S>(&self, emails: I) -> Vec<(S, Result<EmailStatus>)>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let pipeline = self.pipeline();
+        let mut checked = HashMap::new();
+        let mut results = Vec::new();
 
         for email in emails {
-            let result = self.check_email(email).await;
-            results.push(result);
+            let result = pipeline
+                .check_email_once(email.as_ref(), &mut checked)
+                .await;
+            results.push((email, result));
         }
 
         results
     }
 
-    /// 批量检查域名
-    pub async fn check_domains_batch(&self, domains: &[&str]) -> Vec<Result<DomainStatus>> {
-        let mut results = Vec::with_capacity(domains.len());
+    /// Check owned or borrowed domains from any iterator, pairing each
+    /// result with its input
+    ///
+    /// Repeated domains (compared case-insensitively) are checked once.
+    pub async fn check_domains<I, S>(&self, domains: I) -> Vec<(S, Result<DomainStatus>)>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let pipeline = self.pipeline();
+        let mut checked = HashMap::new();
+        let mut results = Vec::new();
 
         for domain in domains {
-            let result = self.check_domain(domain).await;
-            results.push(result);
+            let result = pipeline
+                .check_domain_once(domain.as_ref(), &mut checked)
+                .await;
+            results.push((domain, result));
         }
 
         results
     }
 
-    /// 从邮箱地址提取域名
-    fn extract_domain(&self, email: &str) -> Result<String> {
-        if let Some(at_pos) = email.rfind('@') {
-            let domain = &email[at_pos + 1..];
-            if domain.is_empty() {
-                return Err(MailGuardError::InvalidEmail("邮箱域名为空".to_string()));
-            }
-            Ok(domain.to_string())
-        } else {
-            Err(MailGuardError::InvalidEmail("邮箱格式无效".to_string()))
+    /// Check addresses like [`MailGuard::check_emails`], also returning a
+    /// [`BatchSummary`] of the results
+    pub async fn check_emails_summarized<I, S>(
+        &self,
+        emails: I,
+    ) -> (Vec<(S, Result<EmailStatus>)>, BatchSummary)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let started = Instant::now();
+        let results = self.check_emails(emails).await;
+        let summary = BatchSummary::of_emails(&results, started.elapsed());
+        (results, summary)
+    }
+
+    /// Check domains like [`MailGuard::check_domains`], also returning a
+    /// [`BatchSummary`] of the results
+    pub async fn check_domains_summarized<I, S>(
+        &self,
+        domains: I,
+    ) -> (Vec<(S, Result<DomainStatus>)>, BatchSummary)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let started = Instant::now();
+        let results = self.check_domains(domains).await;
+        let summary = BatchSummary::of_domains(&results, started.elapsed());
+        (results, summary)
+    }
+
+    /// Find every address in a text and check it
+    ///
+    /// Matches come in order of appearance with their byte offsets (see
+    /// [`extract_emails`]). Distinct domains are checked once.
+    pub async fn scan_text(&self, text: &str) -> Vec<ScanMatch> {
+        self.check_emails(extract_emails(text))
+            .await
+            .into_iter()
+            .map(|(span, result)| ScanMatch { span, result })
+            .collect()
+    }
+
+    /// Report what this detector can detect and what it misses
+    pub fn coverage(&self) -> CoverageReport {
+        self.pipeline().coverage()
+    }
+
+    /// Add an allowlist rule, returning whether it was new
+    ///
+    /// Takes effect for the next check, including checks running on other
+    /// tasks. Accepts the same entries as
+    /// [`MailGuardConfig::allowlist`], and fails with
+    /// [`MailGuardError::InvalidRule`] on an invalid pattern.
+    pub fn add_allowlist_rule(&self, entry: &str) -> Result<bool> {
+        let pipeline = self.pipeline();
+        let added = pipeline.allowlist.insert(entry)?;
+        if added {
+            pipeline.rules_changed();
+        }
+        Ok(added)
+    }
+
+    /// Remove an allowlist rule, written as it was added, returning whether
+    /// it was present
+    pub fn remove_allowlist_rule(&self, entry: &str) -> bool {
+        let pipeline = self.pipeline();
+        let removed = pipeline.allowlist.remove(entry);
+        if removed {
+            pipeline.rules_changed();
         }
+        removed
+    }
+
+    /// Add a blocklist rule, returning whether it was new
+    ///
+    /// See [`MailGuard::add_allowlist_rule`]. With the default check order
+    /// the allowlist still wins for domains on both lists.
+    pub fn add_blocklist_rule(&self, entry: &str) -> Result<bool> {
+        let pipeline = self.pipeline();
+        let added = pipeline.blocklist.insert(entry)?;
+        if added {
+            pipeline.rules_changed();
+        }
+        Ok(added)
+    }
+
+    /// Remove a blocklist rule, written as it was added, returning whether
+    /// it was present
+    pub fn remove_blocklist_rule(&self, entry: &str) -> bool {
+        let pipeline = self.pipeline();
+        let removed = pipeline.blocklist.remove(entry);
+        if removed {
+            pipeline.rules_changed();
+        }
+        removed
     }
 
     /// 清理缓存中的过期条目
     pub fn cleanup_cache(&self) {
-        if let Some(cache) = &self.cache {
-            cache.cleanup_expired();
-        }
+        self.pipeline().cleanup_expired();
     }
 
     /// 获取缓存统计信息
+    ///
+    /// `None` with caching disabled, or when the cache backend can't tell.
     pub fn cache_stats(&self) -> Option<usize> {
-        self.cache.as_ref().map(|cache| cache.size())
+        self.pipeline()
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.size())
+    }
+
+    /// Number of entries in the email-level cache, if it is enabled
+    pub fn email_cache_stats(&self) -> Option<usize> {
+        self.pipeline()
+            .email_cache
+            .as_ref()
+            .map(|cache| cache.size())
+    }
+
+    /// Estimated memory held by the domain and email caches, in bytes
+    ///
+    /// `None` with both disabled; a domain cache backend that doesn't
+    /// track memory counts as zero.
+    pub fn cache_memory_usage(&self) -> Option<usize> {
+        let pipeline = self.pipeline();
+        if pipeline.cache.is_none() && pipeline.email_cache.is_none() {
+            return None;
+        }
+        let domains = pipeline
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.memory_usage())
+            .unwrap_or(0);
+        let emails = pipeline
+            .email_cache
+            .as_ref()
+            .map_or(0, |cache| cache.memory_usage());
+        Some(domains + emails)
+    }
+
+    /// Per-shard usage of the domain cache
+    ///
+    /// Empty with caching disabled, or with a cache backend that isn't
+    /// sharded.
+    pub fn cache_shard_stats(&self) -> Vec<ShardStats> {
+        self.pipeline()
+            .cache
+            .as_ref()
+            .map(|cache| cache.shard_stats())
+            .unwrap_or_default()
     }
 
     /// 清空缓存
     pub fn clear_cache(&self) {
+        let pipeline = self.pipeline();
+        if let Some(cache) = &pipeline.cache {
+            cache.clear();
+        }
+        if let Some(cache) = &pipeline.email_cache {
+            cache.clear();
+        }
+        if let Some(failures) = &pipeline.failure_cache {
+            failures.clear();
+        }
+    }
+
+    /// Drop the cached verdict for one domain, e.g. after overriding a
+    /// false positive
+    ///
+    /// Cached addresses at the domain are dropped too; everything else
+    /// stays cached.
+    pub async fn invalidate(&self, domain: &str) -> Result<()> {
+        let domain = ascii_domain(domain)?;
+        let pipeline = self.pipeline();
+        if let Some(cache) = &pipeline.email_cache {
+            cache.retain(|_, status| !status.domain.eq_ignore_ascii_case(&domain));
+        }
+        if let Some(failures) = &pipeline.failure_cache {
+            failures.remove(&domain);
+        }
+        if let Some(cache) = &pipeline.cache {
+            for check in pipeline.checks.iter().filter(|check| check.cacheable()) {
+                cache.remove(&cache_key(check.name(), &domain)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check domains concurrently to warm the cache, e.g. with the
+    /// highest-traffic domains at startup, returning how many were checked
+    ///
+    /// Up to [`PRELOAD_CONCURRENCY`] checks run at once. Domains that fail
+    /// to check are logged and skipped.
+    pub async fn preload<I, S>(&self, domains: I) -> usize
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let pipeline = self.pipeline();
+        let permits = Arc::new(Semaphore::new(PRELOAD_CONCURRENCY));
+        let mut seen = HashSet::new();
+        let mut tasks = JoinSet::new();
+
+        for domain in domains {
+            let domain = domain.as_ref().to_lowercase();
+            if !seen.insert(domain.clone()) {
+                continue;
+            }
+            let pipeline = pipeline.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await.ok()?;
+                match pipeline.check_domain(&domain).await {
+                    Ok(_) => Some(()),
+                    Err(err) => {
+                        tracing::warn!("Failed to preload {domain}: {err}");
+                        None
+                    }
+                }
+            });
+        }
+
+        let mut checked = 0;
+        while let Some(joined) = tasks.join_next().await {
+            if matches!(joined, Ok(Some(()))) {
+                checked += 1;
+            }
+        }
+        checked
+    }
+
+    /// Insert precomputed verdicts into the domain cache, returning how many
+    /// were added
+    ///
+    /// A verdict is `None` for a safe domain or the threat type of a listed
+    /// one. It is stored as the SURBL check's, and kept for its
+    /// [`MailGuardConfig::verdict_ttl`], jittered like checked verdicts.
+    /// With caching disabled nothing is added.
+    pub async fn preload_verdicts<I, S>(&self, verdicts: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = (S, Option<ThreatType>)>,
+        S: AsRef<str>,
+    {
+        let pipeline = self.pipeline();
+        let Some(cache) = &pipeline.cache else {
+            return Ok(0);
+        };
+
+        let mut added = 0;
+        for (domain, verdict) in verdicts {
+            let domain = ascii_domain(domain.as_ref())?;
+            let ttl = jittered(
+                pipeline.config.verdict_ttl(SURBL, verdict.as_ref()),
+                pipeline.config.cache_ttl_jitter,
+            );
+            cache
+                .set(&cache_key(SURBL, &domain), verdict.into(), ttl)
+                .await?;
+            added += 1;
+        }
+        Ok(added)
+    }
+
+    /// Dump the cached domain verdicts as a JSON array of [`CacheRecord`]s
+    ///
+    /// Shows what the detector has learned, and can be replayed elsewhere,
+    /// e.g. into staging, with [`MailGuard::import_cache_json`]. Empty with
+    /// caching disabled, or with a cache backend that can't list its
+    /// entries.
+    pub fn export_cache_json(&self) -> String {
+        serde_json::to_string_pretty(&self.cache_records()).expect("cache records serialize")
+    }
+
+    /// Add the verdicts of a [`MailGuard::export_cache_json`] dump, returning
+    /// how many were added
+    ///
+    /// Verdicts that expired since are skipped; the rest keep their
+    /// remaining lifetime. With caching disabled nothing is added.
+    pub async fn import_cache_json(&self, json: &str) -> Result<usize> {
+        let records: Vec<CacheRecord> =
+            serde_json::from_str(json).map_err(|err| MailGuardError::Storage(err.into()))?;
+        self.import_records(records).await
+    }
+
+    /// Save the cached domain verdicts to a JSON snapshot file, returning
+    /// how many were saved
+    ///
+    /// The file holds the same records as [`MailGuard::export_cache_json`].
+    /// Restore them with [`MailGuard::load_cache`], e.g. after a restart.
+    pub fn save_cache(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let records = self.cache_records();
+        let json = serde_json::to_vec(&records).expect("cache records serialize");
+        fs::write(path, json).map_err(MailGuardError::Storage)?;
+        Ok(records.len())
+    }
+
+    /// Pre-seed the domain cache from a snapshot written by
+    /// [`MailGuard::save_cache`], returning how many verdicts were added
+    ///
+    /// Skips expired verdicts like [`MailGuard::import_cache_json`].
+    pub async fn load_cache(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let json = fs::read_to_string(path).map_err(MailGuardError::Storage)?;
+        self.import_cache_json(&json).await
+    }
+
+    fn cache_records(&self) -> Vec<CacheRecord> {
+        let entries = self
+            .pipeline()
+            .cache
+            .as_ref()
+            .map(|cache| cache.entries())
+            .unwrap_or_default();
+        to_records(entries)
+    }
+
+    async fn import_records(&self, records: Vec<CacheRecord>) -> Result<usize> {
+        let pipeline = self.pipeline();
+        let Some(cache) = &pipeline.cache else {
+            return Ok(0);
+        };
+
+        let entries = from_records(records);
+        let now = Instant::now();
+        for (key, entry) in &entries {
+            let remaining = entry
+                .ttl
+                .saturating_sub(now.saturating_duration_since(entry.timestamp));
+            cache.set(key, entry.value.clone(), remaining).await?;
+        }
+        Ok(entries.len())
+    }
+}
+
+impl Pipeline {
+    /// Assemble a pipeline, failing if the configuration is invalid or
+    /// names checks that don't exist
+    ///
+    /// Caches of `previous` are reused when their settings are unchanged.
+    fn build(
+        config: MailGuardConfig,
+        dns_client: Option<&DnsClient>,
+        cache_backend: Option<&Arc<dyn CacheBackend>>,
+        extra_checks: &[Arc<dyn Check>],
+        previous: Option<&Pipeline>,
+    ) -> Result<Self> {
+        config.validate()?;
+        let dns_client = dns_client
+            .cloned()
+            .unwrap_or_else(|| default_dns_client(&config));
+        let (pipeline, unknown) =
+            Self::assemble(config, dns_client, cache_backend, extra_checks, previous);
+        if !unknown.is_empty() {
+            return Err(MailGuardError::InvalidConfig(format!(
+                "check_order names unknown checks: {}",
+                unknown.join(", ")
+            )));
+        }
+        let unknown = pipeline.unknown_ensemble_members();
+        if !unknown.is_empty() {
+            return Err(MailGuardError::InvalidConfig(format!(
+                "ensemble weights name unknown checks: {}",
+                unknown.join(", ")
+            )));
+        }
+        let unknown = pipeline.unknown_precedence_checks();
+        if !unknown.is_empty() {
+            return Err(MailGuardError::InvalidConfig(format!(
+                "threat_aggregation precedence names unknown checks: {}",
+                unknown.join(", ")
+            )));
+        }
+        Ok(pipeline)
+    }
+
+    /// Set up the cache and the built-in checks followed by `extra_checks`,
+    /// reordered by `config.check_order`
+    ///
+    /// Also returns the names in `check_order` that matched no check.
+    fn assemble(
+        config: MailGuardConfig,
+        dns_client: DnsClient,
+        cache_backend: Option<&Arc<dyn CacheBackend>>,
+        extra_checks: &[Arc<dyn Check>],
+        previous: Option<&Pipeline>,
+    ) -> (Self, Vec<String>) {
+        let cache: Option<Arc<dyn CacheBackend>> = match previous {
+            _ if !config.enable_cache => None,
+            _ if cache_backend.is_some() => cache_backend.cloned(),
+            Some(previous)
+                if previous.config.enable_cache == config.enable_cache
+                    && previous.config.cache_ttl == config.cache_ttl
+                    && previous.config.cache_max_entries == config.cache_max_entries
+                    && previous.config.cache_max_memory_bytes == config.cache_max_memory_bytes
+                    && previous.config.cache_shards == config.cache_shards
+                    && previous.config.cache_max_age == config.cache_max_age
+                    && previous.config.cache_hooks.same_as(&config.cache_hooks) =>
+            {
+                previous.cache.clone()
+            }
+            _ => Some(Arc::new(bounded(
+                Cache::with_ttl(config.cache_ttl)
+                    .with_weigher(CachedVerdict::heap_size)
+                    .with_hooks(config.cache_hooks.clone()),
+                &config,
+            ))),
+        };
+        let email_cache = match previous {
+            Some(previous)
+                if previous.config.email_cache_ttl == config.email_cache_ttl
+                    && previous.config.cache_max_entries == config.cache_max_entries
+                    && previous.config.cache_max_memory_bytes == config.cache_max_memory_bytes
+                    && previous.config.cache_shards == config.cache_shards
+                    && previous.config.cache_max_age == config.cache_max_age =>
+            {
+                previous.email_cache.clone()
+            }
+            _ => config.email_cache_ttl.map(|ttl| {
+                bounded(
+                    EmailCache::with_ttl(ttl).with_weigher(status_heap_size),
+                    &config,
+                )
+            }),
+        };
+        let failure_cache = config
+            .failure_cache_ttl
+            .filter(|_| config.enable_cache)
+            .map(TtlCache::with_ttl);
+
+        let allowlist = SharedDomainList::new(DomainList::new(&config.allowlist));
+        let blocklist = SharedDomainList::new(DomainList::new(&config.blocklist));
+        let mut checks: Vec<Arc<dyn Check>> = vec![
+            Arc::new(AllowlistCheck::new(allowlist.clone())),
+            Arc::new(BlocklistCheck::new(blocklist.clone())),
+        ];
+        let trusted_providers = DomainList::new(&config.trusted_providers);
+        if !trusted_providers.is_empty() {
+            checks.push(Arc::new(TrustedProviderCheck::new(
+                trusted_providers.clone(),
+            )));
+        }
+        if let (Some(ranks), Some(trusted_rank)) = (&config.domain_ranks, config.trusted_rank) {
+            checks.push(Arc::new(RankCheck::new(ranks.clone(), trusted_rank)));
+        }
+        checks.push(Arc::new(
+            SurblCheck::new(dns_client.clone(), config.octet_overrides.clone())
+                .with_severity_overrides(config.severity_overrides.clone())
+                .with_parent_depth(config.parent_lookup_depth),
+        ));
+        if config.detect_disposable_mx {
+            let hosts = DISPOSABLE_MX_HOSTS
+                .iter()
+                .map(|host| host.to_string())
+                .chain(config.disposable_mx_hosts.iter().cloned());
+            checks.push(Arc::new(MxFingerprintCheck::new(
+                dns_client.clone(),
+                DomainList::new(hosts),
+            )));
+        }
+        checks.extend(extra_checks.iter().cloned());
+
+        let mut ordered = Vec::with_capacity(checks.len());
+        let mut unknown = Vec::new();
+        for name in &config.check_order {
+            match checks.iter().position(|check| check.name() == name) {
+                Some(index) => ordered.push(checks.remove(index)),
+                None if ordered.iter().any(|check| check.name() == name) => {}
+                None => unknown.push(name.clone()),
+            }
+        }
+        ordered.extend(checks);
+
+        let detector = Self {
+            cache,
+            cache_locks: previous
+                .map(|previous| previous.cache_locks.clone())
+                .unwrap_or_default(),
+            email_cache,
+            failure_cache,
+            checks: ordered,
+            allowlist,
+            blocklist,
+            trusted_providers,
+            config,
+        };
+        (detector, unknown)
+    }
+
+    /// Ensemble members that match no check in the pipeline, sorted
+    fn unknown_ensemble_members(&self) -> Vec<String> {
+        let Some(ensemble) = &self.config.ensemble else {
+            return Vec::new();
+        };
+        self.unknown_checks(ensemble.weights.keys())
+    }
+
+    /// Checks named for threat aggregation precedence that match no check
+    /// in the pipeline, sorted
+    fn unknown_precedence_checks(&self) -> Vec<String> {
+        self.unknown_checks(self.config.threat_aggregation.named_checks())
+    }
+
+    fn unknown_checks<'a>(&self, names: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+        let mut unknown: Vec<String> = names
+            .into_iter()
+            .filter(|name| !self.checks.iter().any(|check| check.name() == *name))
+            .cloned()
+            .collect();
+        unknown.sort_unstable();
+        unknown
+    }
+
+    /// 检查域名
+    pub async fn check_domain(&self, domain: &str) -> Result<DomainStatus> {
+        // 验证域名格式
+        let domain = ascii_domain(domain)?;
+
+        let typo_of = if self.config.detect_typos {
+            typo::likely_typo_excluding(&domain, &self.trusted_providers).map(str::to_string)
+        } else {
+            None
+        };
+
+        let rank = self
+            .config
+            .domain_ranks
+            .as_ref()
+            .and_then(|ranks| ranks.rank(&domain));
+
+        if let Some(failures) = &self.failure_cache
+            && let Some(err) = failures.get(&domain)
+        {
+            return Err(MailGuardError::CachedFailure(Box::new(err)));
+        }
+        let mut run = match self.run_checks(&domain).await {
+            Ok(run) => run,
+            Err(err) => {
+                if let Some(failures) = &self.failure_cache
+                    && RetryKind::of(&err).is_some()
+                {
+                    failures.set(domain, err.clone());
+                }
+                return Err(err);
+            }
+        };
+        let outcome = self
+            .config
+            .threat_aggregation
+            .combine(run.outcome, &mut run.threats);
+        let (is_threat, threat_type) = verdict(
+            outcome,
+            self.config.min_blocking_severity,
+            &self.config.severity_overrides,
+        );
+
+        let mut status = DomainStatus {
+            domain,
+            is_threat,
+            threat_type,
+            threats: run.threats,
+            confidence: run.confidence,
+            from_cache: run.from_cache,
+            typo_of,
+            rank,
+            sources_checked: sources_checked(&run.results, run.from_cache),
+            check_results: run.results,
+            ensemble: run.ensemble,
+            reasons: Vec::new(),
+            checked_at: SystemTime::now(),
+            crate_version: CRATE_VERSION.to_string(),
+        };
+        status.reasons = explain(&status, self.config.min_blocking_severity);
+        Ok(status)
+    }
+
+    /// Run the pipeline for a lowercased, validated domain
+    async fn run_checks(&self, domain: &str) -> Result<PipelineRun> {
+        let mut run = PipelineRun {
+            outcome: CheckOutcome::Pass,
+            from_cache: false,
+            results: Vec::new(),
+            threats: Vec::new(),
+            confidence: 0.0,
+            ensemble: None,
+        };
+        // Outcomes so far, including cached ones, for the ensemble tally
+        let mut outcomes: Vec<CheckResult> = Vec::new();
+
+        for (index, check) in self.checks.iter().enumerate() {
+            // 检查缓存
+            let (outcome, threats, cached) = match &self.cache {
+                Some(cache) if check.cacheable() => {
+                    self.cached_check(cache.as_ref(), check.as_ref(), domain)
+                        .await?
+                }
+                _ => {
+                    let (outcome, threats) = self.run_check(check.as_ref(), domain).await?;
+                    (outcome, threats, false)
+                }
+            };
+            run.threats.extend(threats);
+            if cached {
+                run.from_cache = true;
+            } else {
+                run.results.push(CheckResult {
+                    check: check.name().to_string(),
+                    outcome: outcome.clone(),
+                });
+            }
+            outcomes.push(CheckResult {
+                check: check.name().to_string(),
+                outcome: outcome.clone(),
+            });
+
+            // Members only vote; the tally decides in place of the last one
+            let outcome = match &self.config.ensemble {
+                Some(ensemble) if ensemble.is_member(check.name()) => {
+                    let later_member = self.checks[index + 1..]
+                        .iter()
+                        .any(|later| ensemble.is_member(later.name()));
+                    if later_member {
+                        continue;
+                    }
+                    let (vote, outcome) = ensemble.tally(&outcomes);
+                    run.ensemble = Some(vote);
+                    outcome
+                }
+                _ => outcome,
+            };
+            if outcome == CheckOutcome::Pass {
+                continue;
+            }
+
+            tracing::debug!("Check {} decided {domain}: {outcome:?}", check.name());
+            if run.outcome == CheckOutcome::Pass {
+                run.outcome = outcome;
+                if self.config.short_circuit {
+                    break;
+                }
+            }
+        }
+
+        by_severity(&mut run.threats, &self.config.severity_overrides);
+        run.confidence = verdict_confidence(&outcomes);
+        Ok(run)
+    }
+
+    /// Get a cacheable check's outcome from the cache, or run the check
+    /// and cache it, returning its threats and whether it came from the
+    /// cache
+    ///
+    /// Goes through the same single-flight lookup as
+    /// [`TtlCache::get_or_try_insert_with`]: concurrent checks missing the
+    /// same entry wait for the first one instead of all querying.
+    async fn cached_check(
+        &self,
+        cache: &dyn CacheBackend,
+        check: &dyn Check,
+        domain: &str,
+    ) -> Result<(CheckOutcome, Vec<ThreatInfo>, bool)> {
+        let key = cache_key(check.name(), domain);
+        self.cache_locks
+            .get_or_try_insert_with(
+                &key,
+                || async {
+                    let cached = cache_lookup(cache, &key).await?;
+                    let (outcome, threats) = cached_outcome(check.name(), &cached);
+                    Some((outcome, threats, true))
+                },
+                || async {
+                    let (outcome, threats) = self.run_check(check, domain).await?;
+                    self.store(check.name(), domain, &outcome, &threats).await;
+                    Ok((outcome, threats, false))
+                },
+            )
+            .await
+    }
+
+    /// Run one check, retrying under the configured policy
+    ///
+    /// Errors are wrapped in [`MailGuardError::CheckFailed`], naming the
+    /// check and the domain.
+    async fn run_check(
+        &self,
+        check: &dyn Check,
+        domain: &str,
+    ) -> Result<(CheckOutcome, Vec<ThreatInfo>)> {
+        let failed = |err| MailGuardError::CheckFailed {
+            input: domain.to_string(),
+            provider: check.name().to_string(),
+            source: Box::new(err),
+        };
+        let Some(policy) = &self.config.retry_policy else {
+            return check.check_threats(domain).await.map_err(failed);
+        };
+
+        let mut attempt = 1;
+        loop {
+            let err = match check.check_threats(domain).await {
+                Ok(found) => return Ok(found),
+                Err(err) => err,
+            };
+            let Some(backoff) = policy.backoff(&err, attempt) else {
+                return Err(failed(err));
+            };
+            tracing::debug!(
+                "Check {} failed for {domain} (attempt {attempt}), retrying in {backoff:?}: {err}",
+                check.name()
+            );
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    /// 更新缓存
+    ///
+    /// Stores a check's outcome under [`cache_key`], for the TTL
+    /// [`MailGuardConfig::verdict_ttl`] gives it. Safe outcomes and threats
+    /// without a type can't be told apart from a pass in the cache, so they
+    /// are not stored.
+    async fn store(
+        &self,
+        check: &str,
+        domain: &str,
+        outcome: &CheckOutcome,
+        threats: &[ThreatInfo],
+    ) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        let value = match outcome {
+            CheckOutcome::Threat(Some(threat_type)) => {
+                CachedVerdict::of_threats(threat_type.clone(), threats)
+            }
+            CheckOutcome::Pass => CachedVerdict::pass(),
+            CheckOutcome::Threat(None) | CheckOutcome::Safe => return,
+        };
+        let ttl = jittered(
+            self.config.verdict_ttl(check, value.threat_type()),
+            self.config.cache_ttl_jitter,
+        );
+        if let Err(err) = cache.set(&cache_key(check, domain), value, ttl).await {
+            tracing::warn!("Failed to cache {check} verdict for {domain}: {err}");
+        }
+    }
+
+    /// Check an address, going through the email cache and reusing domain
+    /// results this batch already has
+    async fn check_email_once(
+        &self,
+        email: &str,
+        checked: &mut HashMap<String, Result<DomainStatus>>,
+    ) -> Result<EmailStatus> {
+        // 验证邮箱格式
+        let address = parse_address(email, self.config.email_validator.as_ref())?;
+        let canonical = address.to_string();
+        let key = normalize_email(&canonical);
+
+        if let Some(cache) = &self.email_cache
+            && let Some(mut status) = cache.get(&key)
+        {
+            // Other spellings of the same mailbox share the entry
+            status.email = email.to_string();
+            status.subaddress = parse_subaddress(&canonical);
+            status.from_cache = true;
+            return Ok(status);
+        }
+
+        // 检查域名
+        let domain_status = self
+            .check_domain_once(address.domain.as_str(), checked)
+            .await?;
+        let mut status = email_status(email, &address, domain_status);
+        if self.config.score_local_parts {
+            score_local_part_of(&mut status, address.local_part.as_str());
+        }
+
+        if let Some(cache) = &self.email_cache
+            && let Some(ttl) = self.config.email_cache_ttl
+        {
+            cache.set_with_ttl(
+                key,
+                status.clone(),
+                jittered(ttl, self.config.cache_ttl_jitter),
+            );
+        }
+        Ok(status)
+    }
+
+    /// Check a domain unless this batch already did, reusing that result
+    async fn check_domain_once(
+        &self,
+        domain: &str,
+        checked: &mut HashMap<String, Result<DomainStatus>>,
+    ) -> Result<DomainStatus> {
+        let key = domain.to_lowercase();
+        if let Some(result) = checked.get(&key) {
+            return result.clone();
+        }
+
+        let result = self.check_domain(domain).await;
+        checked.insert(key, result.clone());
+        result
+    }
+
+    /// Report what this detector can detect and what it misses
+    pub fn coverage(&self) -> CoverageReport {
+        let checks = self.checks.iter().map(|check| check.coverage()).collect();
+
+        let mut gaps =
+            vec!["Only domains are checked; no IP-based lists are configured".to_string()];
+        if self.config.query_type == QueryType::A {
+            gaps.push(
+                "Listings published only as TXT records are missed (query_type is A)".to_string(),
+            );
+        }
+        if self.blocklist.is_empty() {
+            gaps.push(
+                "Blocklist is empty; domains the providers miss can't be blocked locally"
+                    .to_string(),
+            );
+        }
+        if !self.config.detect_typos {
+            gaps.push(
+                "Typo detection is off; lookalikes of major providers are not flagged".to_string(),
+            );
+        }
+
+        CoverageReport::new(checks, gaps)
+    }
+
+    /// Drop expired entries from both caches
+    fn cleanup_expired(&self) {
         if let Some(cache) = &self.cache {
+            cache.cleanup_expired();
+        }
+        if let Some(cache) = &self.email_cache {
+            cache.cleanup_expired();
+        }
+        if let Some(failures) = &self.failure_cache {
+            failures.cleanup_expired();
+        }
+    }
+
+    /// Drop cached verdicts a rule change may have made stale
+    ///
+    /// Cached addresses embed the old verdict. Cached domain verdicts are
+    /// each one check's own, so list changes don't affect them.
+    fn rules_changed(&self) {
+        if let Some(cache) = &self.email_cache {
             cache.clear();
         }
     }
 }
 
+/// Parse with the configured validator, or the built-in rules without one
+pub(crate) fn parse_address(
+    email: &str,
+    validator: Option<&EmailValidator>,
+) -> Result<EmailAddress> {
+    match validator {
+        Some(validator) => EmailAddress::parse_with(email, validator),
+        None => EmailAddress::parse(email),
+    }
+}
+
+/// Combine a parsed address with the status of its domain
+pub(crate) fn email_status(
+    email: &str,
+    address: &EmailAddress,
+    domain_status: DomainStatus,
+) -> EmailStatus {
+    let canonical = address.to_string();
+
+    EmailStatus {
+        email: email.to_string(),
+        domain: domain_status.domain,
+        normalized_email: normalize_email(&canonical),
+        subaddress: parse_subaddress(&canonical),
+        local_part_suspicion: None,
+        is_threat: domain_status.is_threat,
+        threat_type: domain_status.threat_type,
+        threats: domain_status.threats,
+        confidence: domain_status.confidence,
+        from_cache: domain_status.from_cache,
+        typo_of: domain_status.typo_of,
+        rank: domain_status.rank,
+        check_results: domain_status.check_results,
+        ensemble: domain_status.ensemble,
+        reasons: domain_status.reasons,
+        checked_at: domain_status.checked_at,
+        sources_checked: domain_status.sources_checked,
+        crate_version: domain_status.crate_version,
+    }
+}
+
+/// Highest confidence of the checks that reported a threat, `0` if none did
+pub(crate) fn verdict_confidence(results: &[CheckResult]) -> f32 {
+    results
+        .iter()
+        .filter(|result| matches!(result.outcome, CheckOutcome::Threat(_)))
+        .map(|result| confidence_of(&result.check))
+        .fold(0.0, f32::max)
+}
+
+/// Score the local part of `status`, lowering a zero confidence to
+/// [`LOW_CONFIDENCE`] if it looks machine-generated
+pub(crate) fn score_local_part_of(status: &mut EmailStatus, local_part: &str) {
+    let suspicion = score_local_part(local_part);
+    if status.confidence == 0.0 && suspicion.score > 0 {
+        status.confidence = LOW_CONFIDENCE;
+    }
+    status.local_part_suspicion = Some(suspicion);
+}
+
+/// Names of the checks that ran, then [`CACHE_SOURCE`] for a cache hit
+pub(crate) fn sources_checked(results: &[CheckResult], from_cache: bool) -> Vec<String> {
+    let mut sources: Vec<String> = results.iter().map(|result| result.check.clone()).collect();
+    if from_cache {
+        sources.push(CACHE_SOURCE.to_string());
+    }
+    sources
+}
+
+/// Turn the deciding outcome into `is_threat` and `threat_type`
+///
+/// Typed threats below `min_blocking_severity` are reported but don't block.
+pub(crate) fn verdict(
+    outcome: CheckOutcome,
+    min_blocking_severity: u8,
+    severity_overrides: &HashMap<ThreatType, u8>,
+) -> (bool, Option<ThreatType>) {
+    match outcome {
+        CheckOutcome::Threat(threat_type) => {
+            let blocking = threat_type.as_ref().is_none_or(|threat_type| {
+                severity(threat_type, severity_overrides) >= min_blocking_severity
+            });
+            (blocking, threat_type)
+        }
+        CheckOutcome::Pass | CheckOutcome::Safe => (false, None),
+    }
+}
+
+/// The threat an outcome reports, without evidence beyond its source
+pub(crate) fn threats_of(check: &str, outcome: &CheckOutcome) -> Vec<ThreatInfo> {
+    match outcome {
+        CheckOutcome::Threat(Some(kind)) => vec![ThreatInfo::new(kind.clone(), check)],
+        _ => Vec::new(),
+    }
+}
+
+/// Sort threats most severe first, keeping check order among equals
+pub(crate) fn by_severity(
+    threats: &mut [ThreatInfo],
+    severity_overrides: &HashMap<ThreatType, u8>,
+) {
+    threats.sort_by_key(|threat| Reverse(severity(&threat.kind, severity_overrides)));
+}
+
+/// Severity of `threat_type`, overridden or built in
+pub(crate) fn severity(
+    threat_type: &ThreatType,
+    severity_overrides: &HashMap<ThreatType, u8>,
+) -> u8 {
+    severity_overrides
+        .get(threat_type)
+        .copied()
+        .unwrap_or_else(|| threat_type.severity_level())
+}
+
+/// Result of running the check pipeline for a domain
+struct PipelineRun {
+    /// The deciding outcome, [`CheckOutcome::Pass`] if no check decided
+    outcome: CheckOutcome,
+    from_cache: bool,
+    results: Vec<CheckResult>,
+    /// Threats reported by every check that ran, cached ones included
+    threats: Vec<ThreatInfo>,
+    /// [`verdict_confidence`] over every check that ran, cached ones included
+    confidence: f32,
+    ensemble: Option<EnsembleVote>,
+}
+
+impl EmailStatus {
+    /// The category of the most severe of [`threats`](Self::threats)
+    pub fn most_severe_threat(&self) -> Option<&ThreatType> {
+        self.threats.first().map(|threat| &threat.kind)
+    }
+}
+
+impl DomainStatus {
+    /// The category of the most severe of [`threats`](Self::threats)
+    ///
+    /// Can differ from `threat_type` when a later check reported something
+    /// worse than the one that decided.
+    pub fn most_severe_threat(&self) -> Option<&ThreatType> {
+        self.threats.first().map(|threat| &threat.kind)
+    }
+}
+
 impl Default for MailGuard {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,220 @@
+//! Domain cache persisted to disk
+//!
+//! CLIs and cron-style batch jobs start cold on every run, re-querying the
+//! same domains each time. [`DiskCache`] is a [`CacheBackend`] that keeps
+//! verdicts in a journal file, so they survive restarts: every change is
+//! appended as one JSON line, and opening the file replays it, dropping
+//! expired entries and compacting what is left.
+//!
+//! ```rust,no_run
+//! use mailguard_rs::{DiskCache, MailGuard};
+//!
+//! let detector = MailGuard::builder()
+//!     .cache_backend(DiskCache::open("/var/cache/mailguard/verdicts.jsonl")?)
+//!     .build()?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! The journal is meant for one process at a time; share verdicts between
+//! processes with a networked backend instead.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache::{CacheBackend, CacheEntry, CachedVerdict, lock_recovering},
+    error::{MailGuardError, Result},
+};
+
+/// One journal line
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Record {
+    Set {
+        key: String,
+        value: CachedVerdict,
+        created_at: SystemTime,
+        ttl: Duration,
+    },
+    Remove {
+        key: String,
+    },
+}
+
+/// Disk-backed [`CacheBackend`]
+#[derive(Debug)]
+pub struct DiskCache {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    entries: HashMap<String, CacheEntry>,
+    journal: File,
+}
+
+impl DiskCache {
+    /// Open the journal at `path`, creating it if it doesn't exist
+    ///
+    /// Lines that can't be parsed, such as one cut short by a crash, are
+    /// skipped.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut entries = HashMap::new();
+        match File::open(&path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let line = line?;
+                    match serde_json::from_str(&line) {
+                        Ok(record) => replay(&mut entries, record),
+                        Err(err) => tracing::warn!("Skipping cache journal line: {err}"),
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        entries.retain(|_, entry: &mut CacheEntry| !entry.is_expired());
+
+        compact(&path, &entries)?;
+        let journal = OpenOptions::new().append(true).open(&path)?;
+        Ok(Self {
+            path,
+            state: Mutex::new(State { entries, journal }),
+        })
+    }
+
+    /// Path of the journal file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Lock the state, recovering it if a panic poisoned the lock
+    ///
+    /// A panic between appending to the journal and updating the map
+    /// leaves the two out of step for one key, which reopening the journal
+    /// settles.
+    fn state(&self) -> MutexGuard<'_, State> {
+        lock_recovering(&self.state)
+    }
+
+    fn append(state: &mut State, record: &Record) -> Result<()> {
+        let mut line = serde_json::to_string(record).expect("journal records serialize");
+        line.push('\n');
+        state
+            .journal
+            .write_all(line.as_bytes())
+            .map_err(MailGuardError::Storage)
+    }
+}
+
+#[async_trait]
+impl CacheBackend for DiskCache {
+    async fn get(&self, key: &str) -> Result<Option<CachedVerdict>> {
+        let state = self.state();
+        Ok(state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| entry.value.clone()))
+    }
+
+    async fn set(&self, key: &str, value: CachedVerdict, ttl: Duration) -> Result<()> {
+        let entry = CacheEntry::new(value, ttl);
+        let record = Record::Set {
+            key: key.to_string(),
+            value: entry.value.clone(),
+            created_at: entry.created_at,
+            ttl,
+        };
+        let mut state = self.state();
+        Self::append(&mut state, &record)?;
+        state.entries.insert(key.to_string(), entry);
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        let mut state = self.state();
+        if state.entries.remove(key).is_some() {
+            let record = Record::Remove {
+                key: key.to_string(),
+            };
+            Self::append(&mut state, &record)?;
+        }
+        Ok(())
+    }
+
+    fn clear(&self) {
+        let mut state = self.state();
+        state.entries.clear();
+        if let Err(err) = state.journal.set_len(0) {
+            tracing::warn!("Failed to truncate {}: {err}", self.path.display());
+        }
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(self.state().entries.len())
+    }
+
+    fn cleanup_expired(&self) {
+        let mut state = self.state();
+        state.entries.retain(|_, entry| !entry.is_expired());
+    }
+
+    fn entries(&self) -> Vec<(String, CacheEntry)> {
+        let state = self.state();
+        state
+            .entries
+            .iter()
+            .filter(|(_, entry)| !entry.is_expired())
+            .map(|(key, entry)| (key.clone(), entry.clone()))
+            .collect()
+    }
+}
+
+fn replay(entries: &mut HashMap<String, CacheEntry>, record: Record) {
+    match record {
+        Record::Set {
+            key,
+            value,
+            created_at,
+            ttl,
+        } => {
+            entries.insert(key, CacheEntry::restore(value, created_at, ttl));
+        }
+        Record::Remove { key } => {
+            entries.remove(&key);
+        }
+    }
+}
+
+/// Rewrite the journal with only the live entries
+///
+/// Written to a temporary file and renamed over the journal, so a crash
+/// midway leaves the old journal intact.
+fn compact(path: &Path, entries: &HashMap<String, CacheEntry>) -> io::Result<()> {
+    let temporary = path.with_extension("tmp");
+    let mut file = File::create(&temporary)?;
+    for (key, entry) in entries {
+        let record = Record::Set {
+            key: key.clone(),
+            value: entry.value.clone(),
+            created_at: entry.created_at,
+            ttl: entry.ttl,
+        };
+        let line = serde_json::to_string(&record).expect("journal records serialize");
+        writeln!(file, "{line}")?;
+    }
+    file.sync_all()?;
+    fs::rename(&temporary, path)
+}
@@ -1,24 +1,85 @@
-use std::{net::Ipv4Addr, time::Duration};
+use std::{
+    cmp::Reverse,
+    net::{IpAddr, Ipv4Addr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use trust_dns_resolver::{
+    TokioAsyncResolver,
+    config::*,
+    error::{ResolveError, ResolveErrorKind},
+};
 
-use trust_dns_resolver::{TokioAsyncResolver, config::*};
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    check::SURBL,
     error::{MailGuardError, Result},
-    threat::ThreatType,
+    fixture::{Fixture, FixtureAnswer},
+    idna::domain_to_ascii,
+    threat::{ThreatInfo, ThreatType},
 };
 
+/// SURBL zone queried for every domain
+pub const SURBL_ZONE: &str = "tempmail.so.multi.surbl.org";
+
+/// Back-off used when a throttled answer carries no usable TTL
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Threat type reported for listings found only through TXT records
+///
+/// TXT answers carry no return code, and 0 is never a valid one.
+pub const UNCATEGORIZED_LISTING: ThreatType = ThreatType::Unknown(0);
+
+/// Which record types are queried to decide whether a domain is listed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QueryType {
+    /// A/AAAA records only
+    #[default]
+    A,
+    /// A/AAAA records, falling back to TXT when they show no listing or fail
+    ATxt,
+    /// TXT records first, then A/AAAA only to categorize a listing
+    ///
+    /// Falls back to A/AAAA when the TXT query fails.
+    TxtFirst,
+}
+
+/// Where DNS answers come from
+#[derive(Clone)]
+enum Backend {
+    /// Real resolver, optionally recording every answer
+    Live {
+        resolver: Box<TokioAsyncResolver>,
+        recorder: Option<Arc<Mutex<Fixture>>>,
+    },
+    /// Answers served from a recorded fixture
+    Playback(Arc<Fixture>),
+}
+
+/// A successful A/AAAA answer
+struct Answer {
+    ips: Vec<IpAddr>,
+    ttl: Duration,
+}
+
+/// A successful TXT answer
+struct TxtAnswer {
+    texts: Vec<String>,
+}
+
 /// DNS query client
+#[derive(Clone)]
 pub struct DnsClient {
-    resolver: TokioAsyncResolver,
+    backend: Backend,
+    query_type: QueryType,
 }
 
 impl DnsClient {
     /// Create a new DNS client
     pub fn new() -> Self {
-        let resolver =
-            TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default());
-
-        Self { resolver }
+        Self::with_timeout(ResolverOpts::default().timeout)
     }
 
     /// Create a DNS client with custom timeout
@@ -28,45 +89,355 @@ impl DnsClient {
 
         let resolver = TokioAsyncResolver::tokio(ResolverConfig::default(), opts);
 
-        Self { resolver }
+        Self {
+            backend: Backend::Live {
+                resolver: Box::new(resolver),
+                recorder: None,
+            },
+            query_type: QueryType::default(),
+        }
     }
 
-    /// Query domain SURBL status
+    /// Create a DNS client that answers from a recorded fixture, without network IO
     ///
-    /// Query format: domain.tempmail.so.multi.surbl.org
-    pub async fn query_surbl(&self, domain: &str) -> Result<Option<ThreatType>> {
-        let surbl_domain = format!("{domain}.tempmail.so.multi.surbl.org");
+    /// Queries missing from the fixture are answered as "no records".
+    pub fn playback(fixture: Fixture) -> Self {
+        Self {
+            backend: Backend::Playback(Arc::new(fixture)),
+            query_type: QueryType::default(),
+        }
+    }
 
-        tracing::debug!("Querying SURBL: {surbl_domain}");
-        match self.resolver.lookup_ip(&surbl_domain).await {
-            Ok(response) => {
-                // Check if there are A records pointing to 127.0.0.x
-                for ip in response.iter() {
-                    if let std::net::IpAddr::V4(ipv4) = ip
-                        && self.is_surbl_positive_response(ipv4)
-                    {
-                        let threat_type = ThreatType::from_ip_last_octet(ipv4.octets()[3]);
-                        tracing::info!("Detected threat domain: {domain} -> {threat_type:?}");
-                        return Ok(Some(threat_type));
+    /// Choose which record types are queried
+    pub fn with_query_type(mut self, query_type: QueryType) -> Self {
+        self.query_type = query_type;
+        self
+    }
+
+    /// Record every answer this client receives (see [`DnsClient::recorded_fixture`])
+    ///
+    /// Has no effect on a playback client.
+    pub fn recording(mut self) -> Self {
+        if let Backend::Live { recorder, .. } = &mut self.backend {
+            *recorder = Some(Arc::new(Mutex::new(Fixture::new())));
+        }
+        self
+    }
+
+    /// Snapshot of the answers recorded so far, if recording is enabled
+    pub fn recorded_fixture(&self) -> Option<Fixture> {
+        match &self.backend {
+            Backend::Live {
+                recorder: Some(recorder),
+                ..
+            } => Some(recorder.lock().unwrap_or_else(|e| e.into_inner()).clone()),
+            _ => None,
+        }
+    }
+
+    /// Resolve A/AAAA records, returning `None` for NXDOMAIN / no records
+    async fn lookup_ip(&self, name: &str) -> Result<Option<Answer>> {
+        match &self.backend {
+            Backend::Live { resolver, recorder } => {
+                let started = Instant::now();
+                let answer = match resolver.lookup_ip(name).await {
+                    Ok(response) => {
+                        let ttl = response
+                            .valid_until()
+                            .saturating_duration_since(Instant::now());
+                        Some(Answer {
+                            ips: response.iter().collect(),
+                            ttl,
+                        })
+                    }
+                    Err(err) if matches!(err.kind(), ResolveErrorKind::NoRecordsFound { .. }) => {
+                        None
                     }
+                    Err(err) => {
+                        tracing::warn!("DNS query failed: {name} - {err}");
+                        return Err(query_error(format!("A query for {name}"), err, started));
+                    }
+                };
+
+                if let Some(recorder) = recorder {
+                    let recorded = match &answer {
+                        Some(answer) => FixtureAnswer::Records {
+                            ips: answer.ips.clone(),
+                            ttl_secs: answer.ttl.as_secs(),
+                        },
+                        None => FixtureAnswer::NoRecords,
+                    };
+                    recorder
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(name, recorded);
                 }
 
-                tracing::debug!("Domain {domain} not found in SURBL");
-                Ok(None)
+                Ok(answer)
             }
-            Err(err) => {
-                // DNS query failure usually indicates domain is not in blacklist
-                match err.kind() {
-                    trust_dns_resolver::error::ResolveErrorKind::NoRecordsFound { .. } => {
-                        tracing::debug!("Domain {domain} not in SURBL blacklist");
-                        Ok(None)
+            Backend::Playback(fixture) => match fixture.get(name) {
+                Some(FixtureAnswer::Records { ips, ttl_secs }) => Ok(Some(Answer {
+                    ips: ips.clone(),
+                    ttl: Duration::from_secs(*ttl_secs),
+                })),
+                Some(_) => Ok(None),
+                None => {
+                    tracing::debug!("No recorded answer for {name}, treating as no records");
+                    Ok(None)
+                }
+            },
+        }
+    }
+
+    /// Resolve TXT records, returning `None` for NXDOMAIN / no records
+    async fn lookup_txt(&self, name: &str) -> Result<Option<TxtAnswer>> {
+        match &self.backend {
+            Backend::Live { resolver, recorder } => {
+                let started = Instant::now();
+                let (answer, ttl) = match resolver.txt_lookup(name).await {
+                    Ok(response) => {
+                        let ttl = response
+                            .valid_until()
+                            .saturating_duration_since(Instant::now());
+                        let texts = response.iter().map(|txt| txt.to_string()).collect();
+                        (Some(TxtAnswer { texts }), ttl)
+                    }
+                    Err(err) if matches!(err.kind(), ResolveErrorKind::NoRecordsFound { .. }) => {
+                        (None, Duration::ZERO)
                     }
-                    _ => {
-                        tracing::warn!("DNS query failed: {surbl_domain} - {err}");
-                        Err(MailGuardError::DnsError(err))
+                    Err(err) => {
+                        tracing::warn!("DNS TXT query failed: {name} - {err}");
+                        return Err(query_error(format!("TXT query for {name}"), err, started));
                     }
+                };
+
+                if let Some(recorder) = recorder {
+                    let recorded = match &answer {
+                        Some(answer) => FixtureAnswer::Text {
+                            texts: answer.texts.clone(),
+                            ttl_secs: ttl.as_secs(),
+                        },
+                        None => FixtureAnswer::NoRecords,
+                    };
+                    recorder
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert_txt(name, recorded);
+                }
+
+                Ok(answer)
+            }
+            Backend::Playback(fixture) => match fixture.get_txt(name) {
+                Some(FixtureAnswer::Text { texts, .. }) => Ok(Some(TxtAnswer {
+                    texts: texts.clone(),
+                })),
+                Some(_) => Ok(None),
+                None => {
+                    tracing::debug!("No recorded TXT answer for {name}, treating as no records");
+                    Ok(None)
+                }
+            },
+        }
+    }
+
+    /// Resolve the MX hosts of a domain, most preferred first
+    ///
+    /// Host names are lowercased and without the trailing dot. Returns an
+    /// empty list for NXDOMAIN / no records and for a null MX (`MX 0 .`).
+    pub async fn lookup_mx(&self, domain: &str) -> Result<Vec<String>> {
+        match &self.backend {
+            Backend::Live { resolver, recorder } => {
+                let started = Instant::now();
+                let (hosts, ttl) = match resolver.mx_lookup(domain).await {
+                    Ok(response) => {
+                        let ttl = response
+                            .valid_until()
+                            .saturating_duration_since(Instant::now());
+                        let mut exchanges: Vec<_> = response.iter().collect();
+                        exchanges.sort_by_key(|mx| mx.preference());
+                        let hosts = exchanges
+                            .into_iter()
+                            .map(|mx| mx.exchange().to_utf8().trim_end_matches('.').to_lowercase())
+                            .filter(|host| !host.is_empty())
+                            .collect();
+                        (hosts, ttl)
+                    }
+                    Err(err) if matches!(err.kind(), ResolveErrorKind::NoRecordsFound { .. }) => {
+                        (Vec::new(), Duration::ZERO)
+                    }
+                    Err(err) => {
+                        tracing::warn!("DNS MX query failed: {domain} - {err}");
+                        return Err(query_error(format!("MX query for {domain}"), err, started));
+                    }
+                };
+
+                if let Some(recorder) = recorder {
+                    let recorded = if hosts.is_empty() {
+                        FixtureAnswer::NoRecords
+                    } else {
+                        FixtureAnswer::Exchanges {
+                            hosts: hosts.clone(),
+                            ttl_secs: ttl.as_secs(),
+                        }
+                    };
+                    recorder
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert_mx(domain, recorded);
+                }
+
+                Ok(hosts)
+            }
+            Backend::Playback(fixture) => match fixture.get_mx(domain) {
+                Some(FixtureAnswer::Exchanges { hosts, .. }) => Ok(hosts.clone()),
+                Some(_) => Ok(Vec::new()),
+                None => {
+                    tracing::debug!("No recorded MX answer for {domain}, treating as no records");
+                    Ok(Vec::new())
+                }
+            },
+        }
+    }
+
+    /// Query domain SURBL status
+    ///
+    /// Query format: domain.tempmail.so.multi.surbl.org. The record types
+    /// used follow the client's [`QueryType`]. An answer listing several
+    /// categories reports the most severe; see
+    /// [`query_surbl_all`](Self::query_surbl_all) for all of them.
+    pub async fn query_surbl(&self, domain: &str) -> Result<Option<ThreatType>> {
+        Ok(self.query_surbl_all(domain).await?.into_iter().next())
+    }
+
+    /// Query domain SURBL status, returning every category the answer
+    /// lists, most severe first
+    ///
+    /// Severity is the built-in [`ThreatType::severity_level`]; the
+    /// detector's [`SurblCheck`](crate::check::SurblCheck) re-ranks by the
+    /// configured overrides. Empty when the domain isn't listed.
+    pub async fn query_surbl_all(&self, domain: &str) -> Result<Vec<ThreatType>> {
+        let threats = self.query_surbl_threats(domain).await?;
+        Ok(threats.into_iter().map(|threat| threat.kind).collect())
+    }
+
+    /// Like [`query_surbl_all`](Self::query_surbl_all), with the zone and
+    /// the raw answer behind each category
+    pub async fn query_surbl_threats(&self, domain: &str) -> Result<Vec<ThreatInfo>> {
+        let uncategorized =
+            || vec![ThreatInfo::new(UNCATEGORIZED_LISTING, SURBL).with_answer(SURBL_ZONE, None)];
+        match self.query_type {
+            QueryType::A => self.query_surbl_ip(domain).await,
+            QueryType::ATxt => {
+                let ip_result = self.query_surbl_ip(domain).await;
+                if matches!(&ip_result, Ok(threats) if !threats.is_empty())
+                    || matches!(ip_result, Err(MailGuardError::RetryAfter(_)))
+                {
+                    return ip_result;
+                }
+
+                match self.query_surbl_txt(domain).await {
+                    Ok(true) => Ok(uncategorized()),
+                    // Keep the A/AAAA outcome, including its error
+                    Ok(false) => ip_result,
+                    Err(err) => ip_result.or(Err(err)),
                 }
             }
+            QueryType::TxtFirst => match self.query_surbl_txt(domain).await {
+                Ok(false) => Ok(Vec::new()),
+                Ok(true) => match self.query_surbl_ip(domain).await {
+                    Ok(threats) if !threats.is_empty() => Ok(threats),
+                    Err(err @ MailGuardError::RetryAfter(_)) => Err(err),
+                    // Listed according to TXT, but no usable category
+                    _ => Ok(uncategorized()),
+                },
+                Err(_) => self.query_surbl_ip(domain).await,
+            },
+        }
+    }
+
+    /// Check whether the domain has SURBL TXT records
+    async fn query_surbl_txt(&self, domain: &str) -> Result<bool> {
+        let surbl_domain = format!("{domain}.{SURBL_ZONE}");
+
+        tracing::debug!("Querying SURBL TXT: {surbl_domain}");
+        match self.lookup_txt(&surbl_domain).await? {
+            Some(answer) => {
+                tracing::info!(
+                    "Detected threat domain via TXT: {domain} -> {:?}",
+                    answer.texts
+                );
+                Ok(!answer.texts.is_empty())
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Query SURBL A/AAAA records and decode the return codes, most severe
+    /// first
+    async fn query_surbl_ip(&self, domain: &str) -> Result<Vec<ThreatInfo>> {
+        let surbl_domain = format!("{domain}.{SURBL_ZONE}");
+
+        tracing::debug!("Querying SURBL: {surbl_domain}");
+        let Some(answer) = self.lookup_ip(&surbl_domain).await? else {
+            // NXDOMAIN means the domain is not in the blacklist
+            tracing::debug!("Domain {domain} not in SURBL blacklist");
+            return Ok(Vec::new());
+        };
+
+        // Check if there are A records pointing to 127.0.0.x
+        let mut threats: Vec<ThreatInfo> = Vec::new();
+        for ip in &answer.ips {
+            let Some(ipv4) = Self::as_ipv4(*ip) else {
+                continue;
+            };
+
+            if self.is_surbl_throttled_response(ipv4) {
+                let retry_after = Some(answer.ttl)
+                    .filter(|duration| !duration.is_zero())
+                    .unwrap_or(DEFAULT_RETRY_AFTER);
+                tracing::warn!(
+                    "SURBL is throttling queries for {domain}, retry after {retry_after:?}"
+                );
+                return Err(MailGuardError::RetryAfter(retry_after));
+            }
+
+            if self.is_surbl_positive_response(ipv4) {
+                let octet = ipv4.octets()[3];
+                let threat_type = ThreatType::from_ip_last_octet(octet);
+                if let ThreatType::Unknown(octet) = threat_type {
+                    // Structured event so new list return codes get noticed
+                    tracing::warn!(
+                        zone = SURBL_ZONE,
+                        octet,
+                        domain,
+                        "Unknown SURBL return code 127.0.0.{octet}"
+                    );
+                }
+                tracing::info!("Detected threat domain: {domain} -> {threat_type:?}");
+                if !threats.iter().any(|threat| threat.kind == threat_type) {
+                    threats.push(
+                        ThreatInfo::new(threat_type, SURBL).with_answer(SURBL_ZONE, Some(*ip)),
+                    );
+                }
+            }
+        }
+
+        if threats.is_empty() {
+            tracing::debug!("Domain {domain} not found in SURBL");
+        }
+        threats.sort_by_key(|threat| Reverse(threat.kind.severity_level()));
+        Ok(threats)
+    }
+
+    /// View an answer as IPv4, unwrapping IPv4-mapped (`::ffff:127.0.0.2`) and
+    /// IPv4-compatible (`::127.0.0.2`) IPv6 answers
+    ///
+    /// IPv6-preferring resolvers and some list mirrors return the listing
+    /// code in AAAA form.
+    fn as_ipv4(ip: IpAddr) -> Option<Ipv4Addr> {
+        match ip {
+            IpAddr::V4(ipv4) => Some(ipv4),
+            IpAddr::V6(ipv6) => ipv6.to_ipv4(),
         }
     }
 
@@ -76,38 +447,61 @@ impl DnsClient {
         octets[0] == 127 && octets[1] == 0 && octets[2] == 0 && octets[3] > 1
     }
 
+    /// Check if IP is the SURBL "query blocked" response (127.0.0.1)
+    ///
+    /// SURBL answers 127.0.0.1 when the querying resolver exceeded its quota
+    /// or is not allowed to use the free service.
+    fn is_surbl_throttled_response(&self, ip: Ipv4Addr) -> bool {
+        ip == Ipv4Addr::new(127, 0, 0, 1)
+    }
+
     /// Validate domain format
     pub fn validate_domain(&self, domain: &str) -> Result<()> {
-        if domain.is_empty() {
-            return Err(MailGuardError::InvalidDomain(
-                "Domain cannot be empty".to_string(),
-            ));
-        }
+        validate_domain(domain)
+    }
+}
 
-        if domain.len() > 253 {
-            return Err(MailGuardError::InvalidDomain(
-                "Domain length exceeds limit".to_string(),
-            ));
-        }
+/// Convert a domain to the lowercased ASCII form that is queried, and
+/// validate it
+pub(crate) fn ascii_domain(domain: &str) -> Result<String> {
+    let ascii =
+        domain_to_ascii(domain).map_err(|reason| MailGuardError::invalid_domain(domain, reason))?;
+    validate_domain(&ascii)?;
+    Ok(ascii)
+}
 
-        // Simple domain format validation
-        if !domain
-            .chars()
-            .all(|c| c.is_alphanumeric() || c == '.' || c == '-')
-        {
-            return Err(MailGuardError::InvalidDomain(format!(
-                "Invalid domain characters: {domain}"
-            )));
-        }
+/// Validate domain format
+pub(crate) fn validate_domain(domain: &str) -> Result<()> {
+    if domain.is_empty() {
+        return Err(MailGuardError::invalid_domain(domain, "Domain is empty"));
+    }
 
-        if domain.starts_with('.') || domain.ends_with('.') || domain.contains("..") {
-            return Err(MailGuardError::InvalidDomain(format!(
-                "Invalid domain format: {domain}"
-            )));
-        }
+    if domain.len() > 253 {
+        return Err(MailGuardError::invalid_domain(
+            domain,
+            "Domain exceeds 253 characters",
+        ));
+    }
 
-        Ok(())
+    // Simple domain format validation
+    if !domain
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+    {
+        return Err(MailGuardError::invalid_domain(
+            domain,
+            "Invalid character in domain",
+        ));
     }
+
+    if domain.starts_with('.') || domain.ends_with('.') || domain.contains("..") {
+        return Err(MailGuardError::invalid_domain(
+            domain,
+            "Misplaced '.' in domain",
+        ));
+    }
+
+    Ok(())
 }
 
 impl Default for DnsClient {
@@ -115,3 +509,14 @@ impl Default for DnsClient {
         Self::new()
     }
 }
+
+/// Error for a failed `operation`, telling timeouts apart from other failures
+fn query_error(operation: String, err: ResolveError, started: Instant) -> MailGuardError {
+    match err.kind() {
+        ResolveErrorKind::Timeout => MailGuardError::Timeout {
+            operation,
+            elapsed: started.elapsed(),
+        },
+        _ => MailGuardError::DnsError(err),
+    }
+}
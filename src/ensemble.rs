@@ -0,0 +1,156 @@
+//! Weighted vote across checks
+//!
+//! By default the first check in order to report a threat decides, so the
+//! least reliable provider in the pipeline can block a domain on its own.
+//! With an [`Ensemble`] configured, a set of member checks vote instead:
+//! each member runs, its weight counts as "listed" when it reports a threat,
+//! and the domain is a threat once the listed weight reaches the threshold.
+//! Checks that are not members, such as the local allow and block lists,
+//! keep deciding on their own.
+//!
+//! ```rust
+//! use mailguard_rs::{Ensemble, MailGuardConfig, check};
+//!
+//! // SURBL alone is enough to block, the in-house heuristic alone isn't
+//! let config = MailGuardConfig {
+//!     ensemble: Some(
+//!         Ensemble::new(3)
+//!             .weight(check::SURBL, 3)
+//!             .weight("heuristic", 1),
+//!     ),
+//!     ..Default::default()
+//! };
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    check::{CheckOutcome, CheckResult},
+    error::{MailGuardError, Result},
+};
+
+/// Trust weights of the voting checks and the weight needed to block
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ensemble {
+    /// Weight of each member, keyed by [`Check::name`](crate::Check::name)
+    ///
+    /// A weight of `0` lets a check's vote be reported without counting.
+    pub weights: HashMap<String, u32>,
+    /// Total weight of listing members needed to report a threat
+    pub threshold: u32,
+}
+
+impl Ensemble {
+    /// Create an ensemble without members
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            weights: HashMap::new(),
+            threshold,
+        }
+    }
+
+    /// Add a member check, replacing its earlier weight if any
+    pub fn weight(mut self, check: impl Into<String>, weight: u32) -> Self {
+        self.weights.insert(check.into(), weight);
+        self
+    }
+
+    /// Whether the named check votes
+    pub fn is_member(&self, check: &str) -> bool {
+        self.weights.contains_key(check)
+    }
+
+    /// Reject ensembles that could never or would always block
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| Err(MailGuardError::InvalidConfig(reason));
+
+        if self.threshold == 0 {
+            return invalid("ensemble threshold must be greater than zero".to_string());
+        }
+        let total: u64 = self.weights.values().map(|weight| u64::from(*weight)).sum();
+        if total < u64::from(self.threshold) {
+            return invalid(format!(
+                "ensemble threshold {} exceeds the total member weight {total}",
+                self.threshold
+            ));
+        }
+        Ok(())
+    }
+
+    /// Count the votes of the members among `results`
+    ///
+    /// Returns the breakdown and the resulting outcome. A threat takes the
+    /// type reported by the heaviest listing member that has one.
+    pub(crate) fn tally(&self, results: &[CheckResult]) -> (EnsembleVote, CheckOutcome) {
+        let mut vote = EnsembleVote {
+            votes: Vec::new(),
+            listed_weight: 0,
+            total_weight: 0,
+            threshold: self.threshold,
+        };
+        let mut threat_type = None;
+        let mut typed_weight = None;
+
+        for result in results {
+            let Some(&weight) = self.weights.get(&result.check) else {
+                continue;
+            };
+            let listed = matches!(result.outcome, CheckOutcome::Threat(_));
+            vote.total_weight = vote.total_weight.saturating_add(weight);
+            if listed {
+                vote.listed_weight = vote.listed_weight.saturating_add(weight);
+            }
+            if let CheckOutcome::Threat(Some(threat)) = &result.outcome
+                && typed_weight.is_none_or(|heaviest| weight > heaviest)
+            {
+                threat_type = Some(threat.clone());
+                typed_weight = Some(weight);
+            }
+            vote.votes.push(Vote {
+                check: result.check.clone(),
+                weight,
+                listed,
+            });
+        }
+
+        let outcome = if vote.is_listed() {
+            CheckOutcome::Threat(threat_type)
+        } else {
+            CheckOutcome::Pass
+        };
+        (vote, outcome)
+    }
+}
+
+/// One member's vote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vote {
+    /// Name of the check
+    pub check: String,
+    /// Its configured weight
+    pub weight: u32,
+    /// Whether it reported a threat
+    pub listed: bool,
+}
+
+/// Breakdown of an ensemble vote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnsembleVote {
+    /// Votes of the members that ran, in order
+    pub votes: Vec<Vote>,
+    /// Total weight of the members that reported a threat
+    pub listed_weight: u32,
+    /// Total weight of the members that ran
+    pub total_weight: u32,
+    /// Weight that was needed to report a threat
+    pub threshold: u32,
+}
+
+impl EnsembleVote {
+    /// Whether the listing members reached the threshold
+    pub fn is_listed(&self) -> bool {
+        self.listed_weight >= self.threshold
+    }
+}
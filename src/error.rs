@@ -1,14 +1,250 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::address::ValidationDetail;
+
+/// Broad kinds of [`MailGuardError`], for routing failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    /// The input was malformed ([`MailGuardError::is_input_error`])
+    Input,
+    /// Something the detector depends on failed
+    /// ([`MailGuardError::is_infrastructure_error`])
+    Infrastructure,
+    /// The configuration or a list rule is invalid
+    Configuration,
+}
+
 /// MailGuard error types
 #[derive(Debug, thiserror::Error)]
 pub enum MailGuardError {
     #[error("DNS query failed: {0}")]
     DnsError(#[from] trust_dns_resolver::error::ResolveError),
 
-    #[error("Invalid email format: {0}")]
-    InvalidEmail(String),
+    /// A query got no answer in time, e.g. after
+    /// [`MailGuardConfig::dns_timeout`](crate::MailGuardConfig::dns_timeout)
+    ///
+    /// Kept apart from [`DnsError`](Self::DnsError) so callers can fail open
+    /// on timeouts without also ignoring misconfiguration.
+    #[error("{operation} timed out after {elapsed:?}")]
+    Timeout {
+        operation: String,
+        elapsed: Duration,
+    },
+
+    #[error("Invalid email address {input:?}: {reason}")]
+    InvalidEmail {
+        input: String,
+        reason: String,
+        /// The syntax rule broken, when the address failed parsing
+        detail: Option<ValidationDetail>,
+    },
+
+    #[error("Invalid domain {input:?}: {reason}")]
+    InvalidDomain { input: String, reason: String },
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("Invalid list rule: {0}")]
+    InvalidRule(String),
+
+    #[error("Unknown threat type: {0}")]
+    InvalidThreatType(String),
+
+    #[error("Failed to start async runtime: {0}")]
+    Runtime(std::io::Error),
+
+    #[error("Provider is throttling queries, retry after {0:?}")]
+    RetryAfter(Duration),
+
+    #[error("Cache storage failed: {0}")]
+    Storage(std::io::Error),
+
+    /// The domain failed to check moments ago, and the failure is cached
+    /// for [`MailGuardConfig::failure_cache_ttl`](crate::MailGuardConfig::failure_cache_ttl)
+    #[error("Recent failure not retried yet: {0}")]
+    CachedFailure(Box<MailGuardError>),
+
+    /// A check of the pipeline failed for the domain `input`
+    ///
+    /// `provider` is the [`Check::name`](crate::Check::name) of the check,
+    /// and `source` what went wrong; see [`root_cause`](Self::root_cause).
+    #[error("Check {provider} failed for {input}: {source}")]
+    CheckFailed {
+        input: String,
+        provider: String,
+        source: Box<MailGuardError>,
+    },
+}
+
+impl MailGuardError {
+    /// An [`InvalidEmail`](Self::InvalidEmail) error for `input`
+    pub fn invalid_email(input: impl Into<String>, reason: impl Into<String>) -> Self {
+        MailGuardError::InvalidEmail {
+            input: input.into(),
+            reason: reason.into(),
+            detail: None,
+        }
+    }
+
+    /// An [`InvalidEmail`](Self::InvalidEmail) error for `input` that broke
+    /// the syntax rule described by `detail`
+    pub fn invalid_email_at(
+        input: impl Into<String>,
+        reason: impl Into<String>,
+        detail: ValidationDetail,
+    ) -> Self {
+        MailGuardError::InvalidEmail {
+            input: input.into(),
+            reason: reason.into(),
+            detail: Some(detail),
+        }
+    }
+
+    /// An [`InvalidDomain`](Self::InvalidDomain) error for `input`
+    pub fn invalid_domain(input: impl Into<String>, reason: impl Into<String>) -> Self {
+        MailGuardError::InvalidDomain {
+            input: input.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// The error behind any [`CheckFailed`](Self::CheckFailed) and
+    /// [`CachedFailure`](Self::CachedFailure) wrapping, e.g. the
+    /// [`DnsError`](Self::DnsError) of a failed SURBL query
+    pub fn root_cause(&self) -> &MailGuardError {
+        match self {
+            MailGuardError::CheckFailed { source, .. } => source.root_cause(),
+            MailGuardError::CachedFailure(err) => err.root_cause(),
+            err => err,
+        }
+    }
+
+    /// Which syntax rule the address broke, and where, if this is an
+    /// [`InvalidEmail`](Self::InvalidEmail) error from parsing
+    pub fn validation_detail(&self) -> Option<&ValidationDetail> {
+        match self.root_cause() {
+            MailGuardError::InvalidEmail { detail, .. } => detail.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// How long the provider asked us to back off, if this is a throttling error
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self.root_cause() {
+            MailGuardError::RetryAfter(duration) => Some(*duration),
+            _ => None,
+        }
+    }
+
+    /// The broad kind of the error
+    pub fn class(&self) -> ErrorClass {
+        if self.is_input_error() {
+            ErrorClass::Input
+        } else if self.is_infrastructure_error() {
+            ErrorClass::Infrastructure
+        } else {
+            ErrorClass::Configuration
+        }
+    }
+
+    /// Whether trying again later may succeed: DNS failures and timeouts,
+    /// throttling and cache storage failures
+    ///
+    /// [`CachedFailure`](Self::CachedFailure)s are retryable once the
+    /// failure cache lets the domain through again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            MailGuardError::DnsError(_)
+            | MailGuardError::Timeout { .. }
+            | MailGuardError::RetryAfter(_)
+            | MailGuardError::Storage(_) => true,
+            MailGuardError::CachedFailure(err) => err.is_retryable(),
+            MailGuardError::CheckFailed { source, .. } => source.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// Whether the input was at fault: a malformed address, domain or
+    /// threat type, which fails the same way every time
+    pub fn is_input_error(&self) -> bool {
+        match self {
+            MailGuardError::InvalidEmail { .. }
+            | MailGuardError::InvalidDomain { .. }
+            | MailGuardError::InvalidThreatType(_) => true,
+            MailGuardError::CachedFailure(err) => err.is_input_error(),
+            MailGuardError::CheckFailed { source, .. } => source.is_input_error(),
+            _ => false,
+        }
+    }
+
+    /// Whether something the detector depends on failed: DNS, a provider,
+    /// the cache or the async runtime
+    ///
+    /// Configuration errors are neither input nor infrastructure errors.
+    pub fn is_infrastructure_error(&self) -> bool {
+        match self {
+            MailGuardError::DnsError(_)
+            | MailGuardError::Timeout { .. }
+            | MailGuardError::RetryAfter(_)
+            | MailGuardError::Storage(_)
+            | MailGuardError::Runtime(_) => true,
+            MailGuardError::CachedFailure(err) => err.is_infrastructure_error(),
+            MailGuardError::CheckFailed { source, .. } => source.is_infrastructure_error(),
+            _ => false,
+        }
+    }
+}
 
-    #[error("Invalid domain format: {0}")]
-    InvalidDomain(String),
+// Manual impl because `std::io::Error` isn't `Clone`
+impl Clone for MailGuardError {
+    fn clone(&self) -> Self {
+        match self {
+            MailGuardError::DnsError(err) => MailGuardError::DnsError(err.clone()),
+            MailGuardError::Timeout { operation, elapsed } => MailGuardError::Timeout {
+                operation: operation.clone(),
+                elapsed: *elapsed,
+            },
+            MailGuardError::InvalidEmail {
+                input,
+                reason,
+                detail,
+            } => MailGuardError::InvalidEmail {
+                input: input.clone(),
+                reason: reason.clone(),
+                detail: detail.clone(),
+            },
+            MailGuardError::InvalidDomain { input, reason } => MailGuardError::InvalidDomain {
+                input: input.clone(),
+                reason: reason.clone(),
+            },
+            MailGuardError::InvalidConfig(msg) => MailGuardError::InvalidConfig(msg.clone()),
+            MailGuardError::InvalidRule(msg) => MailGuardError::InvalidRule(msg.clone()),
+            MailGuardError::InvalidThreatType(msg) => {
+                MailGuardError::InvalidThreatType(msg.clone())
+            }
+            MailGuardError::Runtime(err) => {
+                MailGuardError::Runtime(std::io::Error::new(err.kind(), err.to_string()))
+            }
+            MailGuardError::RetryAfter(duration) => MailGuardError::RetryAfter(*duration),
+            MailGuardError::Storage(err) => {
+                MailGuardError::Storage(std::io::Error::new(err.kind(), err.to_string()))
+            }
+            MailGuardError::CachedFailure(err) => MailGuardError::CachedFailure(err.clone()),
+            MailGuardError::CheckFailed {
+                input,
+                provider,
+                source,
+            } => MailGuardError::CheckFailed {
+                input: input.clone(),
+                provider: provider.clone(),
+                source: source.clone(),
+            },
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, MailGuardError>;
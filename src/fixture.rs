@@ -0,0 +1,115 @@
+//! Recorded DNS answers for deterministic, offline tests
+//!
+//! A [`Fixture`] maps DNS query names to the answers a live resolver gave.
+//! Record one with [`Fixture::record`] (or a [`DnsClient::recording`] client),
+//! save it next to your tests, and replay it with [`DnsClient::playback`].
+
+use std::{collections::BTreeMap, fs, net::IpAddr, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dns::DnsClient;
+
+/// A single recorded DNS answer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FixtureAnswer {
+    /// The query returned these addresses
+    Records { ips: Vec<IpAddr>, ttl_secs: u64 },
+    /// The TXT query returned these strings
+    Text { texts: Vec<String>, ttl_secs: u64 },
+    /// The MX query returned these exchange hosts, by preference
+    Exchanges { hosts: Vec<String>, ttl_secs: u64 },
+    /// The query returned NXDOMAIN / no records
+    NoRecords,
+}
+
+impl FixtureAnswer {
+    /// Remaining TTL of the recorded answer
+    pub fn ttl(&self) -> Duration {
+        match self {
+            FixtureAnswer::Records { ttl_secs, .. }
+            | FixtureAnswer::Text { ttl_secs, .. }
+            | FixtureAnswer::Exchanges { ttl_secs, .. } => Duration::from_secs(*ttl_secs),
+            FixtureAnswer::NoRecords => Duration::ZERO,
+        }
+    }
+}
+
+/// Recorded DNS answers keyed by query name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fixture {
+    /// A/AAAA answers
+    pub answers: BTreeMap<String, FixtureAnswer>,
+    /// TXT answers
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub txt_answers: BTreeMap<String, FixtureAnswer>,
+    /// MX answers
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mx_answers: BTreeMap<String, FixtureAnswer>,
+}
+
+impl Fixture {
+    /// Create an empty fixture
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Query the given domains against the live provider and record the answers
+    ///
+    /// Failed queries (timeouts, SERVFAIL) are logged and left out of the
+    /// fixture.
+    pub async fn record(domains: &[&str], timeout: Duration) -> Self {
+        let client = DnsClient::with_timeout(timeout).recording();
+
+        for domain in domains {
+            if let Err(err) = client.query_surbl(domain).await {
+                tracing::warn!("Failed to record {domain}: {err}");
+            }
+        }
+
+        client.recorded_fixture().unwrap_or_default()
+    }
+
+    /// Add an answer for a query name
+    pub fn insert(&mut self, query: impl Into<String>, answer: FixtureAnswer) {
+        self.answers.insert(query.into().to_lowercase(), answer);
+    }
+
+    /// Look up the recorded answer for a query name
+    pub fn get(&self, query: &str) -> Option<&FixtureAnswer> {
+        self.answers.get(&query.to_lowercase())
+    }
+
+    /// Add a TXT answer for a query name
+    pub fn insert_txt(&mut self, query: impl Into<String>, answer: FixtureAnswer) {
+        self.txt_answers.insert(query.into().to_lowercase(), answer);
+    }
+
+    /// Look up the recorded TXT answer for a query name
+    pub fn get_txt(&self, query: &str) -> Option<&FixtureAnswer> {
+        self.txt_answers.get(&query.to_lowercase())
+    }
+
+    /// Add an MX answer for a query name
+    pub fn insert_mx(&mut self, query: impl Into<String>, answer: FixtureAnswer) {
+        self.mx_answers.insert(query.into().to_lowercase(), answer);
+    }
+
+    /// Look up the recorded MX answer for a query name
+    pub fn get_mx(&self, query: &str) -> Option<&FixtureAnswer> {
+        self.mx_answers.get(&query.to_lowercase())
+    }
+
+    /// Load a fixture from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Save the fixture as pretty-printed JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)
+    }
+}
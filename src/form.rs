@@ -0,0 +1,236 @@
+//! Validation of forms with several email fields
+//!
+//! B2B onboarding forms collect a handful of addresses at once (billing,
+//! technical, abuse contacts), each with its own rules. An [`EmailForm`]
+//! declares a [`FieldPolicy`] per field and checks all of them in one batch,
+//! returning a per-field result and a combined verdict.
+//!
+//! ```rust
+//! use mailguard_rs::{EmailForm, FieldPolicy, Fixture, MailGuard, dns::DnsClient};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let guard = MailGuard::builder()
+//!         .dns_client(DnsClient::playback(Fixture::new()))
+//!         .build()?;
+//!
+//!     let form = EmailForm::new()
+//!         .field("billing", FieldPolicy::required())
+//!         .field("abuse", FieldPolicy::optional());
+//!
+//!     let report = form
+//!         .validate(&guard, [("billing", "ap@example.com"), ("abuse", "")])
+//!         .await;
+//!     assert!(report.is_accepted());
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    address::ValidationDetail,
+    detector::{EmailStatus, MailGuard},
+    error::MailGuardError,
+    threat::ThreatType,
+};
+
+/// Rules for one email field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldPolicy {
+    /// Reject the form if the field is missing or blank
+    pub required: bool,
+    /// Reject addresses whose domain is a threat
+    pub reject_threats: bool,
+    /// Reject addresses whose domain looks like a typo of a major provider
+    ///
+    /// Only effective when the detector has `detect_typos` enabled.
+    pub reject_typos: bool,
+    /// Accept the address when the lookup itself fails (timeouts,
+    /// throttling), instead of rejecting it
+    pub accept_on_error: bool,
+}
+
+impl FieldPolicy {
+    /// A field that must be filled in with a non-threat address
+    pub fn required() -> Self {
+        Self {
+            required: true,
+            ..Self::optional()
+        }
+    }
+
+    /// A field that may be left blank, but must be a non-threat address if
+    /// filled in
+    pub fn optional() -> Self {
+        Self {
+            required: false,
+            reject_threats: true,
+            reject_typos: false,
+            accept_on_error: true,
+        }
+    }
+
+    /// Also reject likely typos of major providers
+    pub fn rejecting_typos(mut self) -> Self {
+        self.reject_typos = true;
+        self
+    }
+}
+
+impl Default for FieldPolicy {
+    fn default() -> Self {
+        Self::optional()
+    }
+}
+
+/// Why a field was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldRejection {
+    /// A required field was missing or blank
+    Missing,
+    /// The value is not a valid address
+    Invalid {
+        reason: String,
+        /// The syntax rule broken and where, to point the user at it
+        detail: Option<ValidationDetail>,
+    },
+    /// The domain is a threat
+    Threat(Option<ThreatType>),
+    /// The domain looks like a typo of this provider
+    Typo(String),
+    /// The lookup failed and the policy doesn't accept on error
+    CheckFailed(String),
+}
+
+/// Result for one field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldResult {
+    /// Status of the address, if the field was filled in and checked
+    pub status: Option<EmailStatus>,
+    /// Why the field was rejected, `None` if it was accepted
+    pub rejection: Option<FieldRejection>,
+}
+
+impl FieldResult {
+    /// Whether the field passed its policy
+    pub fn is_accepted(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+/// Per-field results of a form, keyed by field name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FormReport {
+    pub fields: BTreeMap<String, FieldResult>,
+}
+
+impl FormReport {
+    /// Whether every field passed its policy
+    pub fn is_accepted(&self) -> bool {
+        self.fields.values().all(FieldResult::is_accepted)
+    }
+
+    /// Rejected fields and why, in field name order
+    pub fn rejections(&self) -> impl Iterator<Item = (&str, &FieldRejection)> {
+        self.fields.iter().filter_map(|(name, result)| {
+            result
+                .rejection
+                .as_ref()
+                .map(|rejection| (name.as_str(), rejection))
+        })
+    }
+}
+
+/// Named email fields and their policies
+#[derive(Debug, Clone, Default)]
+pub struct EmailForm {
+    fields: BTreeMap<String, FieldPolicy>,
+}
+
+impl EmailForm {
+    /// Create a form without fields
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a field, replacing any earlier policy with the same name
+    pub fn field(mut self, name: impl Into<String>, policy: FieldPolicy) -> Self {
+        self.fields.insert(name.into(), policy);
+        self
+    }
+
+    /// Validate submitted values against the form
+    ///
+    /// Values for fields the form doesn't declare are ignored. All addresses
+    /// are checked in one batch, so fields sharing a domain cost one lookup.
+    pub async fn validate<'a, I>(&self, guard: &MailGuard, values: I) -> FormReport
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let values: BTreeMap<&str, &str> = values
+            .into_iter()
+            .filter(|(name, _)| self.fields.contains_key(*name))
+            .map(|(name, value)| (name, value.trim()))
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+
+        let emails: Vec<&str> = values.values().copied().collect();
+        let results = guard.check_emails_batch(&emails).await;
+        let mut checked: BTreeMap<&str, _> = values.keys().copied().zip(results).collect();
+
+        let mut report = FormReport::default();
+        for (name, policy) in &self.fields {
+            let result = match checked.remove(name.as_str()) {
+                None if policy.required => FieldResult {
+                    status: None,
+                    rejection: Some(FieldRejection::Missing),
+                },
+                None => FieldResult {
+                    status: None,
+                    rejection: None,
+                },
+                Some(Ok(status)) => FieldResult {
+                    rejection: apply_policy(policy, &status),
+                    status: Some(status),
+                },
+                Some(Err(MailGuardError::InvalidEmail { reason, detail, .. })) => FieldResult {
+                    status: None,
+                    rejection: Some(FieldRejection::Invalid { reason, detail }),
+                },
+                Some(Err(MailGuardError::InvalidDomain { reason, .. })) => FieldResult {
+                    status: None,
+                    rejection: Some(FieldRejection::Invalid {
+                        reason,
+                        detail: None,
+                    }),
+                },
+                Some(Err(_)) if policy.accept_on_error => FieldResult {
+                    status: None,
+                    rejection: None,
+                },
+                Some(Err(err)) => FieldResult {
+                    status: None,
+                    rejection: Some(FieldRejection::CheckFailed(err.to_string())),
+                },
+            };
+            report.fields.insert(name.clone(), result);
+        }
+
+        report
+    }
+}
+
+fn apply_policy(policy: &FieldPolicy, status: &EmailStatus) -> Option<FieldRejection> {
+    if policy.reject_threats && status.is_threat {
+        return Some(FieldRejection::Threat(status.threat_type.clone()));
+    }
+    if policy.reject_typos
+        && let Some(provider) = &status.typo_of
+    {
+        return Some(FieldRejection::Typo(provider.clone()));
+    }
+    None
+}
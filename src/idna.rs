@@ -0,0 +1,42 @@
+//! Internationalized domain names
+//!
+//! DNS only carries ASCII, so Unicode domains (`example.中国`) are checked
+//! in their punycode form (`example.xn--fiqs8s`, RFC 3492). Parsed
+//! addresses store the ASCII form in [`Domain`](crate::address::Domain);
+//! use [`domain_to_unicode`] to display it again.
+//!
+//! Domains are mapped and normalized as UTS #46 specifies, so a label
+//! typed in composed or decomposed form (`é` or `e` + `\u{301}`), or with
+//! full-width letters, converts to the same ASCII name.
+//!
+//! ```rust
+//! use mailguard_rs::idna::{domain_to_ascii, domain_to_unicode};
+//!
+//! assert_eq!(domain_to_ascii("Bücher.example").unwrap(), "xn--bcher-kva.example");
+//! assert_eq!(domain_to_unicode("example.xn--fiqs8s"), "example.中国");
+//! ```
+
+/// Prefix of punycode labels
+pub const ACE_PREFIX: &str = "xn--";
+
+/// Convert a domain to its lowercased ASCII form, encoding Unicode labels
+pub fn domain_to_ascii(domain: &str) -> Result<String, &'static str> {
+    ::idna::domain_to_ascii(domain).map_err(|_| "Domain label can't be encoded")
+}
+
+/// Convert a domain to Unicode, decoding punycode labels
+///
+/// Labels that aren't valid punycode are kept as they are.
+pub fn domain_to_unicode(domain: &str) -> String {
+    domain
+        .split('.')
+        .map(|label| {
+            label
+                .get(..ACE_PREFIX.len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(ACE_PREFIX))
+                .and_then(|_| ::idna::punycode::decode_to_string(&label[ACE_PREFIX.len()..]))
+                .unwrap_or_else(|| label.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
@@ -2,17 +2,73 @@
 //!
 //! Detect temporary emails and malicious domains by querying SURBL DNS records.
 
+pub mod address;
+pub mod address_list;
+pub mod aggregation;
+pub mod anonymize;
+pub mod blocking;
 pub mod cache;
+pub mod canonical;
+pub mod check;
+pub mod compare;
+pub mod cookbook;
+pub mod coverage;
 pub mod detector;
+pub mod disk_cache;
 pub mod dns;
+pub mod ensemble;
 pub mod error;
+pub mod fixture;
+pub mod form;
+pub mod idna;
+pub mod lists;
+pub mod memcached;
+pub mod normalize;
+pub mod offline;
+pub mod rank;
+pub mod reason;
+pub mod retry;
+pub mod scan;
+pub mod simulate;
+pub mod suffix;
+pub mod summary;
+pub mod suspicion;
 pub mod threat;
+pub mod tiered_cache;
+pub mod typo;
+pub mod validator;
 
-pub use detector::{DomainStatus, EmailStatus, MailGuard, MailGuardConfig};
-pub use error::MailGuardError;
-pub use threat::ThreatType;
+pub use address::{Domain, EmailAddress, LocalPart, ValidationDetail, ValidationRule};
+pub use address_list::{AddressListError, ParsedAddressList, parse_address_list};
+pub use aggregation::ThreatAggregation;
+pub use anonymize::{AnonymizedStatus, Anonymizer};
+pub use cache::{CacheBackend, CacheHooks, CacheRecord, CachedVerdict, ShardStats};
+pub use canonical::{to_canonical_json, to_canonical_json_pretty};
+pub use check::{Check, CheckOutcome, CheckResult};
+pub use compare::VerdictComparison;
+pub use coverage::{CheckCoverage, CoverageReport};
+pub use detector::{DomainStatus, EmailStatus, MailGuard, MailGuardBuilder, MailGuardConfig};
+pub use disk_cache::DiskCache;
+pub use dns::QueryType;
+pub use ensemble::{Ensemble, EnsembleVote, Vote};
+pub use error::{ErrorClass, MailGuardError};
+pub use fixture::{Fixture, FixtureAnswer};
+pub use form::{EmailForm, FieldPolicy, FieldRejection, FieldResult, FormReport};
+pub use memcached::MemcachedCache;
+pub use normalize::{Subaddress, is_same_mailbox, normalize_email, parse_subaddress};
+pub use offline::OfflineMailGuard;
+pub use rank::DomainRanks;
+pub use reason::Reason;
+pub use retry::{RetryKind, RetryPolicy};
+pub use scan::{EmailSpan, ScanMatch, extract_emails};
+pub use simulate::{Confusion, SimulationReport, simulate};
+pub use summary::{BatchOutcome, BatchSummary, DomainRollup};
+pub use suspicion::{LocalPartSuspicion, SuspicionSignal};
+pub use threat::{Taxonomy, ThreatInfo, ThreatType};
+pub use tiered_cache::TieredCache;
+pub use validator::EmailValidator;
 
-/// Check a single email address
+/// Check a single email address with the [global](MailGuard::global) detector
 ///
 /// # Example
 ///
@@ -27,18 +83,15 @@ pub use threat::ThreatType;
 /// }
 /// ```
 pub async fn check_email(email: &str) -> Result<EmailStatus, MailGuardError> {
-    let detector = MailGuard::new();
-    detector.check_email(email).await
+    MailGuard::global().check_email(email).await
 }
 
-/// Check domain
+/// Check domain with the [global](MailGuard::global) detector
 pub async fn check_domain(domain: &str) -> Result<DomainStatus, MailGuardError> {
-    let detector = MailGuard::new();
-    detector.check_domain(domain).await
+    MailGuard::global().check_domain(domain).await
 }
 
-/// Batch check emails
+/// Batch check emails with the [global](MailGuard::global) detector
 pub async fn check_emails_batch(emails: &[&str]) -> Vec<Result<EmailStatus, MailGuardError>> {
-    let detector = MailGuard::new();
-    detector.check_emails_batch(emails).await
+    MailGuard::global().check_emails_batch(emails).await
 }
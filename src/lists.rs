@@ -0,0 +1,283 @@
+//! User-supplied allow and block lists
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+};
+
+use regex::{Regex, RegexSet};
+
+use crate::error::{MailGuardError, Result};
+
+/// Prefix marking a list entry as a regular expression
+pub const REGEX_PREFIX: &str = "regex:";
+
+/// Set of domain rules matched against a domain
+///
+/// Entries come in three forms:
+///
+/// - Plain domains: `example.com` matches `example.com` and
+///   `mail.example.com`, but not `notexample.com`.
+/// - Wildcards: `*` matches any run of characters, including dots, so
+///   `*.temp-mail.*` matches `inbox.temp-mail.org`.
+/// - Regular expressions: entries starting with `regex:`, e.g.
+///   `regex:^tmp[0-9]+\.com$`. Matching is case-insensitive and unanchored
+///   unless the pattern anchors itself.
+///
+/// Wildcards and regular expressions are compiled into a single
+/// [`RegexSet`] when the list is built, and recompiled when a pattern is
+/// inserted or removed.
+#[derive(Debug, Clone, Default)]
+pub struct DomainList {
+    domains: HashSet<String>,
+    sources: Vec<String>,
+    patterns: Option<RegexSet>,
+}
+
+impl DomainList {
+    /// Build a list from rule entries, skipping invalid patterns with a warning
+    pub fn new<I, S>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut domains = HashSet::new();
+        let mut patterns = Vec::new();
+
+        for entry in entries {
+            match parse_entry(entry.as_ref()) {
+                Ok(Some(Rule::Domain(domain))) => {
+                    domains.insert(domain);
+                }
+                Ok(Some(Rule::Pattern(pattern))) => patterns.push(pattern),
+                Ok(None) => {}
+                Err(err) => tracing::warn!("Skipping list entry: {err}"),
+            }
+        }
+
+        // Every pattern compiled on its own, so this only fails on size limits
+        let compiled = compile_patterns(&patterns).unwrap_or_else(|err| {
+            tracing::warn!("Skipping list patterns: {err}");
+            None
+        });
+        if compiled.is_none() {
+            patterns.clear();
+        }
+
+        Self {
+            domains,
+            sources: patterns,
+            patterns: compiled,
+        }
+    }
+
+    /// Build a list from rule entries, failing on the first invalid pattern
+    pub fn try_new<I, S>(entries: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut domains = HashSet::new();
+        let mut patterns = Vec::new();
+
+        for entry in entries {
+            match parse_entry(entry.as_ref())? {
+                Some(Rule::Domain(domain)) => {
+                    domains.insert(domain);
+                }
+                Some(Rule::Pattern(pattern)) => patterns.push(pattern),
+                None => {}
+            }
+        }
+
+        Ok(Self {
+            domains,
+            patterns: compile_patterns(&patterns)?,
+            sources: patterns,
+        })
+    }
+
+    /// Add a rule entry, returning whether it was new
+    ///
+    /// Fails with [`MailGuardError::InvalidRule`] on an invalid pattern,
+    /// leaving the list unchanged.
+    pub fn insert(&mut self, entry: &str) -> Result<bool> {
+        match parse_entry(entry)? {
+            Some(Rule::Domain(domain)) => Ok(self.domains.insert(domain)),
+            Some(Rule::Pattern(pattern)) => {
+                if self.sources.contains(&pattern) {
+                    return Ok(false);
+                }
+                let mut sources = self.sources.clone();
+                sources.push(pattern);
+                self.patterns = compile_patterns(&sources)?;
+                self.sources = sources;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Remove a rule entry, written as it was added, returning whether it
+    /// was present
+    pub fn remove(&mut self, entry: &str) -> bool {
+        match parse_entry(entry) {
+            Ok(Some(Rule::Domain(domain))) => self.domains.remove(&domain),
+            Ok(Some(Rule::Pattern(pattern))) => {
+                let Some(index) = self.sources.iter().position(|source| *source == pattern) else {
+                    return false;
+                };
+                self.sources.remove(index);
+                // A subset of patterns that compiled together compiles again
+                self.patterns = compile_patterns(&self.sources).unwrap_or_default();
+                true
+            }
+            Ok(None) | Err(_) => false,
+        }
+    }
+
+    /// Check if a lowercased domain matches any rule in the list
+    pub fn matches(&self, domain: &str) -> bool {
+        self.matches_domain(domain)
+            || self
+                .patterns
+                .as_ref()
+                .is_some_and(|patterns| patterns.is_match(domain))
+    }
+
+    /// Check the domain and its parents against the plain domain entries
+    fn matches_domain(&self, domain: &str) -> bool {
+        if self.domains.is_empty() {
+            return false;
+        }
+
+        let mut candidate = domain;
+        loop {
+            if self.domains.contains(candidate) {
+                return true;
+            }
+            match candidate.split_once('.') {
+                Some((_, parent)) => candidate = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// Number of rules in the list
+    pub fn len(&self) -> usize {
+        self.domains.len() + self.patterns.as_ref().map_or(0, RegexSet::len)
+    }
+
+    /// Whether the list has no rules
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A [`DomainList`] that can be changed while it is shared
+///
+/// Clones share the same rules, so a check holding one sees every change
+/// made through another right away.
+#[derive(Debug, Clone, Default)]
+pub struct SharedDomainList {
+    inner: Arc<RwLock<DomainList>>,
+}
+
+impl SharedDomainList {
+    /// Share a list
+    pub fn new(list: DomainList) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(list)),
+        }
+    }
+
+    /// See [`DomainList::matches`]
+    pub fn matches(&self, domain: &str) -> bool {
+        self.read().matches(domain)
+    }
+
+    /// See [`DomainList::insert`]
+    pub fn insert(&self, entry: &str) -> Result<bool> {
+        self.write().insert(entry)
+    }
+
+    /// See [`DomainList::remove`]
+    pub fn remove(&self, entry: &str) -> bool {
+        self.write().remove(entry)
+    }
+
+    /// Replace every rule at once
+    pub fn replace(&self, list: DomainList) {
+        *self.write() = list;
+    }
+
+    /// Number of rules in the list
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Whether the list has no rules
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, DomainList> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, DomainList> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl From<DomainList> for SharedDomainList {
+    fn from(list: DomainList) -> Self {
+        Self::new(list)
+    }
+}
+
+/// A parsed list entry
+enum Rule {
+    Domain(String),
+    Pattern(String),
+}
+
+/// Compile pattern entries into one set, or `None` if there are none
+fn compile_patterns(patterns: &[String]) -> Result<Option<RegexSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    RegexSet::new(patterns)
+        .map(Some)
+        .map_err(|err| MailGuardError::InvalidRule(err.to_string()))
+}
+
+/// Parse one list entry, returning `None` for blank entries
+fn parse_entry(entry: &str) -> Result<Option<Rule>> {
+    let entry = entry.trim();
+
+    if let Some(pattern) = entry.strip_prefix(REGEX_PREFIX) {
+        let pattern = format!("(?i){pattern}");
+        // Compile individually so the error names the offending entry
+        Regex::new(&pattern)
+            .map_err(|err| MailGuardError::InvalidRule(format!("{entry}: {err}")))?;
+        return Ok(Some(Rule::Pattern(pattern)));
+    }
+
+    let domain = entry.trim_end_matches('.').to_lowercase();
+    if domain.is_empty() {
+        return Ok(None);
+    }
+
+    if domain.contains('*') {
+        let pattern = domain
+            .split('*')
+            .map(regex::escape)
+            .collect::<Vec<_>>()
+            .join(".*");
+        return Ok(Some(Rule::Pattern(format!("^{pattern}$"))));
+    }
+
+    Ok(Some(Rule::Domain(domain)))
+}
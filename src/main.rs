@@ -1,141 +1,708 @@
-use mailguard_rs::{MailGuard, check_domain, check_email};
+//! `mailguard`: check addresses and domains from the command line
+//!
+//! Exits with 0 if everything checked is clean, 1 if any threat was found
+//! and 2 on usage errors or failed lookups.
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
+use std::{
+    collections::VecDeque,
+    env,
+    fs::File,
+    io::{self, BufWriter, IsTerminal, Write},
+    marker::PhantomData,
+    path::Path,
+    process::ExitCode,
+    sync::Arc,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
 
-    println!("🛡️  MailGuard-RS Temporary Email Detection Tool");
-    println!("================================================");
-
-    // Test some known temporary email domains
-    let test_emails = vec![
-        "test@gmail.com",         // Normal email
-        "user@10minutemail.com",  // Temporary email
-        "temp@guerrillamail.com", // Temporary email
-        "example@mailinator.com", // Temporary email
-        "valid@outlook.com",      // Normal email
-    ];
-
-    println!("\n📧 Testing Email Detection:");
-    println!("--------------------------");
-
-    for email in &test_emails {
-        match check_email(email).await {
-            Ok(status) => {
-                let threat_info = if status.is_threat {
-                    if let Some(threat_type) = &status.threat_type {
-                        format!(
-                            " (⚠️  {description}: {level})",
-                            description = threat_type.description(),
-                            level = threat_type.severity_level()
-                        )
-                    } else {
-                        " (⚠️  Unknown Threat)".to_string()
-                    }
-                } else {
-                    " (✅ Safe)".to_string()
-                };
+use mailguard_rs::{
+    DomainStatus, EmailStatus, MailGuard, MailGuardConfig, MailGuardError, Reason, ThreatType,
+};
+use serde::Serialize;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    task::JoinHandle,
+};
 
-                let cache_info = if status.from_cache { " [Cached]" } else { "" };
-
-                println!(
-                    "  {} -> {}{}{}",
-                    email,
-                    if status.is_threat {
-                        "Temp Email"
-                    } else {
-                        "Normal Email"
-                    },
-                    threat_info,
-                    cache_info
-                );
+const USAGE: &str = "\
+Usage: mailguard <command> [options]
+
+Commands:
+  check <email>...     Check email addresses
+  domain <domain>...   Check domains
+  batch <file>         Check the addresses in a file, one per line or in a
+                       CSV column (\"-\" reads standard input)
+
+Options:
+  --timeout <secs>     DNS timeout in seconds (default: 5)
+  --no-cache           Don't cache verdicts between lookups
+  --format <format>    Output format: text, table, json, ndjson or csv
+                       (default: text)
+  -h, --help           Print this help
+
+Batch options:
+  --input <file>       The file to check, instead of <file>
+  --column <name>      Read addresses from this column of a CSV file with a
+                       header row (default for .csv files: email)
+  --output <file>      Write results to this file instead of standard output
+  --concurrency <n>    Addresses checked at once (default: 16)
+  --no-progress        Don't show progress on standard error";
+
+/// Column read from `.csv` inputs unless `--column` names another
+const DEFAULT_COLUMN: &str = "email";
+
+/// How often the progress line is redrawn
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+enum Command {
+    Check(Vec<String>),
+    Domain(Vec<String>),
+    Batch(Batch),
+    Help,
+}
+
+struct Batch {
+    input: String,
+    column: Option<String>,
+    concurrency: usize,
+    progress: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Text,
+    Table,
+    Json,
+    Ndjson,
+    Csv,
+}
+
+struct Cli {
+    command: Command,
+    config: MailGuardConfig,
+    format: Format,
+    output: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Cli, String> {
+    let mut config = MailGuardConfig::default();
+    let mut format = Format::Text;
+    let mut command = None;
+    let mut operands = Vec::new();
+    let mut input = None;
+    let mut column = None;
+    let mut output = None;
+    let mut concurrency = 16;
+    let mut progress = true;
+
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or(format!("{flag} needs a value"));
+        match arg.as_str() {
+            "-h" | "--help" => command = Some("help".to_string()),
+            "--timeout" => {
+                let secs: f64 = value(&arg)?
+                    .parse()
+                    .map_err(|_| "--timeout takes a number of seconds".to_string())?;
+                config.dns_timeout =
+                    Duration::try_from_secs_f64(secs).map_err(|err| format!("--timeout: {err}"))?;
+            }
+            "--no-cache" => config.enable_cache = false,
+            "--format" => {
+                format = match value(&arg)?.as_str() {
+                    "text" => Format::Text,
+                    "table" => Format::Table,
+                    "json" => Format::Json,
+                    "ndjson" => Format::Ndjson,
+                    "csv" => Format::Csv,
+                    other => return Err(format!("unknown format {other:?}")),
+                }
             }
-            Err(e) => {
-                println!("  {email} -> Error: {e}");
+            "--input" => input = Some(value(&arg)?),
+            "--column" => column = Some(value(&arg)?),
+            "--output" => output = Some(value(&arg)?),
+            "--concurrency" => {
+                concurrency = value(&arg)?
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or("--concurrency takes a positive number")?;
             }
+            "--no-progress" => progress = false,
+            flag if flag.starts_with("--") => return Err(format!("unknown option {flag}")),
+            _ if command.is_none() => command = Some(arg),
+            _ => operands.push(arg),
         }
     }
 
-    // Test domain detection
-    let test_domains = vec![
-        "gmail.com",
-        "10minutemail.com",
-        "guerrillamail.com",
-        "mailinator.com",
-    ];
+    if let Some(input) = input {
+        operands.push(input);
+    }
+    let command = match command.as_deref() {
+        Some("help") => Command::Help,
+        Some("check") if !operands.is_empty() => Command::Check(operands),
+        Some("domain") if !operands.is_empty() => Command::Domain(operands),
+        Some("batch") if operands.len() == 1 => Command::Batch(Batch {
+            input: operands.remove(0),
+            column,
+            concurrency,
+            progress,
+        }),
+        Some(name @ ("check" | "domain" | "batch")) => {
+            return Err(format!("wrong number of arguments for {name}"));
+        }
+        Some(other) => return Err(format!("unknown command {other:?}")),
+        None => return Err("missing command".to_string()),
+    };
+    Ok(Cli {
+        command,
+        config,
+        format,
+        output,
+    })
+}
 
-    println!("\n🌐 Testing Domain Detection:");
-    println!("---------------------------");
+/// What the checks found, for the summary and the exit code
+#[derive(Default)]
+struct Tally {
+    total: usize,
+    threats: usize,
+    errors: usize,
+}
 
-    for domain in &test_domains {
-        match check_domain(domain).await {
-            Ok(status) => {
-                let threat_info = if status.is_threat {
-                    if let Some(threat_type) = &status.threat_type {
-                        format!(
-                            " (⚠️  {description}: Level {level})",
-                            description = threat_type.description(),
-                            level = threat_type.severity_level()
-                        )
-                    } else {
-                        " (⚠️  Unknown Threat)".to_string()
-                    }
+impl Tally {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            Tally { errors: 1.., .. } => ExitCode::from(2),
+            Tally { threats: 1.., .. } => ExitCode::from(1),
+            _ => ExitCode::SUCCESS,
+        }
+    }
+}
+
+/// A result the CLI prints
+///
+/// JSON and NDJSON output the status as the library serializes it; CSV
+/// flattens it into [`Row::COLUMNS`], after an `input` column. Columns are
+/// only ever added at the end, so scripts can rely on their positions.
+trait Row: Serialize {
+    const COLUMNS: &'static [&'static str];
+
+    fn verdict(&self) -> (bool, Option<&ThreatType>);
+
+    /// Confidence of the verdict and whether it came from the cache
+    fn confidence(&self) -> (f32, bool);
+
+    /// Values of [`Row::COLUMNS`]
+    fn fields(&self) -> Vec<String>;
+}
+
+/// CSV values of the verdict fields `EmailStatus` and `DomainStatus` share,
+/// `is_threat` to `crate_version`
+macro_rules! verdict_fields {
+    ($status:expr) => {{
+        let status = $status;
+        let joined = |values: Vec<String>| values.join(";");
+        vec![
+            status.is_threat.to_string(),
+            status
+                .threat_type
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            joined(
+                status
+                    .threats
+                    .iter()
+                    .map(|threat| format!("{}:{}", threat.source_list, threat.kind))
+                    .collect(),
+            ),
+            format!("{:.2}", status.confidence),
+            status.from_cache.to_string(),
+            status.typo_of.clone().unwrap_or_default(),
+            status.rank.map(|rank| rank.to_string()).unwrap_or_default(),
+            joined(status.reasons.iter().map(reason_kind).collect()),
+            joined(status.sources_checked.clone()),
+            status
+                .checked_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+                .to_string(),
+            status.crate_version.clone(),
+        ]
+    }};
+}
+
+const EMAIL_COLUMNS: &[&str] = &[
+    "email",
+    "normalized_email",
+    "domain",
+    "is_threat",
+    "threat_type",
+    "threats",
+    "confidence",
+    "from_cache",
+    "typo_of",
+    "rank",
+    "reasons",
+    "sources_checked",
+    "checked_at",
+    "crate_version",
+];
+
+const DOMAIN_COLUMNS: &[&str] = &[
+    "domain",
+    "is_threat",
+    "threat_type",
+    "threats",
+    "confidence",
+    "from_cache",
+    "typo_of",
+    "rank",
+    "reasons",
+    "sources_checked",
+    "checked_at",
+    "crate_version",
+];
+
+/// The `kind` tag a reason serializes with, e.g. `listed_on`
+fn reason_kind(reason: &Reason) -> String {
+    serde_json::to_value(reason).expect("reasons serialize")["kind"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string()
+}
+
+impl Row for EmailStatus {
+    const COLUMNS: &'static [&'static str] = EMAIL_COLUMNS;
+
+    fn verdict(&self) -> (bool, Option<&ThreatType>) {
+        (self.is_threat, self.threat_type.as_ref())
+    }
+
+    fn confidence(&self) -> (f32, bool) {
+        (self.confidence, self.from_cache)
+    }
+
+    fn fields(&self) -> Vec<String> {
+        let mut fields = vec![
+            self.email.clone(),
+            self.normalized_email.clone(),
+            self.domain.clone(),
+        ];
+        fields.extend(verdict_fields!(self));
+        fields
+    }
+}
+
+impl Row for DomainStatus {
+    const COLUMNS: &'static [&'static str] = DOMAIN_COLUMNS;
+
+    fn verdict(&self) -> (bool, Option<&ThreatType>) {
+        (self.is_threat, self.threat_type.as_ref())
+    }
+
+    fn confidence(&self) -> (f32, bool) {
+        (self.confidence, self.from_cache)
+    }
+
+    fn fields(&self) -> Vec<String> {
+        let mut fields = vec![self.domain.clone()];
+        fields.extend(verdict_fields!(self));
+        fields
+    }
+}
+
+/// Quote a CSV field if it needs it
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn write_csv<'a>(out: &mut dyn Write, fields: impl IntoIterator<Item = &'a str>) -> io::Result<()> {
+    let fields: Vec<_> = fields.into_iter().map(csv_field).collect();
+    writeln!(out, "{}", fields.join(","))
+}
+
+const TABLE_HEADER: [&str; 5] = ["INPUT", "VERDICT", "THREAT", "CONFIDENCE", "CACHED"];
+
+/// Writes results as they come, tallying them; errors go to standard error
+///
+/// Tables are the exception: they are written once every row is in, to
+/// align the columns.
+struct Printer<T> {
+    format: Format,
+    out: Box<dyn Write>,
+    tally: Tally,
+    table: Vec<[String; 5]>,
+    status: PhantomData<fn(T)>,
+}
+
+impl<T: Row> Printer<T> {
+    fn new(format: Format, out: Box<dyn Write>) -> io::Result<Self> {
+        let mut printer = Self {
+            format,
+            out,
+            tally: Tally::default(),
+            table: Vec::new(),
+            status: PhantomData,
+        };
+        match format {
+            Format::Json => write!(printer.out, "[")?,
+            Format::Csv => write_csv(
+                &mut printer.out,
+                ["input"].into_iter().chain(T::COLUMNS.iter().copied()),
+            )?,
+            _ => {}
+        }
+        Ok(printer)
+    }
+
+    fn record(&mut self, input: &str, result: Result<T, MailGuardError>) -> io::Result<()> {
+        let status = match result {
+            Ok(status) => status,
+            Err(err) => {
+                self.tally.total += 1;
+                self.tally.errors += 1;
+                eprintln!("{input}: {err}");
+                return Ok(());
+            }
+        };
+        let (is_threat, threat_type) = status.verdict();
+        match self.format {
+            Format::Text => match threat_type {
+                _ if !is_threat => writeln!(self.out, "{input}\tclean")?,
+                Some(threat_type) => writeln!(self.out, "{input}\tthreat ({threat_type})")?,
+                None => writeln!(self.out, "{input}\tthreat")?,
+            },
+            Format::Table => {
+                let (confidence, from_cache) = status.confidence();
+                self.table.push([
+                    input.to_string(),
+                    if is_threat { "threat" } else { "clean" }.to_string(),
+                    threat_type.map(ToString::to_string).unwrap_or_default(),
+                    format!("{confidence:.2}"),
+                    if from_cache { "yes" } else { "no" }.to_string(),
+                ]);
+            }
+            // One status per line, so large outputs stay greppable
+            Format::Json => {
+                let separator = if self.tally.total == self.tally.errors {
+                    "\n"
                 } else {
-                    " (✅ Safe)".to_string()
+                    ",\n"
                 };
+                let json = serde_json::to_string(&status).expect("statuses serialize");
+                write!(self.out, "{separator}{json}")?;
+            }
+            Format::Ndjson => {
+                let json = serde_json::to_string(&status).expect("statuses serialize");
+                writeln!(self.out, "{json}")?;
+            }
+            Format::Csv => {
+                let fields = status.fields();
+                write_csv(
+                    &mut self.out,
+                    [input].into_iter().chain(fields.iter().map(String::as_str)),
+                )?;
+            }
+        }
+        self.tally.total += 1;
+        self.tally.threats += usize::from(is_threat);
+        Ok(())
+    }
 
-                let cache_info = if status.from_cache { " [Cached]" } else { "" };
-
-                println!(
-                    "  {} -> {}{}{}",
-                    domain,
-                    if status.is_threat {
-                        "Malicious Domain"
-                    } else {
-                        "Normal Domain"
-                    },
-                    threat_info,
-                    cache_info
-                );
+    fn finish(mut self) -> io::Result<Tally> {
+        match self.format {
+            Format::Json => {
+                let printed = self.tally.total - self.tally.errors;
+                writeln!(self.out, "{}]", if printed == 0 { "" } else { "\n" })?;
             }
-            Err(e) => {
-                println!("  {domain} -> Error: {e}");
+            Format::Table => {
+                let header = TABLE_HEADER.map(str::to_string);
+                let mut widths = [0; 5];
+                for row in std::iter::once(&header).chain(&self.table) {
+                    for (width, cell) in widths.iter_mut().zip(row) {
+                        *width = (*width).max(cell.chars().count());
+                    }
+                }
+                for row in std::iter::once(&header).chain(&self.table) {
+                    let cells: Vec<_> = row
+                        .iter()
+                        .zip(widths)
+                        .map(|(cell, width)| format!("{cell:<width$}"))
+                        .collect();
+                    writeln!(self.out, "{}", cells.join("  ").trim_end())?;
+                }
             }
+            _ => {}
         }
+        self.out.flush()?;
+        Ok(self.tally)
     }
+}
 
-    // Test batch detection
-    println!("\n📦 Testing Batch Detection:");
-    println!("----------------------------");
+/// Split a CSV record, unquoting `"quoted, fields"` with `""` escapes
+///
+/// Quoted fields can't span lines.
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().expect("starts with one field");
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+    fields
+}
 
-    let detector = MailGuard::new();
-    let batch_results = detector.check_emails_batch(&test_emails).await;
+/// Addresses of a batch input, read a line at a time
+struct Addresses {
+    lines: tokio::io::Lines<BufReader<Box<dyn AsyncRead + Unpin + Send>>>,
+    /// Index of the address column, for CSV inputs
+    column: Option<usize>,
+    /// Bytes read so far, for progress
+    read: u64,
+}
 
-    for (email, result) in test_emails.iter().zip(batch_results.iter()) {
-        match result {
-            Ok(status) => {
-                println!(
-                    "  {} -> {} (cached: {})",
-                    email,
-                    if status.is_threat { "⚠️ " } else { "✅" },
-                    status.from_cache
-                );
+impl Addresses {
+    async fn open(batch: &Batch) -> Result<(Self, Option<u64>), String> {
+        let failed = |err: io::Error| format!("{}: {err}", batch.input);
+        let (reader, size): (Box<dyn AsyncRead + Unpin + Send>, _) = match batch.input.as_str() {
+            "-" => (Box::new(tokio::io::stdin()), None),
+            path => {
+                let file = tokio::fs::File::open(path).await.map_err(failed)?;
+                let size = file.metadata().await.map_err(failed)?.len();
+                (Box::new(file), Some(size))
+            }
+        };
+        let mut addresses = Self {
+            lines: BufReader::new(reader).lines(),
+            column: None,
+            read: 0,
+        };
+
+        let is_csv = Path::new(&batch.input)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+        let column = batch.column.as_deref().or(is_csv.then_some(DEFAULT_COLUMN));
+        if let Some(column) = column {
+            let header = addresses.next_line().await.map_err(failed)?;
+            let index = header
+                .as_deref()
+                .map(split_csv)
+                .and_then(|names| names.iter().position(|name| name.trim() == column))
+                .ok_or_else(|| format!("{}: no {column:?} column in header", batch.input))?;
+            addresses.column = Some(index);
+        }
+        Ok((addresses, size))
+    }
+
+    async fn next_line(&mut self) -> io::Result<Option<String>> {
+        let line = self.lines.next_line().await?;
+        if let Some(line) = &line {
+            self.read += line.len() as u64 + 1;
+        }
+        Ok(line)
+    }
+
+    /// The next address, skipping blank lines and `#` comments of plain
+    /// inputs
+    async fn next(&mut self) -> io::Result<Option<String>> {
+        while let Some(line) = self.next_line().await? {
+            let address = match self.column {
+                Some(index) => split_csv(&line).into_iter().nth(index).unwrap_or_default(),
+                None if line.trim_start().starts_with('#') => continue,
+                None => line,
+            };
+            let address = address.trim();
+            if !address.is_empty() {
+                return Ok(Some(address.to_string()));
             }
-            Err(e) => {
-                println!("  {email} -> ❌ {e}");
+        }
+        Ok(None)
+    }
+}
+
+/// A progress line on standard error, redrawn in place
+struct Progress {
+    size: Option<u64>,
+    drawn_at: Option<Instant>,
+}
+
+impl Progress {
+    fn update(&mut self, read: u64, tally: &Tally) {
+        if self
+            .drawn_at
+            .is_some_and(|at| at.elapsed() < PROGRESS_INTERVAL)
+        {
+            return;
+        }
+        self.drawn_at = Some(Instant::now());
+
+        let counts = format!(
+            "{} checked, {} threats, {} errors",
+            tally.total, tally.threats, tally.errors
+        );
+        match self.size.filter(|&size| size > 0) {
+            Some(size) => {
+                let done = (read.min(size) * 30 / size) as usize;
+                let percent = read.min(size) * 100 / size;
+                eprint!(
+                    "\r[{}{}] {percent:>3}% {counts}",
+                    "#".repeat(done),
+                    "-".repeat(30 - done)
+                );
             }
+            None => eprint!("\r{counts}"),
         }
     }
 
-    // Display cache statistics
-    if let Some(cache_size) = detector.cache_stats() {
-        println!("\n📊 Cache Statistics: {cache_size} entries");
+    /// Erase the line, e.g. before printing an error; the next update
+    /// redraws it
+    fn clear(&mut self) {
+        if self.drawn_at.take().is_some() {
+            eprint!("\r\x1b[2K");
+        }
     }
+}
 
-    println!("\n✨ Detection Complete!");
+/// Stream the addresses of a batch through `concurrency` checks at a time,
+/// printing results in input order
+///
+/// Progress is shown on a terminal, unless results are printed there too.
+async fn run_batch(
+    detector: Arc<MailGuard>,
+    batch: &Batch,
+    printer: &mut Printer<EmailStatus>,
+    prints_to_terminal: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut addresses, size) = Addresses::open(batch).await?;
+    let show_progress = batch.progress && io::stderr().is_terminal() && !prints_to_terminal;
+    let mut progress = show_progress.then_some(Progress {
+        size,
+        drawn_at: None,
+    });
 
+    let mut pending: VecDeque<(String, JoinHandle<Result<EmailStatus, MailGuardError>>)> =
+        VecDeque::new();
+    let mut exhausted = false;
+    loop {
+        while !exhausted && pending.len() < batch.concurrency {
+            match addresses.next().await? {
+                Some(email) => {
+                    let detector = Arc::clone(&detector);
+                    let task = email.clone();
+                    pending.push_back((
+                        email,
+                        tokio::spawn(async move { detector.check_email(&task).await }),
+                    ));
+                }
+                None => exhausted = true,
+            }
+        }
+        let Some((email, task)) = pending.pop_front() else {
+            break;
+        };
+        let result = task
+            .await
+            .unwrap_or_else(|err| Err(MailGuardError::Runtime(io::Error::other(err))));
+        if let (Some(progress), Err(_)) = (&mut progress, &result) {
+            progress.clear();
+        }
+        printer.record(&email, result)?;
+        if let Some(progress) = &mut progress {
+            progress.update(addresses.read, &printer.tally);
+        }
+    }
+
+    if let Some(progress) = &mut progress {
+        progress.clear();
+    }
     Ok(())
 }
+
+async fn run(cli: Cli) -> Result<Tally, Box<dyn std::error::Error>> {
+    if let Command::Help = cli.command {
+        println!("{USAGE}");
+        return Ok(Tally::default());
+    }
+
+    let detector = Arc::new(MailGuard::try_with_config(cli.config)?);
+    let prints_to_terminal = cli.output.is_none() && io::stdout().is_terminal();
+    let out: Box<dyn Write> = match &cli.output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).map_err(|err| format!("{path}: {err}"))?,
+        )),
+        None => Box::new(io::stdout().lock()),
+    };
+    let started = Instant::now();
+    let is_batch = matches!(cli.command, Command::Batch(_));
+    let tally = match cli.command {
+        Command::Help => unreachable!("handled above"),
+        Command::Check(emails) => {
+            let mut printer = Printer::new(cli.format, out)?;
+            for email in &emails {
+                printer.record(email, detector.check_email(email).await)?;
+            }
+            printer.finish()?
+        }
+        Command::Domain(domains) => {
+            let mut printer = Printer::new(cli.format, out)?;
+            for domain in &domains {
+                printer.record(domain, detector.check_domain(domain).await)?;
+            }
+            printer.finish()?
+        }
+        Command::Batch(batch) => {
+            let mut printer = Printer::new(cli.format, out)?;
+            run_batch(detector, &batch, &mut printer, prints_to_terminal).await?;
+            printer.finish()?
+        }
+    };
+    if is_batch {
+        eprintln!(
+            "Checked {} addresses in {:.1?}: {} clean, {} threats, {} errors",
+            tally.total,
+            started.elapsed(),
+            tally.total - tally.threats - tally.errors,
+            tally.threats,
+            tally.errors
+        );
+    }
+    Ok(tally)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(io::stderr)
+        .init();
+
+    let cli = match parse_args(env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(err) => {
+            eprintln!("mailguard: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(cli).await {
+        Ok(tally) => tally.exit_code(),
+        Err(err) => {
+            eprintln!("mailguard: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Domain cache shared through memcached
+//!
+//! For shops that already run memcached, e.g. for sessions, [`MemcachedCache`]
+//! keeps verdicts there so every instance of a service shares them. It
+//! speaks memcached's text protocol over one TCP connection, reconnecting
+//! after failures.
+//!
+//! ```rust,no_run
+//! use mailguard_rs::{MailGuard, MemcachedCache};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let detector = MailGuard::builder()
+//!     .cache_backend(MemcachedCache::connect("127.0.0.1:11211").await?)
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Memcached may drop verdicts early under memory pressure; that only
+//! costs a DNS query.
+
+use std::{
+    io,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufStream},
+    net::TcpStream,
+    runtime::Handle,
+    sync::Mutex,
+};
+
+use crate::{
+    cache::{CacheBackend, CachedVerdict},
+    error::{MailGuardError, Result},
+};
+
+/// Prefix of every key, unless changed with [`MemcachedCache::with_prefix`]
+pub const DEFAULT_PREFIX: &str = "mailguard:";
+
+/// Longest relative expiry memcached accepts; longer ones are sent as a
+/// Unix timestamp
+const MAX_RELATIVE_EXPIRY: u64 = 30 * 24 * 60 * 60;
+
+/// memcached-backed [`CacheBackend`]
+///
+/// Cheap to clone; clones share the connection.
+#[derive(Clone)]
+pub struct MemcachedCache {
+    connection: Arc<Connection>,
+    prefix: String,
+    timeout: Duration,
+}
+
+struct Connection {
+    addr: String,
+    stream: Mutex<Option<BufStream<TcpStream>>>,
+}
+
+impl MemcachedCache {
+    /// Connect to the memcached server at `addr`, e.g. `127.0.0.1:11211`
+    pub async fn connect(addr: impl Into<String>) -> io::Result<Self> {
+        let addr = addr.into();
+        let stream = BufStream::new(TcpStream::connect(&addr).await?);
+        Ok(Self {
+            connection: Arc::new(Connection {
+                addr,
+                stream: Mutex::new(Some(stream)),
+            }),
+            prefix: DEFAULT_PREFIX.to_string(),
+            timeout: Duration::from_secs(1),
+        })
+    }
+
+    /// Prefix every key with `prefix` instead of [`DEFAULT_PREFIX`], e.g.
+    /// to keep environments sharing a server apart
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Fail requests that take longer than `timeout` (default: 1 second)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{key}", self.prefix)
+    }
+
+    /// Send one request and read its reply, reconnecting first if an
+    /// earlier request failed
+    async fn request(&self, request: Request<'_>) -> Result<Reply> {
+        let mut stream = self.connection.stream.lock().await;
+        let result = tokio::time::timeout(self.timeout, async {
+            if stream.is_none() {
+                let connected = TcpStream::connect(&self.connection.addr).await?;
+                *stream = Some(BufStream::new(connected));
+            }
+            let conn = stream.as_mut().expect("connected above");
+            request.send(conn).await?;
+            request.read_reply(conn).await
+        })
+        .await
+        .unwrap_or_else(|_| {
+            Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "memcached timed out",
+            ))
+        });
+
+        // The connection may be mid-reply; start over with a new one
+        if result.is_err() {
+            *stream = None;
+        }
+        result.map_err(MailGuardError::Storage)
+    }
+}
+
+enum Request<'a> {
+    Get(&'a str),
+    Set(&'a str, &'a [u8], u64),
+    Delete(&'a str),
+    FlushAll,
+}
+
+enum Reply {
+    Value(Option<Vec<u8>>),
+    Done,
+}
+
+impl Request<'_> {
+    async fn send(&self, conn: &mut BufStream<TcpStream>) -> io::Result<()> {
+        match self {
+            Request::Get(key) => conn.write_all(format!("get {key}\r\n").as_bytes()).await?,
+            Request::Set(key, data, expiry) => {
+                let header = format!("set {key} 0 {expiry} {}\r\n", data.len());
+                conn.write_all(header.as_bytes()).await?;
+                conn.write_all(data).await?;
+                conn.write_all(b"\r\n").await?;
+            }
+            Request::Delete(key) => {
+                conn.write_all(format!("delete {key}\r\n").as_bytes())
+                    .await?
+            }
+            Request::FlushAll => conn.write_all(b"flush_all\r\n").await?,
+        }
+        conn.flush().await
+    }
+
+    async fn read_reply(&self, conn: &mut BufStream<TcpStream>) -> io::Result<Reply> {
+        let line = read_line(conn).await?;
+        match (self, line.as_str()) {
+            (Request::Get(_), "END") => Ok(Reply::Value(None)),
+            (Request::Get(_), value) if value.starts_with("VALUE ") => {
+                let length: usize = value
+                    .rsplit(' ')
+                    .next()
+                    .and_then(|length| length.parse().ok())
+                    .ok_or_else(|| protocol_error(value))?;
+                let mut data = vec![0; length + 2];
+                conn.read_exact(&mut data).await?;
+                data.truncate(length);
+                match read_line(conn).await?.as_str() {
+                    "END" => Ok(Reply::Value(Some(data))),
+                    other => Err(protocol_error(other)),
+                }
+            }
+            (Request::Set(..), "STORED")
+            | (Request::Delete(_), "DELETED" | "NOT_FOUND")
+            | (Request::FlushAll, "OK") => Ok(Reply::Done),
+            (_, other) => Err(protocol_error(other)),
+        }
+    }
+}
+
+async fn read_line(conn: &mut BufStream<TcpStream>) -> io::Result<String> {
+    let mut line = String::new();
+    if conn.read_line(&mut line).await? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(line.trim_end().to_string())
+}
+
+fn protocol_error(reply: &str) -> io::Error {
+    io::Error::other(format!("unexpected memcached reply: {reply}"))
+}
+
+/// Expiry field for `ttl`: whole seconds, rounded up, or a Unix timestamp
+/// past memcached's 30-day limit for relative ones
+fn expiry(ttl: Duration) -> u64 {
+    let seconds = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+    if seconds <= MAX_RELATIVE_EXPIRY {
+        return seconds.max(1);
+    }
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    now.as_secs() + seconds
+}
+
+#[async_trait]
+impl CacheBackend for MemcachedCache {
+    async fn get(&self, key: &str) -> Result<Option<CachedVerdict>> {
+        let key = self.key(key);
+        let Reply::Value(data) = self.request(Request::Get(&key)).await? else {
+            return Ok(None);
+        };
+        match data.map(|data| serde_json::from_slice(&data)) {
+            Some(Ok(value)) => Ok(Some(value)),
+            Some(Err(err)) => {
+                // Written by something else; let the next check overwrite it
+                tracing::warn!("Ignoring undecodable memcached value for {key}: {err}");
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, key: &str, value: CachedVerdict, ttl: Duration) -> Result<()> {
+        let key = self.key(key);
+        let data = serde_json::to_vec(&value).expect("verdicts serialize");
+        self.request(Request::Set(&key, &data, expiry(ttl)))
+            .await
+            .map(|_| ())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        let key = self.key(key);
+        self.request(Request::Delete(&key)).await.map(|_| ())
+    }
+
+    /// Flushes the whole server, including entries of other applications
+    /// and prefixes; memcached can't drop only ours
+    ///
+    /// The flush is started in the background and needs a Tokio runtime.
+    fn clear(&self) {
+        let Ok(handle) = Handle::try_current() else {
+            tracing::warn!("Can't flush memcached outside a Tokio runtime");
+            return;
+        };
+        let cache = self.clone();
+        handle.spawn(async move {
+            if let Err(err) = cache.request(Request::FlushAll).await {
+                tracing::warn!("Failed to flush memcached: {err}");
+            }
+        });
+    }
+}
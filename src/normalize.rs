@@ -0,0 +1,132 @@
+//! Email address normalization
+//!
+//! Produces a canonical form of an address so that aliases of the same
+//! mailbox (`John.Doe+promo@GoogleMail.com` and `johndoe@gmail.com`) compare
+//! equal.
+
+use serde::{Deserialize, Serialize};
+
+/// Subaddress (plus-tag) extracted from an email address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subaddress {
+    /// Address with the tag removed (`user@example.com`)
+    pub base_address: String,
+    /// Tag without its separator (`foo` for `user+foo@example.com`)
+    pub tag: String,
+}
+
+/// Provider-specific canonicalization rule
+struct ProviderRule {
+    /// Domains served by this provider
+    domains: &'static [&'static str],
+    /// Domain the canonical address is rewritten to, if the provider has aliases
+    canonical_domain: Option<&'static str>,
+    /// Whether dots in the local part are ignored by the provider
+    ignore_dots: bool,
+    /// Separator that starts a subaddress tag
+    tag_separator: char,
+}
+
+const PROVIDER_RULES: &[ProviderRule] = &[
+    ProviderRule {
+        domains: &["gmail.com", "googlemail.com"],
+        canonical_domain: Some("gmail.com"),
+        ignore_dots: true,
+        tag_separator: '+',
+    },
+    ProviderRule {
+        domains: &["outlook.com", "hotmail.com", "live.com", "msn.com"],
+        canonical_domain: None,
+        ignore_dots: false,
+        tag_separator: '+',
+    },
+    ProviderRule {
+        domains: &["yahoo.com", "ymail.com", "rocketmail.com"],
+        canonical_domain: None,
+        ignore_dots: false,
+        tag_separator: '-',
+    },
+    ProviderRule {
+        domains: &["protonmail.com", "protonmail.ch", "proton.me", "pm.me"],
+        canonical_domain: Some("proton.me"),
+        ignore_dots: false,
+        tag_separator: '+',
+    },
+];
+
+/// Subaddress separator used for domains without a specific rule
+const DEFAULT_TAG_SEPARATOR: char = '+';
+
+/// Normalize an email address to its canonical form
+///
+/// The address is lowercased, the subaddress tag is removed and
+/// provider-specific rules (Gmail ignoring dots, domain aliases) are applied.
+/// Input without an `@` is returned lowercased.
+pub fn normalize_email(email: &str) -> String {
+    let email = email.trim().to_lowercase();
+    let Some((local, domain)) = email.rsplit_once('@') else {
+        return email;
+    };
+
+    let rule = provider_rule(domain);
+    let tag_separator = rule.map_or(DEFAULT_TAG_SEPARATOR, |rule| rule.tag_separator);
+    let mut local = strip_tag(local, tag_separator).to_string();
+    let mut domain = domain;
+
+    if let Some(rule) = rule {
+        if rule.ignore_dots {
+            local.retain(|c| c != '.');
+        }
+        if let Some(canonical_domain) = rule.canonical_domain {
+            domain = canonical_domain;
+        }
+    }
+
+    format!("{local}@{domain}")
+}
+
+/// Check whether two addresses reach the same mailbox
+///
+/// Both addresses are normalized with [`normalize_email`], so dots (for
+/// Gmail), subaddress tags and provider domain aliases are ignored.
+pub fn is_same_mailbox(a: &str, b: &str) -> bool {
+    normalize_email(a) == normalize_email(b)
+}
+
+/// Extract the subaddress tag of an email address, if it has one
+///
+/// Uses the provider's tag separator (`-` for Yahoo, `+` otherwise). The
+/// base address keeps the original casing of the input.
+pub fn parse_subaddress(email: &str) -> Option<Subaddress> {
+    let (local, domain) = email.trim().rsplit_once('@')?;
+    let separator = provider_rule(&domain.to_lowercase())
+        .map_or(DEFAULT_TAG_SEPARATOR, |rule| rule.tag_separator);
+
+    let base = strip_tag(local, separator);
+    if base.len() == local.len() {
+        return None;
+    }
+
+    Some(Subaddress {
+        base_address: format!("{base}@{domain}"),
+        tag: local[base.len() + separator.len_utf8()..].to_string(),
+    })
+}
+
+/// Find the canonicalization rule for a lowercased domain
+fn provider_rule(domain: &str) -> Option<&'static ProviderRule> {
+    PROVIDER_RULES
+        .iter()
+        .find(|rule| rule.domains.contains(&domain))
+}
+
+/// Remove the subaddress tag from a local part
+///
+/// A leading separator is kept so `+user` doesn't collapse to an empty
+/// local part.
+fn strip_tag(local: &str, separator: char) -> &str {
+    match local.find(separator) {
+        Some(pos) if pos > 0 => &local[..pos],
+        _ => local,
+    }
+}
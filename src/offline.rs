@@ -0,0 +1,268 @@
+//! Detector that cannot perform network IO
+//!
+//! [`OfflineMailGuard`] holds no DNS client and its methods are synchronous,
+//! so code using it provably never sends a lookup out of the process. It
+//! decides from the allow and block lists of a [`MailGuardConfig`] and from
+//! verdicts imported from earlier online runs; anything else is reported as
+//! safe, with no check having decided (see [`DomainStatus::check_results`]).
+//!
+//! ```rust
+//! use mailguard_rs::{MailGuardConfig, OfflineMailGuard, ThreatType};
+//!
+//! let config = MailGuardConfig {
+//!     blocklist: ["blocked.test".to_string()].into_iter().collect(),
+//!     ..Default::default()
+//! };
+//! let mut guard = OfflineMailGuard::with_config(&config)?;
+//! guard.import_verdicts([("tempmail.test", Some(ThreatType::Spam))]);
+//!
+//! assert!(guard.check_email("user@blocked.test")?.is_threat);
+//! assert!(guard.check_email("user@tempmail.test")?.is_threat);
+//! assert!(!guard.check_email("user@example.com")?.is_threat);
+//! # Ok::<(), mailguard_rs::MailGuardError>(())
+//! ```
+
+use std::{collections::HashMap, time::SystemTime};
+
+use crate::{
+    check::{ALLOWLIST, BLOCKLIST, CheckOutcome, CheckResult},
+    detector::{
+        CRATE_VERSION, DomainStatus, EmailStatus, MailGuardConfig, by_severity, email_status,
+        parse_address, score_local_part_of, sources_checked, threats_of, verdict,
+        verdict_confidence,
+    },
+    dns::ascii_domain,
+    error::Result,
+    lists::DomainList,
+    reason::explain,
+    threat::ThreatType,
+    typo,
+    validator::EmailValidator,
+};
+
+/// Name of the lookup in imported verdicts, as reported in `check_results`
+pub const IMPORTED: &str = "imported";
+
+/// Detector built only from local data
+#[derive(Debug, Clone)]
+pub struct OfflineMailGuard {
+    allowlist: DomainList,
+    blocklist: DomainList,
+    verdicts: HashMap<String, Option<ThreatType>>,
+    /// Providers never reported as typos
+    trusted_providers: DomainList,
+    detect_typos: bool,
+    score_local_parts: bool,
+    email_validator: Option<EmailValidator>,
+    min_blocking_severity: u8,
+    severity_overrides: HashMap<ThreatType, u8>,
+}
+
+impl Default for OfflineMailGuard {
+    fn default() -> Self {
+        Self {
+            allowlist: DomainList::default(),
+            blocklist: DomainList::default(),
+            verdicts: HashMap::new(),
+            trusted_providers: DomainList::default(),
+            detect_typos: false,
+            score_local_parts: false,
+            email_validator: None,
+            min_blocking_severity: MailGuardConfig::default().min_blocking_severity,
+            severity_overrides: HashMap::new(),
+        }
+    }
+}
+
+impl OfflineMailGuard {
+    /// Create an offline detector with no lists and no imported verdicts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the lists, typo detection, local-part scoring, validation and
+    /// severity settings of a configuration
+    ///
+    /// Network settings are ignored. Fails on invalid list rules.
+    pub fn with_config(config: &MailGuardConfig) -> Result<Self> {
+        Ok(Self {
+            allowlist: DomainList::try_new(&config.allowlist)?,
+            blocklist: DomainList::try_new(&config.blocklist)?,
+            verdicts: HashMap::new(),
+            trusted_providers: DomainList::try_new(&config.trusted_providers)?,
+            detect_typos: config.detect_typos,
+            score_local_parts: config.score_local_parts,
+            email_validator: config.email_validator.clone(),
+            min_blocking_severity: config.min_blocking_severity,
+            severity_overrides: config.severity_overrides.clone(),
+        })
+    }
+
+    /// Import verdicts by domain, `None` meaning the domain is known clean
+    ///
+    /// Later imports replace earlier verdicts for the same domain.
+    pub fn import_verdicts<I, S>(&mut self, verdicts: I)
+    where
+        I: IntoIterator<Item = (S, Option<ThreatType>)>,
+        S: AsRef<str>,
+    {
+        for (domain, threat_type) in verdicts {
+            self.verdicts
+                .insert(domain.as_ref().to_lowercase(), threat_type);
+        }
+    }
+
+    /// Import the verdicts of statuses returned by an online detector
+    ///
+    /// Threats without a type (local blocklist hits) are skipped, since the
+    /// imported verdicts can't tell them apart from clean domains.
+    pub fn import_statuses<'a>(&mut self, statuses: impl IntoIterator<Item = &'a DomainStatus>) {
+        self.import_verdicts(
+            statuses
+                .into_iter()
+                .filter(|status| !status.is_threat || status.threat_type.is_some())
+                .map(|status| (status.domain.as_str(), status.threat_type.clone())),
+        );
+    }
+
+    /// Number of imported verdicts
+    pub fn len(&self) -> usize {
+        self.verdicts.len()
+    }
+
+    /// Whether no verdicts have been imported
+    pub fn is_empty(&self) -> bool {
+        self.verdicts.is_empty()
+    }
+
+    /// Check a single email address
+    pub fn check_email(&self, email: &str) -> Result<EmailStatus> {
+        let address = parse_address(email, self.email_validator.as_ref())?;
+        let domain_status = self.check_domain(address.domain.as_str())?;
+        let mut status = email_status(email, &address, domain_status);
+        if self.score_local_parts {
+            score_local_part_of(&mut status, address.local_part.as_str());
+        }
+        Ok(status)
+    }
+
+    /// Check a domain
+    ///
+    /// `from_cache` is set when an imported verdict decided.
+    pub fn check_domain(&self, domain: &str) -> Result<DomainStatus> {
+        let domain = ascii_domain(domain)?;
+
+        let typo_of = if self.detect_typos {
+            typo::likely_typo_excluding(&domain, &self.trusted_providers).map(str::to_string)
+        } else {
+            None
+        };
+
+        let mut check_results = Vec::new();
+        let (outcome, from_cache) = self.run_checks(&domain, &mut check_results);
+
+        let (is_threat, threat_type) = verdict(
+            outcome,
+            self.min_blocking_severity,
+            &self.severity_overrides,
+        );
+        let mut threats: Vec<_> = check_results
+            .iter()
+            .flat_map(|result| threats_of(&result.check, &result.outcome))
+            .collect();
+        by_severity(&mut threats, &self.severity_overrides);
+
+        let mut status = DomainStatus {
+            domain,
+            is_threat,
+            threat_type,
+            threats,
+            confidence: verdict_confidence(&check_results),
+            from_cache,
+            typo_of,
+            rank: None,
+            sources_checked: sources_checked(&check_results, from_cache),
+            check_results,
+            ensemble: None,
+            reasons: Vec::new(),
+            checked_at: SystemTime::now(),
+            crate_version: CRATE_VERSION.to_string(),
+        };
+        status.reasons = explain(&status, self.min_blocking_severity);
+        Ok(status)
+    }
+
+    /// Run the local lists, then the imported verdicts, recording each
+    /// outcome; also returns whether an imported verdict decided
+    fn run_checks(&self, domain: &str, results: &mut Vec<CheckResult>) -> (CheckOutcome, bool) {
+        let mut record = |check: &str, outcome: &CheckOutcome| {
+            results.push(CheckResult {
+                check: check.to_string(),
+                outcome: outcome.clone(),
+            });
+        };
+
+        if self.allowlist.matches(domain) {
+            record(ALLOWLIST, &CheckOutcome::Safe);
+            return (CheckOutcome::Safe, false);
+        }
+        record(ALLOWLIST, &CheckOutcome::Pass);
+
+        if self.blocklist.matches(domain) {
+            record(BLOCKLIST, &CheckOutcome::Threat(None));
+            return (CheckOutcome::Threat(None), false);
+        }
+        record(BLOCKLIST, &CheckOutcome::Pass);
+
+        let (outcome, from_cache) = match self.verdicts.get(domain) {
+            Some(Some(threat_type)) => (CheckOutcome::Threat(Some(threat_type.clone())), true),
+            Some(None) => (CheckOutcome::Safe, true),
+            None => (CheckOutcome::Pass, false),
+        };
+        record(IMPORTED, &outcome);
+        (outcome, from_cache)
+    }
+
+    /// Batch check emails
+    pub fn check_emails_batch(&self, emails: &[&str]) -> Vec<Result<EmailStatus>> {
+        emails.iter().map(|email| self.check_email(email)).collect()
+    }
+
+    /// Batch check domains
+    pub fn check_domains_batch(&self, domains: &[&str]) -> Vec<Result<DomainStatus>> {
+        domains
+            .iter()
+            .map(|domain| self.check_domain(domain))
+            .collect()
+    }
+
+    /// Check addresses from any iterator, pairing each result with its input
+    pub fn check_emails<I, S>(&self, emails: I) -> Vec<(S, Result<EmailStatus>)>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        emails
+            .into_iter()
+            .map(|email| {
+                let result = self.check_email(email.as_ref());
+                (email, result)
+            })
+            .collect()
+    }
+
+    /// Check domains from any iterator, pairing each result with its input
+    pub fn check_domains<I, S>(&self, domains: I) -> Vec<(S, Result<DomainStatus>)>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        domains
+            .into_iter()
+            .map(|domain| {
+                let result = self.check_domain(domain.as_ref());
+                (domain, result)
+            })
+            .collect()
+    }
+}
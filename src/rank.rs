@@ -0,0 +1,109 @@
+//! Popularity ranks from top-sites lists
+//!
+//! Domains near the top of a list such as [Tranco] are long-lived and
+//! heavily used, which makes them an unlikely home for throwaway mailboxes.
+//! [`DomainRanks`] loads such a list; with
+//! [`MailGuardConfig::domain_ranks`](crate::MailGuardConfig::domain_ranks)
+//! set, every status reports the domain's rank, and domains ranked within
+//! [`MailGuardConfig::trusted_rank`](crate::MailGuardConfig::trusted_rank)
+//! are reported safe without a DNS query. Unranked domains get `None`, which
+//! callers can treat as a reason for closer scrutiny.
+//!
+//! [Tranco]: https://tranco-list.eu/
+//!
+//! ```rust
+//! use mailguard_rs::DomainRanks;
+//!
+//! let ranks = DomainRanks::parse("1,google.com\n2,example.com\n")?;
+//! assert_eq!(ranks.rank("mail.google.com"), Some(1));
+//! assert_eq!(ranks.rank("fresh-signup.test"), None);
+//! # Ok::<(), mailguard_rs::MailGuardError>(())
+//! ```
+
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+use crate::error::{MailGuardError, Result};
+
+/// Ranks of popular domains, 1 being the most popular
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct DomainRanks {
+    ranks: HashMap<String, u32>,
+}
+
+impl DomainRanks {
+    /// Create an empty list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a `rank,domain` CSV, as published by Tranco
+    ///
+    /// Blank lines are skipped. When a domain appears twice, the better rank
+    /// is kept.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut ranks = Self::new();
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || MailGuardError::InvalidRule(format!("line {}: {line}", number + 1));
+            let (rank, domain) = line.split_once(',').ok_or_else(invalid)?;
+            let rank = rank.trim().parse().map_err(|_| invalid())?;
+            ranks.insert(domain.trim(), rank);
+        }
+        Ok(ranks)
+    }
+
+    /// Load a `rank,domain` CSV file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Set a domain's rank, keeping the better one if it is already listed
+    pub fn insert(&mut self, domain: &str, rank: u32) {
+        self.ranks
+            .entry(domain.to_lowercase())
+            .and_modify(|existing| *existing = (*existing).min(rank))
+            .or_insert(rank);
+    }
+
+    /// Rank of the domain, or of the closest listed parent domain
+    ///
+    /// Lists rank registrable domains, so `mail.google.com` gets the rank of
+    /// `google.com`.
+    pub fn rank(&self, domain: &str) -> Option<u32> {
+        let domain = domain.to_lowercase();
+        let mut candidate = domain.as_str();
+        loop {
+            if let Some(rank) = self.ranks.get(candidate) {
+                return Some(*rank);
+            }
+            let (_, parent) = candidate.split_once('.')?;
+            if !parent.contains('.') {
+                return None;
+            }
+            candidate = parent;
+        }
+    }
+
+    /// Number of ranked domains
+    pub fn len(&self) -> usize {
+        self.ranks.len()
+    }
+
+    /// Whether the list is empty
+    pub fn is_empty(&self) -> bool {
+        self.ranks.is_empty()
+    }
+}
+
+// Lists run to a million entries; don't dump them into debug output
+impl fmt::Debug for DomainRanks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DomainRanks")
+            .field("len", &self.ranks.len())
+            .finish()
+    }
+}
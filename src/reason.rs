@@ -0,0 +1,142 @@
+//! Machine-readable explanations of verdicts
+//!
+//! Every status carries `reasons`, a list of [`Reason`]s describing the
+//! evidence behind the verdict in check order, so downstream systems can
+//! log, localize and display precisely why an address was flagged or let
+//! through without parsing `check_results` themselves.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    check::{ALLOWLIST, BLOCKLIST, CheckOutcome, MX_FINGERPRINT, RANK, SURBL, TRUSTED_PROVIDER},
+    detector::DomainStatus,
+    dns::SURBL_ZONE,
+    offline::IMPORTED,
+    threat::ThreatType,
+};
+
+/// One piece of evidence behind a verdict
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Reason {
+    /// The domain matched the local allowlist
+    Allowlisted,
+    /// The domain matched the local blocklist
+    Blocklisted,
+    /// A DNS list lists the domain
+    ListedOn {
+        /// Zone that lists the domain
+        provider: String,
+        /// Category from the listing's return code
+        category: Option<ThreatType>,
+    },
+    /// The domain's MX hosts belong to a known disposable-mail backend
+    DisposableMx,
+    /// The domain belongs to a trusted major mail provider
+    TrustedProvider,
+    /// The domain ranks high enough on the top-sites list to be trusted
+    TrustedRank {
+        /// The domain's rank
+        rank: u32,
+    },
+    /// A verdict stored earlier decided, from the cache or imported
+    StoredVerdict {
+        /// Category of the stored verdict
+        category: Option<ThreatType>,
+    },
+    /// A custom check reached a verdict
+    Check {
+        /// [`Check::name`](crate::Check::name) of the check
+        check: String,
+        /// What it concluded
+        outcome: CheckOutcome,
+    },
+    /// An ensemble vote was taken (see [`EnsembleVote`](crate::EnsembleVote))
+    Ensemble {
+        /// Weight of the members that reported a threat
+        listed_weight: u32,
+        /// Weight that was needed to report a threat
+        threshold: u32,
+    },
+    /// The domain is listed, but below the severity that blocks
+    BelowSeverity {
+        /// Category of the listing
+        category: ThreatType,
+        /// Configured [`MailGuardConfig::min_blocking_severity`](crate::MailGuardConfig::min_blocking_severity)
+        min_blocking_severity: u8,
+    },
+    /// The domain is likely a typo of a major provider
+    LikelyTypo {
+        /// Provider it likely mistypes
+        provider: String,
+    },
+}
+
+/// Collect the reasons behind a domain status
+pub(crate) fn explain(status: &DomainStatus, min_blocking_severity: u8) -> Vec<Reason> {
+    let mut reasons = Vec::new();
+
+    for result in &status.check_results {
+        let reason = match (result.check.as_str(), &result.outcome) {
+            (_, CheckOutcome::Pass) => continue,
+            (ALLOWLIST, CheckOutcome::Safe) => Reason::Allowlisted,
+            (BLOCKLIST, CheckOutcome::Threat(_)) => Reason::Blocklisted,
+            (SURBL, CheckOutcome::Threat(category)) => Reason::ListedOn {
+                provider: SURBL_ZONE.to_string(),
+                category: category.clone(),
+            },
+            (MX_FINGERPRINT, CheckOutcome::Threat(_)) => Reason::DisposableMx,
+            (TRUSTED_PROVIDER, CheckOutcome::Safe) => Reason::TrustedProvider,
+            (RANK, CheckOutcome::Safe) => Reason::TrustedRank {
+                rank: status.rank.unwrap_or_default(),
+            },
+            (IMPORTED, CheckOutcome::Threat(category)) => Reason::StoredVerdict {
+                category: category.clone(),
+            },
+            (IMPORTED, CheckOutcome::Safe) => continue,
+            (check, outcome) => Reason::Check {
+                check: check.to_string(),
+                outcome: outcome.clone(),
+            },
+        };
+        reasons.push(reason);
+    }
+
+    // A cache hit stands in for the checks it skipped
+    let imported = status
+        .check_results
+        .iter()
+        .any(|result| result.check == IMPORTED);
+    if status.from_cache
+        && !imported
+        && let Some(category) = &status.threat_type
+    {
+        reasons.push(Reason::StoredVerdict {
+            category: Some(category.clone()),
+        });
+    }
+
+    if let Some(vote) = &status.ensemble {
+        reasons.push(Reason::Ensemble {
+            listed_weight: vote.listed_weight,
+            threshold: vote.threshold,
+        });
+    }
+
+    if !status.is_threat
+        && let Some(category) = &status.threat_type
+    {
+        reasons.push(Reason::BelowSeverity {
+            category: category.clone(),
+            min_blocking_severity,
+        });
+    }
+
+    if let Some(provider) = &status.typo_of {
+        reasons.push(Reason::LikelyTypo {
+            provider: provider.clone(),
+        });
+    }
+
+    reasons
+}
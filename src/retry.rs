@@ -0,0 +1,109 @@
+//! Retrying checks that fail transiently
+//!
+//! A check that errors fails the whole lookup. With
+//! [`MailGuardConfig::retry_policy`](crate::MailGuardConfig::retry_policy)
+//! set, each check is retried on the error kinds the policy names, with
+//! exponential backoff between attempts. This sits above any retries the
+//! resolver does itself, and also covers custom checks.
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use mailguard_rs::{MailGuardConfig, RetryKind, RetryPolicy};
+//!
+//! let config = MailGuardConfig {
+//!     retry_policy: Some(RetryPolicy {
+//!         max_attempts: 3,
+//!         retry_on: vec![RetryKind::Dns, RetryKind::Throttled],
+//!         ..Default::default()
+//!     }),
+//!     ..Default::default()
+//! };
+//! ```
+
+use std::time::Duration;
+
+use crate::error::{MailGuardError, Result};
+
+/// Kinds of check failure a [`RetryPolicy`] can retry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryKind {
+    /// The DNS query failed or timed out
+    Dns,
+    /// The provider is throttling queries
+    ///
+    /// Waits at least as long as the provider asked, giving up when that
+    /// exceeds [`RetryPolicy::max_backoff`].
+    Throttled,
+}
+
+impl RetryKind {
+    /// The retryable kind of an error, if it has one
+    pub fn of(error: &MailGuardError) -> Option<Self> {
+        match error.root_cause() {
+            MailGuardError::DnsError(_) | MailGuardError::Timeout { .. } => Some(RetryKind::Dns),
+            MailGuardError::RetryAfter(_) => Some(RetryKind::Throttled),
+            _ => None,
+        }
+    }
+}
+
+/// How often and how patiently failing checks are retried
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per check, including the first
+    pub max_attempts: u32,
+    /// Error kinds that are retried; others fail right away
+    pub retry_on: Vec<RetryKind>,
+    /// Wait before the first retry, doubled for each one after
+    pub initial_backoff: Duration,
+    /// Longest wait between attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            retry_on: vec![RetryKind::Dns],
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Check that the policy can be applied
+    pub fn validate(&self) -> Result<()> {
+        if self.max_attempts == 0 {
+            return Err(MailGuardError::InvalidConfig(
+                "retry_policy.max_attempts must be at least 1".to_string(),
+            ));
+        }
+        if self.initial_backoff > self.max_backoff {
+            return Err(MailGuardError::InvalidConfig(
+                "retry_policy.initial_backoff must not exceed max_backoff".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Wait before retrying after `error` on attempt `attempt` (1-based),
+    /// or `None` to give up
+    pub fn backoff(&self, error: &MailGuardError, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let kind = RetryKind::of(error).filter(|kind| self.retry_on.contains(kind))?;
+
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_backoff);
+        match (kind, error.retry_after()) {
+            (RetryKind::Throttled, Some(retry_after)) if retry_after > self.max_backoff => None,
+            (RetryKind::Throttled, Some(retry_after)) => Some(exponential.max(retry_after)),
+            _ => Some(exponential),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Finding addresses in free text
+//!
+//! Support tickets, form payloads and log lines mention addresses anywhere
+//! in running text. [`extract_emails`] finds them with their byte offsets,
+//! and [`MailGuard::scan_text`](crate::MailGuard::scan_text) also checks
+//! each one.
+//!
+//! ```rust
+//! use mailguard_rs::scan::extract_emails;
+//!
+//! let text = "Please reply to <jane.doe@example.com> or ops@example.org.";
+//! let found = extract_emails(text);
+//! assert_eq!(found[0].email, "jane.doe@example.com");
+//! assert_eq!(&text[found[1].start..found[1].end], "ops@example.org");
+//! ```
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{detector::EmailStatus, error::Result};
+
+/// Deliberately loose; candidates are validated by the address parser
+static EMAIL_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"[A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+(?:\.[A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+)*@[A-Za-z0-9](?:[A-Za-z0-9-]*[A-Za-z0-9])?(?:\.[A-Za-z0-9](?:[A-Za-z0-9-]*[A-Za-z0-9])?)+",
+    )
+    .expect("email pattern is valid")
+});
+
+/// An address found in a text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmailSpan {
+    /// Byte offset of the first character
+    pub start: usize,
+    /// Byte offset just past the last character
+    pub end: usize,
+    /// The address as it appears in the text
+    pub email: String,
+}
+
+impl AsRef<str> for EmailSpan {
+    fn as_ref(&self) -> &str {
+        &self.email
+    }
+}
+
+/// An address found in a text and the result of checking it
+#[derive(Debug, Clone)]
+pub struct ScanMatch {
+    /// Where the address was found
+    pub span: EmailSpan,
+    /// Result of checking it; candidates the address parser rejects are
+    /// reported as [`MailGuardError::InvalidEmail`](crate::MailGuardError::InvalidEmail)
+    pub result: Result<EmailStatus>,
+}
+
+/// Find address-like substrings, in order of appearance
+pub fn extract_emails(text: &str) -> Vec<EmailSpan> {
+    EMAIL_PATTERN
+        .find_iter(text)
+        .map(|found| EmailSpan {
+            start: found.start(),
+            end: found.end(),
+            email: found.as_str().to_string(),
+        })
+        .collect()
+}
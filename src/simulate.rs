@@ -0,0 +1,163 @@
+//! Replaying labeled data through a candidate configuration
+//!
+//! Collect addresses already known to be good or bad (confirmed fraud,
+//! long-standing customers), run them through a detector built from the
+//! configuration under test and read precision, recall and false-positive
+//! rate for the overall verdict and for every check. This lets thresholds,
+//! ensembles and check orders be tuned against data.
+//!
+//! For every check to be measured on every sample, build the candidate with
+//! `short_circuit: false` and the cache disabled; otherwise skipped checks,
+//! and checks answered from the cache, are not counted for that sample.
+//!
+//! ```rust
+//! use mailguard_rs::{Fixture, MailGuard, MailGuardConfig, dns::DnsClient, simulate::simulate};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let candidate = MailGuard::builder()
+//!         .config(MailGuardConfig {
+//!             enable_cache: false,
+//!             short_circuit: false,
+//!             blocklist: ["burner.test".to_string()].into_iter().collect(),
+//!             ..Default::default()
+//!         })
+//!         .dns_client(DnsClient::playback(Fixture::new()))
+//!         .build()?;
+//!
+//!     let report = simulate(
+//!         &candidate,
+//!         [("fraud@burner.test", true), ("alice@example.com", false)],
+//!     )
+//!     .await;
+//!     assert_eq!(report.overall.recall(), 1.0);
+//!     assert_eq!(report.overall.false_positive_rate(), 0.0);
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{check::CheckOutcome, detector::MailGuard};
+
+/// Confusion matrix of flagged versus labeled-bad samples
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Confusion {
+    /// Bad samples that were flagged
+    pub true_positives: usize,
+    /// Good samples that were flagged
+    pub false_positives: usize,
+    /// Good samples that were not flagged
+    pub true_negatives: usize,
+    /// Bad samples that were not flagged
+    pub false_negatives: usize,
+}
+
+impl Confusion {
+    /// Count one sample
+    pub fn record(&mut self, flagged: bool, is_bad: bool) {
+        match (flagged, is_bad) {
+            (true, true) => self.true_positives += 1,
+            (true, false) => self.false_positives += 1,
+            (false, false) => self.true_negatives += 1,
+            (false, true) => self.false_negatives += 1,
+        }
+    }
+
+    /// Number of counted samples
+    pub fn total(&self) -> usize {
+        self.true_positives + self.false_positives + self.true_negatives + self.false_negatives
+    }
+
+    /// Fraction of flagged samples that were bad (0.0 - 1.0)
+    ///
+    /// Returns 1.0 when nothing was flagged.
+    pub fn precision(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_positives,
+            1.0,
+        )
+    }
+
+    /// Fraction of bad samples that were flagged (0.0 - 1.0)
+    ///
+    /// Returns 1.0 when there were no bad samples.
+    pub fn recall(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_negatives,
+            1.0,
+        )
+    }
+
+    /// Fraction of good samples that were flagged (0.0 - 1.0)
+    ///
+    /// Returns 0.0 when there were no good samples.
+    pub fn false_positive_rate(&self) -> f64 {
+        ratio(
+            self.false_positives,
+            self.false_positives + self.true_negatives,
+            0.0,
+        )
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize, empty: f64) -> f64 {
+    match denominator {
+        0 => empty,
+        denominator => numerator as f64 / denominator as f64,
+    }
+}
+
+/// Results of replaying a labeled dataset
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    /// Final verdicts (`is_threat`) against the labels
+    pub overall: Confusion,
+    /// Each check's own outcome against the labels, by check name
+    ///
+    /// A check counts as flagging a sample when it returned
+    /// [`CheckOutcome::Threat`], whether or not that decided the verdict.
+    pub checks: BTreeMap<String, Confusion>,
+    /// Samples that could not be checked, with the error message
+    pub errors: Vec<(String, String)>,
+}
+
+/// Check every sample and compare with its label, `true` meaning bad
+///
+/// Samples are checked as one batch, so domains shared by several samples
+/// are looked up once.
+pub async fn simulate<'a, I>(guard: &MailGuard, samples: I) -> SimulationReport
+where
+    I: IntoIterator<Item = (&'a str, bool)>,
+{
+    let samples: Vec<(&str, bool)> = samples.into_iter().collect();
+    let emails: Vec<&str> = samples.iter().map(|(email, _)| *email).collect();
+    let results = guard.check_emails_batch(&emails).await;
+
+    let mut report = SimulationReport::default();
+    for ((email, is_bad), result) in samples.into_iter().zip(results) {
+        let status = match result {
+            Ok(status) => status,
+            Err(err) => {
+                report.errors.push((email.to_string(), err.to_string()));
+                continue;
+            }
+        };
+
+        report.overall.record(status.is_threat, is_bad);
+        for result in &status.check_results {
+            let flagged = matches!(result.outcome, CheckOutcome::Threat(_));
+            report
+                .checks
+                .entry(result.check.clone())
+                .or_default()
+                .record(flagged, is_bad);
+        }
+    }
+
+    report
+}
@@ -0,0 +1,126 @@
+//! Public suffixes and registrable domains
+//!
+//! DNS lists list registrable domains (`evil.co.uk`), while addresses often
+//! use hosts below them (`mail.a.evil.co.uk`). [`registrable_domain`] finds
+//! the part a list would list. [`lookup_candidates`] lists the names to
+//! query, from the exact domain up to the registrable one, as SURBL
+//! recommends for multi-label hosts.
+//!
+//! The built-in table, [`MULTI_LABEL_SUFFIXES`], is a hand-picked subset of
+//! the [Public Suffix List](https://publicsuffix.org/), not the full list:
+//! the multi-label suffixes common in mail. Every single-label TLD counts
+//! as a public suffix. A suffix missing from the table is taken for a
+//! registrable domain, so `mail.evil.co.at` resolves to `co.at`: parent
+//! lookups then go one level too high, but never above the TLD, and the
+//! exact domain is always queried first.
+//!
+//! ```rust
+//! use mailguard_rs::suffix::{lookup_candidates, registrable_domain};
+//!
+//! assert_eq!(registrable_domain("mail.evil.co.uk"), Some("evil.co.uk"));
+//! assert_eq!(
+//!     lookup_candidates("a.b.evil.com", 5),
+//!     ["a.b.evil.com", "b.evil.com", "evil.com"]
+//! );
+//! ```
+
+/// Multi-label public suffixes under which domains are registered
+///
+/// Covers the country-code second levels and shared hosting zones that show
+/// up in mail; single-label TLDs need no entry.
+pub const MULTI_LABEL_SUFFIXES: &[&str] = &[
+    "ac.jp",
+    "ac.uk",
+    "co.id",
+    "co.il",
+    "co.in",
+    "co.jp",
+    "co.kr",
+    "co.nz",
+    "co.th",
+    "co.uk",
+    "co.za",
+    "com.ar",
+    "com.au",
+    "com.br",
+    "com.cn",
+    "com.co",
+    "com.hk",
+    "com.mx",
+    "com.my",
+    "com.ng",
+    "com.pe",
+    "com.ph",
+    "com.pk",
+    "com.pl",
+    "com.sg",
+    "com.tr",
+    "com.tw",
+    "com.ua",
+    "com.vn",
+    "edu.au",
+    "gov.uk",
+    "ne.jp",
+    "net.au",
+    "net.br",
+    "net.cn",
+    "or.jp",
+    "org.au",
+    "org.br",
+    "org.uk",
+    "ltd.uk",
+    "me.uk",
+    "plc.uk",
+    "blogspot.com",
+    "github.io",
+    "herokuapp.com",
+    "netlify.app",
+    "pages.dev",
+    "vercel.app",
+];
+
+/// Whether the domain is a public suffix, under which others register
+pub fn is_public_suffix(domain: &str) -> bool {
+    let domain = domain.trim_end_matches('.');
+    !domain.contains('.')
+        || MULTI_LABEL_SUFFIXES
+            .iter()
+            .any(|suffix| suffix.eq_ignore_ascii_case(domain))
+}
+
+/// The registrable part of the domain, or `None` for a public suffix
+pub fn registrable_domain(domain: &str) -> Option<&str> {
+    let domain = domain.trim_end_matches('.');
+    if is_public_suffix(domain) {
+        return None;
+    }
+    let mut candidate = domain;
+    while let Some((_, parent)) = candidate.split_once('.') {
+        if is_public_suffix(parent) {
+            return Some(candidate);
+        }
+        candidate = parent;
+    }
+    None
+}
+
+/// Names to query for the domain, most specific first
+///
+/// Starts with the domain itself and adds up to `max_parents` parent
+/// domains, never going above the registrable domain. A public suffix
+/// yields only itself.
+pub fn lookup_candidates(domain: &str, max_parents: usize) -> Vec<&str> {
+    let mut candidates = vec![domain];
+    let Some(registrable) = registrable_domain(domain) else {
+        return candidates;
+    };
+    let mut candidate = domain;
+    while candidates.len() <= max_parents && candidate.len() > registrable.len() {
+        let Some((_, parent)) = candidate.split_once('.') else {
+            break;
+        };
+        candidates.push(parent);
+        candidate = parent;
+    }
+    candidates
+}
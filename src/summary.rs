@@ -0,0 +1,216 @@
+//! Aggregate statistics for batch checks
+//!
+//! Reporting jobs usually want totals next to the individual results.
+//! [`MailGuard::check_emails_summarized`](crate::MailGuard::check_emails_summarized)
+//! returns a [`BatchSummary`] alongside them; [`BatchSummary::of_emails`]
+//! and [`BatchSummary::of_domains`] summarize results obtained any other
+//! way, e.g. from [`OfflineMailGuard`](crate::OfflineMailGuard).
+//!
+//! Pipelines that retry or dead-letter failures collect the results into a
+//! [`BatchOutcome`], which keeps successes and failures apart:
+//!
+//! ```rust
+//! use mailguard_rs::{BatchOutcome, EmailStatus, OfflineMailGuard};
+//!
+//! let guard = OfflineMailGuard::new();
+//! let outcome: BatchOutcome<_, EmailStatus> = guard
+//!     .check_emails(["user@example.com", "not-an-address"])
+//!     .into_iter()
+//!     .collect();
+//! assert_eq!(outcome.succeeded.len(), 1);
+//! assert!(outcome.retryable().is_empty());
+//! ```
+
+use std::{collections::BTreeMap, time::Duration};
+
+use serde::Serialize;
+
+use crate::{
+    detector::{DomainStatus, EmailStatus},
+    error::{ErrorClass, MailGuardError, Result},
+    threat::ThreatType,
+};
+
+/// Checks and threats for one domain of a batch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DomainRollup {
+    /// Inputs at this domain that were checked
+    pub checked: usize,
+    /// Of those, the ones reported as threats
+    pub threats: usize,
+}
+
+/// Totals over the results of a batch
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BatchSummary {
+    /// Number of inputs
+    pub total: usize,
+    /// Inputs reported as threats
+    pub threats: usize,
+    /// Threats per category, most frequent first; `None` counts threats
+    /// without a type, such as blocklist hits
+    pub threats_by_category: Vec<(Option<ThreatType>, usize)>,
+    /// Results served from a cache
+    pub cache_hits: usize,
+    /// Inputs that failed to check
+    pub errors: usize,
+    /// Wall time the batch took
+    pub elapsed: Duration,
+    /// Per-domain totals, keyed by lowercased domain
+    pub domains: BTreeMap<String, DomainRollup>,
+}
+
+impl BatchSummary {
+    /// Summarize address results
+    pub fn of_emails<S>(results: &[(S, Result<EmailStatus>)], elapsed: Duration) -> Self {
+        let mut summary = Self::started(results.len(), elapsed);
+        for (_, result) in results {
+            match result {
+                Ok(status) => summary.record(
+                    &status.domain,
+                    status.is_threat,
+                    &status.threat_type,
+                    status.from_cache,
+                ),
+                Err(_) => summary.errors += 1,
+            }
+        }
+        summary.finish()
+    }
+
+    /// Summarize domain results
+    pub fn of_domains<S>(results: &[(S, Result<DomainStatus>)], elapsed: Duration) -> Self {
+        let mut summary = Self::started(results.len(), elapsed);
+        for (_, result) in results {
+            match result {
+                Ok(status) => summary.record(
+                    &status.domain,
+                    status.is_threat,
+                    &status.threat_type,
+                    status.from_cache,
+                ),
+                Err(_) => summary.errors += 1,
+            }
+        }
+        summary.finish()
+    }
+
+    /// Share of successful checks served from a cache, 0.0 if none succeeded
+    pub fn cache_hit_ratio(&self) -> f64 {
+        let checked = self.total - self.errors;
+        if checked == 0 {
+            return 0.0;
+        }
+        self.cache_hits as f64 / checked as f64
+    }
+
+    fn started(total: usize, elapsed: Duration) -> Self {
+        Self {
+            total,
+            elapsed,
+            ..Default::default()
+        }
+    }
+
+    fn record(
+        &mut self,
+        domain: &str,
+        is_threat: bool,
+        threat_type: &Option<ThreatType>,
+        from_cache: bool,
+    ) {
+        let rollup = self.domains.entry(domain.to_string()).or_default();
+        rollup.checked += 1;
+        if from_cache {
+            self.cache_hits += 1;
+        }
+        if !is_threat {
+            return;
+        }
+        rollup.threats += 1;
+        self.threats += 1;
+        match self
+            .threats_by_category
+            .iter_mut()
+            .find(|(category, _)| category == threat_type)
+        {
+            Some((_, count)) => *count += 1,
+            None => self.threats_by_category.push((threat_type.clone(), 1)),
+        }
+    }
+
+    fn finish(mut self) -> Self {
+        // Stable, so ties keep the order categories first appeared in
+        self.threats_by_category
+            .sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        self
+    }
+}
+
+/// Results of a batch with successes and failures apart, each paired with
+/// its input and in input order
+#[derive(Debug, Clone)]
+pub struct BatchOutcome<S, T> {
+    /// Inputs that were checked, with their status
+    pub succeeded: Vec<(S, T)>,
+    /// Inputs that failed to check, with their error
+    pub failed: Vec<(S, MailGuardError)>,
+}
+
+impl<S, T> Default for BatchOutcome<S, T> {
+    fn default() -> Self {
+        Self {
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+}
+
+impl<S, T> BatchOutcome<S, T> {
+    /// Whether every input was checked
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Failed inputs grouped by [`MailGuardError::class`]
+    pub fn failures_by_class(&self) -> BTreeMap<ErrorClass, Vec<&(S, MailGuardError)>> {
+        let mut classes: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for failure in &self.failed {
+            classes.entry(failure.1.class()).or_default().push(failure);
+        }
+        classes
+    }
+
+    /// Failed inputs worth checking again later
+    /// ([`MailGuardError::is_retryable`])
+    pub fn retryable(&self) -> Vec<&S> {
+        self.failed_where(MailGuardError::is_retryable)
+    }
+
+    /// Failed inputs that will fail the same way again, e.g. for a dead
+    /// letter queue
+    pub fn permanent(&self) -> Vec<&S> {
+        self.failed_where(|err| !err.is_retryable())
+    }
+
+    fn failed_where(&self, keep: impl Fn(&MailGuardError) -> bool) -> Vec<&S> {
+        self.failed
+            .iter()
+            .filter(|(_, err)| keep(err))
+            .map(|(input, _)| input)
+            .collect()
+    }
+}
+
+impl<S, T> FromIterator<(S, Result<T>)> for BatchOutcome<S, T> {
+    fn from_iter<I: IntoIterator<Item = (S, Result<T>)>>(results: I) -> Self {
+        let mut outcome = Self::default();
+        for (input, result) in results {
+            match result {
+                Ok(status) => outcome.succeeded.push((input, status)),
+                Err(err) => outcome.failed.push((input, err)),
+            }
+        }
+        outcome
+    }
+}
@@ -0,0 +1,143 @@
+//! Heuristics for machine-generated local parts
+//!
+//! Bots signing up with throwaway addresses rarely pick readable names; their
+//! local parts look like `x7kq9zt2mw`, `a1b2c3d4e5f6...` or a UUID. With
+//! [`MailGuardConfig::score_local_parts`](crate::MailGuardConfig::score_local_parts)
+//! set, every [`EmailStatus`](crate::EmailStatus) carries a
+//! [`LocalPartSuspicion`] built from the signals below. It never changes
+//! `is_threat`; combine it with the verdict as fits your signup flow.
+//!
+//! ```rust
+//! use mailguard_rs::suspicion::{SuspicionSignal, score_local_part};
+//!
+//! let suspicion = score_local_part("3f2504e0-4f89-11d3-9a0c-0305e82c3301");
+//! assert!(suspicion.signals.contains(&SuspicionSignal::UuidLike));
+//! assert_eq!(score_local_part("jane.doe").score, 0);
+//! ```
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Local parts shorter than this carry too little information to score
+const MIN_SCORED_LENGTH: usize = 6;
+
+/// Shannon entropy, in bits per character, above which characters look random
+const HIGH_ENTROPY_BITS: f64 = 3.3;
+
+/// Shortest run along a keyboard row that counts as a walk
+const MIN_WALK_LENGTH: usize = 4;
+
+const KEYBOARD_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+static UUID_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:[0-9a-f]{8}-?[0-9a-f]{4}-?[0-9a-f]{4}-?[0-9a-f]{4}-?[0-9a-f]{12}|[0-9a-f]{16,})$",
+    )
+    .expect("UUID pattern is valid")
+});
+
+/// A trait of the local part typical of generated addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuspicionSignal {
+    /// Characters are spread nearly uniformly, as in random strings
+    HighEntropy,
+    /// A large share of the characters are digits
+    ///
+    /// All-digit local parts don't count, since some large providers hand
+    /// out numeric addresses.
+    DigitHeavy,
+    /// Contains a run along a keyboard row, such as `qwer` or `6789`
+    KeyboardWalk,
+    /// Looks like a UUID or a long hex string
+    UuidLike,
+}
+
+impl SuspicionSignal {
+    /// Contribution of the signal to the score
+    pub fn weight(&self) -> u8 {
+        match self {
+            SuspicionSignal::HighEntropy => 35,
+            SuspicionSignal::DigitHeavy => 25,
+            SuspicionSignal::KeyboardWalk => 25,
+            SuspicionSignal::UuidLike => 60,
+        }
+    }
+}
+
+/// How machine-generated a local part looks
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalPartSuspicion {
+    /// Sum of the signal weights, capped at 100
+    pub score: u8,
+    /// Signals found, strongest first
+    pub signals: Vec<SuspicionSignal>,
+}
+
+/// Score a local part, ignoring case, dots and any `+tag`
+pub fn score_local_part(local_part: &str) -> LocalPartSuspicion {
+    let base = local_part.split('+').next().unwrap_or(local_part);
+    let compact: String = base
+        .chars()
+        .filter(|c| *c != '.')
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    let mut signals = Vec::new();
+    if UUID_PATTERN.is_match(&compact) {
+        signals.push(SuspicionSignal::UuidLike);
+    }
+
+    let length = compact.chars().count();
+    if length >= MIN_SCORED_LENGTH {
+        if entropy(&compact) >= HIGH_ENTROPY_BITS {
+            signals.push(SuspicionSignal::HighEntropy);
+        }
+        let digits = compact.chars().filter(char::is_ascii_digit).count();
+        if digits * 10 >= length * 3 && digits < length {
+            signals.push(SuspicionSignal::DigitHeavy);
+        }
+    }
+    if has_keyboard_walk(&compact) {
+        signals.push(SuspicionSignal::KeyboardWalk);
+    }
+
+    signals.sort_by_key(|signal| std::cmp::Reverse(signal.weight()));
+    let score = signals
+        .iter()
+        .map(|signal| u32::from(signal.weight()))
+        .sum::<u32>()
+        .min(100) as u8;
+
+    LocalPartSuspicion { score, signals }
+}
+
+/// Shannon entropy in bits per character
+fn entropy(text: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in text.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let length = text.chars().count() as f64;
+    counts
+        .values()
+        .map(|count| {
+            let p = *count as f64 / length;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn has_keyboard_walk(text: &str) -> bool {
+    KEYBOARD_ROWS.iter().any(|row| {
+        let reversed: String = row.chars().rev().collect();
+        [*row, reversed.as_str()].iter().any(|row| {
+            row.as_bytes()
+                .windows(MIN_WALK_LENGTH)
+                .filter_map(|walk| std::str::from_utf8(walk).ok())
+                .any(|walk| text.contains(walk))
+        })
+    })
+}
@@ -1,18 +1,51 @@
-use serde::{Deserialize, Serialize};
+use std::{fmt, net::IpAddr, str::FromStr, time::SystemTime};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, MapAccess, Visitor},
+};
+
+use crate::{
+    check::{BLOCKLIST, SURBL},
+    error::MailGuardError,
+    offline::IMPORTED,
+};
+
+/// Highest value returned by [`ThreatType::severity_level`]
+pub const MAX_SEVERITY: u8 = 5;
+
+/// Confidence in a direct listing, such as a SURBL answer or a blocklist hit
+pub const HIGH_CONFIDENCE: f32 = 0.9;
+/// Confidence in a heuristic, such as an MX fingerprint
+pub const MEDIUM_CONFIDENCE: f32 = 0.6;
+/// Confidence in a machine-generated-looking local part alone
+pub const LOW_CONFIDENCE: f32 = 0.3;
 
 /// Threat type enumeration
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Serializes as its [`Display`](fmt::Display) identifier, e.g. `"spam"` or
+/// `"unknown:200"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ThreatType {
-    /// Spam source (127.0.0.2, 127.0.0.9)
+    /// Spam source (127.0.0.2)
     Spam,
     /// Phishing website (127.0.0.3)
     Phishing,
-    /// Malware (127.0.0.4, 127.0.0.6, 127.0.0.7, 127.0.0.11)
+    /// Malware (127.0.0.4, 127.0.0.11)
     Malware,
     /// Botnet (127.0.0.5)
     Botnet,
+    /// Cracked site: a legitimate site taken over to host malicious
+    /// content (127.0.0.6)
+    Cracked,
+    /// Abused redirector or URL shortener (127.0.0.7)
+    Redirector,
+    /// Legitimate site abused to send or advertise spam (127.0.0.9)
+    AbusedLegit,
     /// Potentially Unwanted Program (127.0.0.10)
     Pup,
+    /// Disposable mail provider, recognized by its MX hosts
+    Disposable,
     /// Unknown threat type
     Unknown(u8),
 }
@@ -21,15 +54,80 @@ impl ThreatType {
     /// Parse threat type from IP address last octet
     pub fn from_ip_last_octet(octet: u8) -> Self {
         match octet {
-            2 | 9 => ThreatType::Spam,
+            2 => ThreatType::Spam,
             3 => ThreatType::Phishing,
-            4 | 6 | 7 | 11 => ThreatType::Malware,
+            4 | 11 => ThreatType::Malware,
             5 => ThreatType::Botnet,
+            6 => ThreatType::Cracked,
+            7 => ThreatType::Redirector,
+            9 => ThreatType::AbusedLegit,
             10 => ThreatType::Pup,
             _ => ThreatType::Unknown(octet),
         }
     }
 
+    /// Stable lowercase identifier of the category, e.g. `spam`
+    ///
+    /// Every unknown code is `unknown`; [`Display`](fmt::Display) adds the
+    /// code (`unknown:200`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThreatType::Spam => "spam",
+            ThreatType::Phishing => "phishing",
+            ThreatType::Malware => "malware",
+            ThreatType::Botnet => "botnet",
+            ThreatType::Cracked => "cracked",
+            ThreatType::Redirector => "redirector",
+            ThreatType::AbusedLegit => "abused_legit",
+            ThreatType::Pup => "pup",
+            ThreatType::Disposable => "disposable",
+            ThreatType::Unknown(_) => "unknown",
+        }
+    }
+
+    /// Identifiers of the category in threat-intel taxonomies, for feeding
+    /// detections into MISP or STIX tooling
+    pub fn taxonomy(&self) -> Taxonomy {
+        Taxonomy {
+            misp_tag: self.misp_tag(),
+            stix_indicator_type: self.stix_indicator_type(),
+        }
+    }
+
+    /// MISP machine tag from the Reference Security Incident Taxonomy
+    /// (RSIT), e.g. `rsit:fraud="phishing"`
+    ///
+    /// RSIT has no class for unwanted programs or disposable providers;
+    /// those and unknown codes are `rsit:other="undetermined"`.
+    pub fn misp_tag(&self) -> &'static str {
+        match self {
+            ThreatType::Spam => r#"rsit:abusive-content="spam""#,
+            ThreatType::Phishing => r#"rsit:fraud="phishing""#,
+            ThreatType::Malware => r#"rsit:malicious-code="malware-distribution""#,
+            ThreatType::Botnet => r#"rsit:malicious-code="c2-server""#,
+            ThreatType::Cracked => r#"rsit:intrusions="system-compromise""#,
+            ThreatType::Redirector | ThreatType::AbusedLegit => r#"rsit:abusive-content="spam""#,
+            ThreatType::Pup | ThreatType::Disposable | ThreatType::Unknown(_) => {
+                r#"rsit:other="undetermined""#
+            }
+        }
+    }
+
+    /// STIX 2.1 indicator type from the `indicator-type-ov` vocabulary,
+    /// e.g. `malicious-activity`
+    pub fn stix_indicator_type(&self) -> &'static str {
+        match self {
+            ThreatType::Phishing | ThreatType::Malware | ThreatType::Botnet => "malicious-activity",
+            ThreatType::Cracked => "compromised",
+            ThreatType::Spam
+            | ThreatType::Redirector
+            | ThreatType::AbusedLegit
+            | ThreatType::Pup => "anomalous-activity",
+            ThreatType::Disposable => "anonymization",
+            ThreatType::Unknown(_) => "unknown",
+        }
+    }
+
     /// Get threat type description
     pub fn description(&self) -> &'static str {
         match self {
@@ -37,7 +135,11 @@ impl ThreatType {
             ThreatType::Phishing => "Phishing Website",
             ThreatType::Malware => "Malware",
             ThreatType::Botnet => "Botnet",
+            ThreatType::Cracked => "Cracked Website",
+            ThreatType::Redirector => "Abused Redirector",
+            ThreatType::AbusedLegit => "Abused Legitimate Website",
             ThreatType::Pup => "Potentially Unwanted Program",
+            ThreatType::Disposable => "Disposable Email Provider",
             ThreatType::Unknown(_) => "Unknown Threat Type",
         }
     }
@@ -48,9 +150,163 @@ impl ThreatType {
             ThreatType::Malware => 5,
             ThreatType::Phishing => 4,
             ThreatType::Botnet => 4,
+            ThreatType::Cracked => 4,
+            ThreatType::Redirector => 3,
+            ThreatType::AbusedLegit => 2,
             ThreatType::Spam => 2,
+            ThreatType::Disposable => 2,
             ThreatType::Pup => 1,
             ThreatType::Unknown(_) => 3,
         }
     }
 }
+
+/// Identifiers of a [`ThreatType`] in threat-intel taxonomies
+///
+/// Serializes as `{"misp_tag": "...", "stix_indicator_type": "..."}`, ready
+/// to attach to a MISP attribute or a STIX indicator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct Taxonomy {
+    /// See [`ThreatType::misp_tag`]
+    pub misp_tag: &'static str,
+    /// See [`ThreatType::stix_indicator_type`]
+    pub stix_indicator_type: &'static str,
+}
+
+/// A threat category with the evidence behind it
+///
+/// Kept on [`DomainStatus::threats`](crate::DomainStatus::threats), so a
+/// disputed rejection can be traced to the list that flagged the domain
+/// and what it answered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThreatInfo {
+    /// The category
+    pub kind: ThreatType,
+    /// Name of the check that reported it, e.g. `surbl`
+    pub source_list: String,
+    /// DNS zone that was queried, for list lookups
+    pub queried_zone: Option<String>,
+    /// The list's raw answer, e.g. `127.0.0.2`, for list lookups
+    pub raw_ip: Option<IpAddr>,
+    /// How much the detection can be trusted, from 0 to 1; see
+    /// [`confidence_of`]
+    pub confidence: f32,
+    /// When it was observed
+    ///
+    /// Verdicts answered from the cache keep the time of the lookup that
+    /// cached them; entries cached by earlier versions, which stored no
+    /// evidence, report the time of the check and no zone or answer.
+    pub observed_at: SystemTime,
+}
+
+impl ThreatInfo {
+    /// A threat of `kind` reported by `source_list` just now, without list
+    /// evidence, with the [`confidence_of`] its source
+    pub fn new(kind: ThreatType, source_list: impl Into<String>) -> Self {
+        let source_list = source_list.into();
+        Self {
+            kind,
+            confidence: confidence_of(&source_list),
+            source_list,
+            queried_zone: None,
+            raw_ip: None,
+            observed_at: SystemTime::now(),
+        }
+    }
+
+    /// Replace the confidence, e.g. for a custom check that knows better
+    pub fn with_confidence(mut self, confidence: f32) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// Record the zone queried and its raw answer
+    pub fn with_answer(mut self, queried_zone: impl Into<String>, raw_ip: Option<IpAddr>) -> Self {
+        self.queried_zone = Some(queried_zone.into());
+        self.raw_ip = raw_ip;
+        self
+    }
+}
+
+/// Confidence in a threat reported by the named check
+///
+/// Direct listings (the blocklist, SURBL and imported verdicts) get
+/// [`HIGH_CONFIDENCE`]; every other check, built-in heuristics such as the
+/// MX fingerprint and custom checks alike, gets [`MEDIUM_CONFIDENCE`].
+pub fn confidence_of(check: &str) -> f32 {
+    match check {
+        BLOCKLIST | SURBL | IMPORTED => HIGH_CONFIDENCE,
+        _ => MEDIUM_CONFIDENCE,
+    }
+}
+
+/// Writes the stable identifier: `spam`, `phishing`, ... or `unknown:200`
+impl fmt::Display for ThreatType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThreatType::Unknown(code) => write!(f, "unknown:{code}"),
+            known => f.write_str(known.as_str()),
+        }
+    }
+}
+
+/// Parses what [`Display`](fmt::Display) writes, ignoring ASCII case
+impl FromStr for ThreatType {
+    type Err = MailGuardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let threat_type = match s.to_ascii_lowercase().as_str() {
+            "spam" => ThreatType::Spam,
+            "phishing" => ThreatType::Phishing,
+            "malware" => ThreatType::Malware,
+            "botnet" => ThreatType::Botnet,
+            "cracked" => ThreatType::Cracked,
+            "redirector" => ThreatType::Redirector,
+            "abused_legit" => ThreatType::AbusedLegit,
+            "pup" => ThreatType::Pup,
+            "disposable" => ThreatType::Disposable,
+            other => other
+                .strip_prefix("unknown:")
+                .and_then(|code| code.parse().ok())
+                .map(ThreatType::Unknown)
+                .ok_or_else(|| MailGuardError::InvalidThreatType(s.to_string()))?,
+        };
+        Ok(threat_type)
+    }
+}
+
+impl Serialize for ThreatType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Accepts the identifiers [`Serialize`] writes, and the variant names and
+/// `{"Unknown": 200}` maps of earlier versions, so stored verdicts stay
+/// readable
+impl<'de> Deserialize<'de> for ThreatType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ThreatTypeVisitor)
+    }
+}
+
+struct ThreatTypeVisitor;
+
+impl<'de> Visitor<'de> for ThreatTypeVisitor {
+    type Value = ThreatType;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a threat type identifier such as \"spam\" or \"unknown:200\"")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        match map.next_entry::<String, u8>()? {
+            Some((variant, code)) if variant == "Unknown" => Ok(ThreatType::Unknown(code)),
+            _ => Err(de::Error::invalid_value(de::Unexpected::Map, &self)),
+        }
+    }
+}
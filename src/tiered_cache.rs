@@ -0,0 +1,116 @@
+//! Memory cache in front of a persistent or shared one
+//!
+//! A networked or disk-backed [`CacheBackend`] keeps verdicts across
+//! restarts and processes, but every lookup pays for a round trip.
+//! [`TieredCache`] puts a small in-memory [`Cache`] in front of it: lookups
+//! try memory first and fall back to the backend, copying what it returns
+//! into memory, and writes go through to both.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! use mailguard_rs::{DiskCache, MailGuard, TieredCache};
+//!
+//! let backend = DiskCache::open("/var/cache/mailguard/verdicts.jsonl")?;
+//! let detector = MailGuard::builder()
+//!     .cache_backend(TieredCache::new(backend, 10_000, Duration::from_secs(60)))
+//!     .build()?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! The memory tier needs the `cache` feature; without it every lookup goes
+//! to the backend.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    cache::{Cache, CacheBackend, CacheEntry, CachedVerdict, ShardStats},
+    error::Result,
+};
+
+/// [`CacheBackend`] answering hot domains from memory and the rest from
+/// another backend
+pub struct TieredCache<B> {
+    front: Cache,
+    front_ttl: Duration,
+    back: B,
+}
+
+impl<B: CacheBackend> TieredCache<B> {
+    /// Front `back` with an in-memory cache of at most `front_entries`
+    /// verdicts, each kept in memory for at most `front_ttl`
+    ///
+    /// Changes other processes make to a shared backend show up once the
+    /// memory copy expires, so `front_ttl` bounds how stale a hot verdict
+    /// can get.
+    pub fn new(back: B, front_entries: usize, front_ttl: Duration) -> Self {
+        Self {
+            front: Cache::with_ttl(front_ttl)
+                .with_weigher(CachedVerdict::heap_size)
+                .with_max_entries(front_entries),
+            front_ttl,
+            back,
+        }
+    }
+
+    /// The backend behind the memory tier
+    pub fn backend(&self) -> &B {
+        &self.back
+    }
+}
+
+#[async_trait]
+impl<B: CacheBackend> CacheBackend for TieredCache<B> {
+    async fn get(&self, key: &str) -> Result<Option<CachedVerdict>> {
+        if let Some(value) = self.front.get(key) {
+            return Ok(Some(value));
+        }
+        let value = self.back.get(key).await?;
+        if let Some(value) = &value {
+            self.front.set(key.to_string(), value.clone());
+        }
+        Ok(value)
+    }
+
+    async fn set(&self, key: &str, value: CachedVerdict, ttl: Duration) -> Result<()> {
+        self.back.set(key, value.clone(), ttl).await?;
+        self.front
+            .set_with_ttl(key.to_string(), value, ttl.min(self.front_ttl));
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        self.front.remove(key);
+        self.back.remove(key).await
+    }
+
+    fn clear(&self) {
+        self.front.clear();
+        self.back.clear();
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.back.size()
+    }
+
+    /// Memory held by the memory tier
+    fn memory_usage(&self) -> Option<usize> {
+        Some(self.front.memory_usage())
+    }
+
+    /// Shards of the memory tier
+    fn shard_stats(&self) -> Vec<ShardStats> {
+        self.front.shard_stats()
+    }
+
+    fn cleanup_expired(&self) {
+        self.front.cleanup_expired();
+        self.back.cleanup_expired();
+    }
+
+    fn entries(&self) -> Vec<(String, CacheEntry)> {
+        self.back.entries()
+    }
+}
@@ -0,0 +1,96 @@
+//! Typosquatting detection against major mail providers
+//!
+//! Flags domains that are one edit (or one look-alike character) away from a
+//! well-known provider, e.g. `gmial.com` or `outl00k.com`. Providers
+//! themselves are never flagged, even when they sit one edit away from
+//! another (`ymail.com`, `mail.com`).
+
+use crate::{check::TRUSTED_PROVIDERS, lists::DomainList};
+
+/// Major mail provider domains that are common typosquatting targets
+pub const MAJOR_PROVIDERS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "yahoo.com",
+    "hotmail.com",
+    "outlook.com",
+    "icloud.com",
+    "protonmail.com",
+    "yandex.com",
+];
+
+/// Characters commonly substituted for look-alike letters
+const HOMOGLYPHS: &[(&str, &str)] = &[
+    ("0", "o"),
+    ("1", "l"),
+    ("3", "e"),
+    ("5", "s"),
+    ("rn", "m"),
+    ("vv", "w"),
+];
+
+/// Return the major provider this domain likely imitates or mistypes
+///
+/// Domains that exactly match a major provider or one of the
+/// [`TRUSTED_PROVIDERS`] are not typos and return `None`.
+pub fn likely_typo_of(domain: &str) -> Option<&'static str> {
+    let domain = domain.to_lowercase();
+    if MAJOR_PROVIDERS.contains(&domain.as_str()) || TRUSTED_PROVIDERS.contains(&domain.as_str()) {
+        return None;
+    }
+
+    let deglyphed = replace_homoglyphs(&domain);
+    MAJOR_PROVIDERS
+        .iter()
+        .copied()
+        .find(|provider| deglyphed == *provider || edit_distance(&domain, provider) == 1)
+}
+
+/// Like [`likely_typo_of`], also treating domains on `providers` as
+/// providers rather than typos
+///
+/// The detectors pass their
+/// [`trusted_providers`](crate::MailGuardConfig::trusted_providers).
+pub fn likely_typo_excluding(domain: &str, providers: &DomainList) -> Option<&'static str> {
+    if providers.matches(&domain.to_lowercase()) {
+        return None;
+    }
+    likely_typo_of(domain)
+}
+
+/// Replace look-alike characters with the letters they imitate
+fn replace_homoglyphs(domain: &str) -> String {
+    HOMOGLYPHS
+        .iter()
+        .fold(domain.to_string(), |acc, (glyph, letter)| {
+            acc.replace(glyph, letter)
+        })
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent transpositions)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut previous_previous = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(previous_previous[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut previous_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
@@ -0,0 +1,91 @@
+//! Custom address validation
+//!
+//! The built-in parser accepts any RFC 5322 `addr-spec`. Platforms with
+//! stricter rules (no `'` or `/` in local parts, an internal naming scheme)
+//! can set [`MailGuardConfig::email_validator`](crate::MailGuardConfig::email_validator)
+//! to an [`EmailValidator`], which then replaces the built-in syntax rules.
+//! The domain must still be a valid hostname, since it is queried.
+//!
+//! ```rust
+//! use mailguard_rs::{EmailValidator, MailGuardConfig};
+//!
+//! let config = MailGuardConfig {
+//!     email_validator: Some(EmailValidator::regex(r"[a-z0-9._+-]+@[a-z0-9.-]+")?),
+//!     ..Default::default()
+//! };
+//! # Ok::<(), mailguard_rs::MailGuardError>(())
+//! ```
+
+use std::{fmt, sync::Arc};
+
+use regex::Regex;
+
+use crate::{
+    address::{ValidationDetail, ValidationRule},
+    error::{MailGuardError, Result},
+};
+
+type ValidatorFn = dyn Fn(&str) -> std::result::Result<(), String> + Send + Sync;
+
+#[derive(Clone)]
+enum Rule {
+    Regex(Regex),
+    Function(Arc<ValidatorFn>),
+}
+
+/// Replacement for the built-in address syntax rules
+#[derive(Clone)]
+pub struct EmailValidator {
+    rule: Rule,
+}
+
+impl EmailValidator {
+    /// Accept addresses the whole of which match `pattern`
+    ///
+    /// Fails with [`MailGuardError::InvalidConfig`] if the pattern doesn't
+    /// compile.
+    pub fn regex(pattern: &str) -> Result<Self> {
+        let regex = Regex::new(&format!("^(?:{pattern})$")).map_err(|err| {
+            MailGuardError::InvalidConfig(format!("invalid email_validator pattern: {err}"))
+        })?;
+        Ok(Self {
+            rule: Rule::Regex(regex),
+        })
+    }
+
+    /// Accept addresses for which `validate` returns `Ok`, rejecting the
+    /// others with the returned reason
+    pub fn function<F>(validate: F) -> Self
+    where
+        F: Fn(&str) -> std::result::Result<(), String> + Send + Sync + 'static,
+    {
+        Self {
+            rule: Rule::Function(Arc::new(validate)),
+        }
+    }
+
+    /// Check a trimmed address
+    pub fn validate(&self, email: &str) -> Result<()> {
+        let verdict = match &self.rule {
+            Rule::Regex(regex) if regex.is_match(email) => Ok(()),
+            Rule::Regex(_) => Err("does not match the configured pattern".to_string()),
+            Rule::Function(validate) => validate(email),
+        };
+        verdict.map_err(|reason| {
+            MailGuardError::invalid_email_at(
+                email,
+                reason,
+                ValidationDetail::new(ValidationRule::Custom, None),
+            )
+        })
+    }
+}
+
+impl fmt::Debug for EmailValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.rule {
+            Rule::Regex(regex) => f.debug_tuple("EmailValidator").field(regex).finish(),
+            Rule::Function(_) => f.write_str("EmailValidator(<function>)"),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use mailguard_rs::{Fixture, MailGuard, MailGuardConfig, dns::DnsClient, parse_address_list};
+
+#[test]
+fn test_mixed_separators() {
+    let parsed =
+        parse_address_list("a@example.com, b@example.com;c@example.com\nd@example.com\r\n");
+    assert_eq!(
+        parsed.addresses,
+        vec![
+            "a@example.com",
+            "b@example.com",
+            "c@example.com",
+            "d@example.com"
+        ]
+    );
+    assert!(parsed.errors.is_empty());
+}
+
+#[test]
+fn test_display_names() {
+    let parsed = parse_address_list(
+        r#""Doe, Jane" <jane@example.com>, Bob <bob@example.org>; <mailto:carol@example.net>"#,
+    );
+    assert_eq!(
+        parsed.addresses,
+        vec!["jane@example.com", "bob@example.org", "carol@example.net"]
+    );
+}
+
+#[test]
+fn test_per_item_errors() {
+    let parsed = parse_address_list("good@example.com, not an email, @missing.com,, Jane <jane@");
+    assert_eq!(parsed.addresses, vec!["good@example.com"]);
+
+    let indexes: Vec<usize> = parsed.errors.iter().map(|err| err.index).collect();
+    assert_eq!(indexes, vec![1, 2, 3]);
+    assert_eq!(parsed.errors[0].input.trim(), "not an email");
+    assert_eq!(parsed.errors[2].reason, "Unbalanced angle brackets");
+}
+
+#[tokio::test]
+async fn test_feeds_batch_check() {
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(Fixture::new()),
+    );
+
+    let parsed = parse_address_list("Ann <ann@example.com>\nbob@example.org");
+    let results = detector.check_emails_batch(&parsed.as_strs()).await;
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(Result::is_ok));
+}
@@ -0,0 +1,258 @@
+use mailguard_rs::{
+    EmailAddress, EmailValidator, Fixture, MailGuard, MailGuardConfig, MailGuardError,
+    ValidationDetail, ValidationRule, dns::DnsClient,
+};
+
+fn is_valid(input: &str) -> bool {
+    EmailAddress::parse(input).is_ok()
+}
+
+fn detail_of(input: &str) -> ValidationDetail {
+    EmailAddress::parse(input)
+        .unwrap_err()
+        .validation_detail()
+        .cloned()
+        .unwrap()
+}
+
+#[test]
+fn test_parse_components() {
+    let address = EmailAddress::parse("John.Doe+tag@Sub.Example.COM").unwrap();
+    assert_eq!(address.local_part.as_str(), "John.Doe+tag");
+    assert_eq!(address.domain.as_str(), "sub.example.com");
+    assert_eq!(address.to_string(), "John.Doe+tag@sub.example.com");
+}
+
+#[test]
+fn test_valid_unusual_addresses() {
+    assert!(is_valid("user@localhost"));
+    assert!(is_valid("o'reilly@example.com"));
+    assert!(is_valid("a!#$%&*/=?^_`{|}~-@example.com"));
+    assert!(is_valid("x@xn--bcher-kva.example"));
+}
+
+#[test]
+fn test_comments_are_dropped() {
+    let address = EmailAddress::parse("john(work)@example.com").unwrap();
+    assert_eq!(address.to_string(), "john@example.com");
+
+    let address = EmailAddress::parse("(note) john@example.com (primary (nested))").unwrap();
+    assert_eq!(address.to_string(), "john@example.com");
+
+    assert!(!is_valid("john(unclosed@example.com"));
+    assert!(!is_valid("john)@example.com"));
+}
+
+#[test]
+fn test_length_limits() {
+    let local = "a".repeat(64);
+    assert!(is_valid(&format!("{local}@example.com")));
+    assert!(!is_valid(&format!("a{local}@example.com")));
+
+    let label = "b".repeat(63);
+    assert!(is_valid(&format!("user@{label}.com")));
+    assert!(!is_valid(&format!("user@b{label}.com")));
+
+    let long_domain = vec!["c".repeat(60); 5].join(".");
+    assert!(!is_valid(&format!("user@{long_domain}")));
+}
+
+#[test]
+fn test_invalid_addresses() {
+    for input in [
+        "invalid-email",
+        "@example.com",
+        "test@",
+        "double@@domain.com",
+        ".leading@example.com",
+        "trailing.@example.com",
+        "two..dots@example.com",
+        "space here@example.com",
+        "user@-example.com",
+        "user@example..com",
+        "user@[192.168.0.1]",
+    ] {
+        let result = EmailAddress::parse(input);
+        assert!(
+            matches!(result, Err(MailGuardError::InvalidEmail { .. })),
+            "{input} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_quoted_local_parts() {
+    let address = EmailAddress::parse(r#""john..doe"@example.com"#).unwrap();
+    assert!(address.local_part.is_quoted());
+    assert_eq!(address.local_part.as_str(), r#""john..doe""#);
+    assert_eq!(address.domain.as_str(), "example.com");
+
+    assert!(is_valid(r#""john doe"@example.com"#));
+    assert!(is_valid(r#""user@office"@example.com"#));
+    assert!(is_valid(r#""with \"escaped\" quotes"@example.com"#));
+    assert!(is_valid(r#""(not a comment)"@example.com"#));
+    assert!(is_valid(r#""john"(comment)@example.com"#));
+
+    assert!(!is_valid(r#""unterminated@example.com"#));
+    assert!(!is_valid(r#""bad"quote"@example.com"#));
+    assert!(!is_valid(r#""tab	inside"@example.com"#));
+}
+
+#[test]
+fn test_quoted_comment_is_preserved() {
+    let address = EmailAddress::parse(r#""(keep)"(drop)@example.com"#).unwrap();
+    assert_eq!(address.local_part.as_str(), r#""(keep)""#);
+}
+
+#[tokio::test]
+async fn test_check_email_quoted_local_part() {
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(Fixture::new()),
+    );
+
+    let status = detector
+        .check_email(r#""john..doe"@example.com"#)
+        .await
+        .unwrap();
+    assert_eq!(status.domain, "example.com");
+    assert_eq!(status.email, r#""john..doe"@example.com"#);
+}
+
+#[test]
+fn test_internationalized_addresses() {
+    let address = EmailAddress::parse("用户@Example.中国").unwrap();
+    assert_eq!(address.local_part.as_str(), "用户");
+    assert_eq!(address.domain.as_str(), "example.xn--fiqs8s");
+    assert_eq!(address.to_unicode(), "用户@example.中国");
+    assert!(address.requires_smtputf8());
+
+    let address = EmailAddress::parse("info@Bücher.example").unwrap();
+    assert_eq!(address.domain.as_str(), "xn--bcher-kva.example");
+    assert!(!address.requires_smtputf8());
+
+    assert!(is_valid(r#""José Díaz"@example.com"#));
+    assert!(is_valid("θσερ@例子。测试"));
+    assert!(!is_valid("user\u{200B}\u{0007}@example.com"));
+    assert!(!is_valid("user@exa mple.中国"));
+}
+
+#[test]
+fn test_decomposed_domains_normalize() {
+    // `é` precomposed (NFC) and as `e` + combining acute accent (NFD)
+    let composed = EmailAddress::parse("info@caf\u{E9}.example").unwrap();
+    let decomposed = EmailAddress::parse("info@cafe\u{301}.example").unwrap();
+    assert_eq!(composed.domain.as_str(), "xn--caf-dma.example");
+    assert_eq!(decomposed.domain, composed.domain);
+
+    let full_width = EmailAddress::parse("info@\u{FF45}xample.com").unwrap();
+    assert_eq!(full_width.domain.as_str(), "example.com");
+}
+
+#[tokio::test]
+async fn test_check_email_internationalized() {
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(Fixture::new()),
+    );
+
+    let status = detector.check_email("用户@example.中国").await.unwrap();
+    assert_eq!(status.domain, "example.xn--fiqs8s");
+    assert_eq!(status.email, "用户@example.中国");
+
+    let json = serde_json::to_string(&status).unwrap();
+    assert!(json.contains(r#""email":"用户@example.中国""#));
+
+    let status = detector.check_domain("MÜNCHEN.example").await.unwrap();
+    assert_eq!(status.domain, "xn--mnchen-3ya.example");
+}
+
+#[test]
+fn test_validation_detail_points_at_the_fault() {
+    let cases = [
+        (
+            "john doe@example.com",
+            ValidationRule::InvalidLocalCharacter,
+            Some(4),
+        ),
+        (
+            "  john doe@example.com",
+            ValidationRule::InvalidLocalCharacter,
+            Some(6),
+        ),
+        (".john@example.com", ValidationRule::MisplacedDot, Some(0)),
+        (
+            "john..doe@example.com",
+            ValidationRule::MisplacedDot,
+            Some(5),
+        ),
+        ("john.@example.com", ValidationRule::MisplacedDot, Some(4)),
+        ("@example.com", ValidationRule::EmptyLocalPart, Some(0)),
+        ("john@", ValidationRule::EmptyDomain, Some(5)),
+        (
+            "john@exa_mple.com",
+            ValidationRule::InvalidDomainCharacter,
+            Some(8),
+        ),
+        ("john@example..com", ValidationRule::EmptyLabel, Some(13)),
+        (
+            "john@example.-com",
+            ValidationRule::MisplacedHyphen,
+            Some(13),
+        ),
+        (
+            r#""jo"hn"@example.com"#,
+            ValidationRule::UnescapedQuote,
+            Some(3),
+        ),
+        (
+            "用户 名@example.com",
+            ValidationRule::InvalidLocalCharacter,
+            Some(2),
+        ),
+        ("john.example.com", ValidationRule::MissingAt, None),
+    ];
+    for (input, rule, offset) in cases {
+        let detail = detail_of(input);
+        assert_eq!((detail.rule, detail.offset), (rule, offset), "{input}");
+    }
+}
+
+#[test]
+fn test_validation_detail_names_allowed_characters() {
+    let detail = detail_of("john doe@example.com");
+    assert_eq!(
+        detail.allowed.as_deref(),
+        Some("letters, digits, '.' and !#$%&'*+-/=?^_`{|}~")
+    );
+    assert_eq!(detail_of("john..doe@example.com").allowed, None);
+
+    let json = serde_json::to_value(detail).unwrap();
+    assert_eq!(json["rule"], "invalid_local_character");
+    assert_eq!(json["offset"], 4);
+}
+
+#[test]
+fn test_validation_detail_offset_is_dropped_when_input_was_rewritten() {
+    // The comment is dropped before checking, so offsets wouldn't line up
+    let detail = detail_of("(work)john doe@example.com");
+    assert_eq!(detail.rule, ValidationRule::InvalidLocalCharacter);
+    assert_eq!(detail.offset, None);
+
+    let detail = detail_of("john@bad_label.中国");
+    assert_eq!(detail.rule, ValidationRule::InvalidDomainCharacter);
+    assert_eq!(detail.offset, None);
+}
+
+#[test]
+fn test_custom_validator_rejections_are_custom() {
+    let validator = EmailValidator::regex(r"[a-z]+@corp\.example").unwrap();
+    let err = EmailAddress::parse_with("John@corp.example", &validator).unwrap_err();
+    assert_eq!(
+        err.validation_detail().map(|detail| detail.rule),
+        Some(ValidationRule::Custom)
+    );
+
+    let err = MailGuardError::invalid_email("john", "Not allowed");
+    assert_eq!(err.validation_detail(), None);
+}
@@ -0,0 +1,135 @@
+use std::net::IpAddr;
+
+use mailguard_rs::{
+    Check, CheckOutcome, Fixture, FixtureAnswer, MailGuard, MailGuardConfig, MailGuardError,
+    ThreatAggregation, ThreatInfo, ThreatType,
+    check::{self, async_trait},
+    dns::DnsClient,
+    error::Result,
+};
+
+/// Second provider, calling `*.listed.test` phishing and spam
+struct OtherList;
+
+#[async_trait]
+impl Check for OtherList {
+    fn name(&self) -> &str {
+        "other_list"
+    }
+
+    async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+        match domain {
+            "phish.listed.test" => Ok(CheckOutcome::Threat(Some(ThreatType::Phishing))),
+            "spam.listed.test" => Ok(CheckOutcome::Threat(Some(ThreatType::Spam))),
+            _ => Ok(CheckOutcome::Pass),
+        }
+    }
+}
+
+/// SURBL lists both domains as spam
+fn playback_client() -> DnsClient {
+    let mut fixture = Fixture::new();
+    for domain in ["phish.listed.test", "spam.listed.test"] {
+        fixture.insert(
+            format!("{domain}.tempmail.so.multi.surbl.org"),
+            FixtureAnswer::Records {
+                ips: vec![IpAddr::from([127, 0, 0, 2])],
+                ttl_secs: 300,
+            },
+        );
+    }
+    DnsClient::playback(fixture)
+}
+
+fn detector(threat_aggregation: ThreatAggregation) -> Result<MailGuard> {
+    let config = MailGuardConfig {
+        enable_cache: false,
+        short_circuit: false,
+        threat_aggregation,
+        ..Default::default()
+    };
+    MailGuard::builder()
+        .config(config)
+        .dns_client(playback_client())
+        .with_check(OtherList)
+        .build()
+}
+
+fn kinds(threats: &[ThreatInfo]) -> Vec<(&str, ThreatType)> {
+    threats
+        .iter()
+        .map(|threat| (threat.source_list.as_str(), threat.kind.clone()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_decider_keeps_the_first_category() {
+    let detector = detector(ThreatAggregation::Decider).unwrap();
+
+    let status = detector.check_domain("phish.listed.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+    assert_eq!(
+        kinds(&status.threats),
+        [
+            ("other_list", ThreatType::Phishing),
+            (check::SURBL, ThreatType::Spam)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_max_severity_picks_and_keeps_the_worst() {
+    let detector = detector(ThreatAggregation::MaxSeverity).unwrap();
+
+    let status = detector.check_domain("phish.listed.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Phishing));
+    assert_eq!(
+        kinds(&status.threats),
+        [("other_list", ThreatType::Phishing)]
+    );
+
+    let status = detector.check_domain("spam.listed.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+    assert_eq!(status.threats.len(), 2);
+}
+
+#[tokio::test]
+async fn test_union_keeps_one_threat_per_category() {
+    let detector = detector(ThreatAggregation::Union).unwrap();
+
+    let status = detector.check_domain("phish.listed.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Phishing));
+    assert_eq!(status.threats.len(), 2);
+
+    let status = detector.check_domain("spam.listed.test").await.unwrap();
+    assert_eq!(kinds(&status.threats), [(check::SURBL, ThreatType::Spam)]);
+}
+
+#[tokio::test]
+async fn test_precedence_trusts_the_named_provider() {
+    let other_first = detector(ThreatAggregation::Precedence(vec![
+        "other_list".to_string(),
+    ]))
+    .unwrap();
+    let status = other_first.check_domain("phish.listed.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Phishing));
+
+    let surbl_first = detector(ThreatAggregation::Precedence(vec![
+        check::SURBL.to_string(),
+    ]))
+    .unwrap();
+    let status = surbl_first.check_domain("phish.listed.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+    assert_eq!(status.threats.len(), 2);
+
+    // Clean domains stay clean
+    let status = surbl_first.check_domain("clean.test").await.unwrap();
+    assert!(!status.is_threat);
+    assert_eq!(status.threat_type, None);
+}
+
+#[test]
+fn test_precedence_of_unknown_check_is_invalid() {
+    let result = detector(ThreatAggregation::Precedence(vec!["spamhaus".to_string()]));
+    assert!(matches!(result, Err(MailGuardError::InvalidConfig(_))));
+}
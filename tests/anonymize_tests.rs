@@ -0,0 +1,57 @@
+use std::net::IpAddr;
+
+use mailguard_rs::{
+    Anonymizer, Fixture, FixtureAnswer, MailGuard, MailGuardConfig, ThreatType, dns::DnsClient,
+};
+
+async fn statuses(emails: &[&str]) -> Vec<mailguard_rs::error::Result<mailguard_rs::EmailStatus>> {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "tempmail.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    MailGuard::with_dns_client(MailGuardConfig::default(), DnsClient::playback(fixture))
+        .check_emails_batch(emails)
+        .await
+}
+
+#[tokio::test]
+async fn test_strip_keeps_domain_and_verdict() {
+    let results = statuses(&["alice+promo@tempmail.test", "bob@example.com", "broken"]).await;
+    let anonymized = Anonymizer::strip().anonymize_batch(&results);
+
+    // The invalid input is dropped
+    assert_eq!(anonymized.len(), 2);
+    assert_eq!(anonymized[0].local_part_hash, None);
+    assert_eq!(anonymized[0].domain, "tempmail.test");
+    assert!(anonymized[0].is_threat);
+    assert_eq!(anonymized[0].threat_type, Some(ThreatType::Spam));
+
+    let json = serde_json::to_string(&anonymized).unwrap();
+    assert!(!json.contains("alice"));
+    assert!(!json.contains("promo"));
+    assert!(!json.contains("bob"));
+}
+
+#[tokio::test]
+async fn test_hash_is_keyed_and_alias_stable() {
+    let results = statuses(&["J.Doe+news@gmail.com", "jdoe@gmail.com", "other@gmail.com"]).await;
+
+    let anonymizer = Anonymizer::hashed("secret");
+    let anonymized = anonymizer.anonymize_batch(&results);
+    let hashes: Vec<&str> = anonymized
+        .iter()
+        .map(|status| status.local_part_hash.as_deref().unwrap())
+        .collect();
+
+    assert_eq!(hashes[0], hashes[1]);
+    assert_ne!(hashes[0], hashes[2]);
+    assert_eq!(hashes[0].len(), 64);
+    assert!(!hashes[0].contains("jdoe"));
+
+    let other_key = Anonymizer::hashed("other").anonymize_batch(&results);
+    assert_ne!(other_key[0].local_part_hash.as_deref(), Some(hashes[0]));
+}
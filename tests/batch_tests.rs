@@ -0,0 +1,228 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use mailguard_rs::{
+    BatchOutcome, BatchSummary, Check, CheckOutcome, DomainRollup, ErrorClass, Fixture, MailGuard,
+    MailGuardConfig, MailGuardError, ThreatType, check::async_trait, dns::DnsClient, error::Result,
+};
+
+/// Flags `threat.test`, fails on `broken.test` and `down.test`, and counts
+/// its invocations
+struct Counting {
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Check for Counting {
+    fn name(&self) -> &str {
+        "counting"
+    }
+
+    async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        match domain {
+            "threat.test" => Ok(CheckOutcome::Threat(Some(ThreatType::Spam))),
+            "broken.test" => Err(MailGuardError::invalid_domain(
+                domain,
+                "rejected by the check",
+            )),
+            "down.test" => Err(MailGuardError::DnsError("SERVFAIL".into())),
+            _ => Ok(CheckOutcome::Pass),
+        }
+    }
+}
+
+fn counting_detector(calls: Arc<AtomicUsize>) -> MailGuard {
+    let config = MailGuardConfig {
+        enable_cache: false,
+        ..Default::default()
+    };
+    MailGuard::builder()
+        .config(config)
+        .dns_client(DnsClient::playback(Fixture::new()))
+        .with_check(Counting { calls })
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_emails_batch_checks_each_domain_once() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let detector = counting_detector(calls.clone());
+
+    let results = detector
+        .check_emails_batch(&[
+            "a@threat.test",
+            "b@THREAT.test",
+            "not-an-email",
+            "c@clean.test",
+            "d@threat.test",
+        ])
+        .await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(results.len(), 5);
+
+    let first = results[0].as_ref().unwrap();
+    assert!(first.is_threat);
+    assert_eq!(first.email, "a@threat.test");
+
+    let second = results[1].as_ref().unwrap();
+    assert!(second.is_threat);
+    assert_eq!(second.email, "b@THREAT.test");
+    assert_eq!(second.normalized_email, "b@threat.test");
+
+    assert!(matches!(
+        results[2],
+        Err(MailGuardError::InvalidEmail { .. })
+    ));
+    assert!(!results[3].as_ref().unwrap().is_threat);
+    assert_eq!(results[4].as_ref().unwrap().email, "d@threat.test");
+}
+
+#[tokio::test]
+async fn test_batch_shares_domain_errors() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let detector = counting_detector(calls.clone());
+
+    let results = detector
+        .check_emails_batch(&["a@broken.test", "b@broken.test"])
+        .await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(results.iter().all(|result| matches!(
+        result.as_ref().map_err(MailGuardError::root_cause),
+        Err(MailGuardError::InvalidDomain { .. })
+    )));
+}
+
+#[tokio::test]
+async fn test_domains_batch_checks_each_domain_once() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let detector = counting_detector(calls.clone());
+
+    let results = detector
+        .check_domains_batch(&["threat.test", "clean.test", "Threat.Test"])
+        .await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(results[0].as_ref().unwrap().is_threat);
+    assert!(!results[1].as_ref().unwrap().is_threat);
+    assert!(results[2].as_ref().unwrap().is_threat);
+}
+
+#[tokio::test]
+async fn test_owned_inputs_are_paired_with_results() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let detector = counting_detector(calls.clone());
+
+    let emails: Vec<String> = vec![
+        "a@threat.test".into(),
+        "b@clean.test".into(),
+        "c@threat.test".into(),
+    ];
+    let results = detector.check_emails(emails).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    let (first, status) = &results[0];
+    assert_eq!(first, "a@threat.test");
+    assert!(status.as_ref().unwrap().is_threat);
+    assert_eq!(results[1].0, "b@clean.test");
+    assert!(!results[1].1.as_ref().unwrap().is_threat);
+
+    let domains = ["threat.test", "clean.test"].into_iter().map(String::from);
+    let results = detector.check_domains(domains).await;
+    assert_eq!(results[0].0, "threat.test");
+    assert!(results[0].1.as_ref().unwrap().is_threat);
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_batch_summary() {
+    let mut config = MailGuardConfig {
+        enable_cache: false,
+        ..Default::default()
+    };
+    config.blocklist.insert("burner.test".to_string());
+    let detector = MailGuard::builder()
+        .config(config)
+        .dns_client(DnsClient::playback(Fixture::new()))
+        .with_check(Counting {
+            calls: Arc::new(AtomicUsize::new(0)),
+        })
+        .build()
+        .unwrap();
+
+    let (results, summary) = detector
+        .check_emails_summarized([
+            "a@threat.test",
+            "b@Threat.test",
+            "c@burner.test",
+            "d@fine.test",
+            "e@broken.test",
+            "not an address",
+        ])
+        .await;
+
+    assert_eq!(results.len(), 6);
+    assert_eq!(summary.total, 6);
+    assert_eq!(summary.threats, 3);
+    assert_eq!(summary.errors, 2);
+    assert_eq!(
+        summary.threats_by_category,
+        [(Some(ThreatType::Spam), 2), (None, 1)]
+    );
+    assert_eq!(
+        summary.domains["threat.test"],
+        DomainRollup {
+            checked: 2,
+            threats: 2
+        }
+    );
+    assert_eq!(summary.domains["fine.test"].threats, 0);
+    assert_eq!(summary.cache_hit_ratio(), 0.0);
+
+    let (_, summary) = detector
+        .check_domains_summarized(Vec::<String>::new())
+        .await;
+    assert_eq!(
+        summary,
+        BatchSummary {
+            elapsed: summary.elapsed,
+            ..Default::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_batch_outcome_separates_failures() {
+    let detector = counting_detector(Arc::new(AtomicUsize::new(0)));
+
+    let outcome: BatchOutcome<_, _> = detector
+        .check_emails([
+            "a@ok.test",
+            "b@broken.test",
+            "c@down.test",
+            "bad",
+            "d@threat.test",
+        ])
+        .await
+        .into_iter()
+        .collect();
+
+    assert!(!outcome.is_complete());
+    let succeeded: Vec<_> = outcome.succeeded.iter().map(|(input, _)| *input).collect();
+    assert_eq!(succeeded, ["a@ok.test", "d@threat.test"]);
+    assert!(outcome.succeeded[1].1.is_threat);
+
+    assert_eq!(outcome.retryable(), [&"c@down.test"]);
+    assert_eq!(outcome.permanent(), [&"b@broken.test", &"bad"]);
+
+    let classes = outcome.failures_by_class();
+    let inputs = |class| -> Vec<&str> { classes[&class].iter().map(|(input, _)| *input).collect() };
+    assert_eq!(inputs(ErrorClass::Input), ["b@broken.test", "bad"]);
+    assert_eq!(inputs(ErrorClass::Infrastructure), ["c@down.test"]);
+    assert!(!classes.contains_key(&ErrorClass::Configuration));
+}
@@ -0,0 +1,59 @@
+use std::net::IpAddr;
+
+use mailguard_rs::{Fixture, FixtureAnswer, MailGuardConfig, ThreatType, blocking, dns::DnsClient};
+
+fn detector() -> blocking::MailGuard {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "tempmail.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    blocking::MailGuard::with_dns_client(MailGuardConfig::default(), DnsClient::playback(fixture))
+        .unwrap()
+}
+
+#[test]
+fn test_blocking_check_email() {
+    let detector = detector();
+
+    let status = detector.check_email("user@tempmail.test").unwrap();
+    assert!(status.is_threat);
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+
+    assert!(detector.check_email("invalid-email").is_err());
+}
+
+#[test]
+fn test_blocking_batches() {
+    let detector = detector();
+
+    let results = detector.check_domains_batch(&["tempmail.test", "clean.test"]);
+    assert!(results[0].as_ref().unwrap().is_threat);
+    assert!(!results[1].as_ref().unwrap().is_threat);
+
+    let results = detector.check_emails_batch(&["a@clean.test", "bad"]);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn test_blocking_convenience_rejects_invalid_input() {
+    assert!(blocking::check_email("invalid-email").is_err());
+    assert!(blocking::check_domain("example..com").is_err());
+}
+
+#[test]
+fn test_blocking_constructors_validate_the_config() {
+    let invalid = || MailGuardConfig {
+        octet_overrides: [(1, ThreatType::Spam)].into_iter().collect(),
+        ..Default::default()
+    };
+    assert!(blocking::MailGuard::with_config(invalid()).is_err());
+    assert!(
+        blocking::MailGuard::with_dns_client(invalid(), DnsClient::playback(Fixture::new()))
+            .is_err()
+    );
+}
@@ -0,0 +1,321 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use mailguard_rs::{
+    CacheBackend, CachedVerdict, Fixture, FixtureAnswer, MailGuard, MailGuardConfig,
+    MailGuardError, ThreatType, check, check::async_trait, dns::DnsClient, error::Result,
+};
+
+/// Verdicts with the TTL they were stored for
+type Entries = HashMap<String, (CachedVerdict, Duration)>;
+
+/// Stands in for a shared store such as Redis
+#[derive(Clone, Default)]
+struct SharedStore {
+    entries: Arc<Mutex<Entries>>,
+}
+
+#[async_trait]
+impl CacheBackend for SharedStore {
+    async fn get(&self, key: &str) -> Result<Option<CachedVerdict>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries.get(key).map(|(value, _)| value.clone()))
+    }
+
+    async fn set(&self, key: &str, value: CachedVerdict, ttl: Duration) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(key.to_string(), (value, ttl));
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        self.entries.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(self.entries.lock().unwrap().len())
+    }
+}
+
+/// A store that is down
+struct Unavailable;
+
+#[async_trait]
+impl CacheBackend for Unavailable {
+    async fn get(&self, _key: &str) -> Result<Option<CachedVerdict>> {
+        Err(MailGuardError::DnsError("connection refused".into()))
+    }
+
+    async fn set(&self, _key: &str, _value: CachedVerdict, _ttl: Duration) -> Result<()> {
+        Err(MailGuardError::DnsError("connection refused".into()))
+    }
+
+    async fn remove(&self, _key: &str) -> Result<()> {
+        Err(MailGuardError::DnsError("connection refused".into()))
+    }
+
+    fn clear(&self) {}
+}
+
+fn playback_client() -> DnsClient {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "listed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    DnsClient::playback(fixture)
+}
+
+#[tokio::test]
+async fn test_custom_backend_stores_verdicts() {
+    let store = SharedStore::default();
+    let detector = MailGuard::builder()
+        .config(MailGuardConfig {
+            cache_ttl: Duration::from_secs(60),
+            threat_cache_ttl: None,
+            ..Default::default()
+        })
+        .dns_client(playback_client())
+        .cache_backend(store.clone())
+        .build()
+        .unwrap();
+
+    let first = detector.check_domain("listed.test").await.unwrap();
+    assert!(first.is_threat);
+    assert!(!first.from_cache);
+    let (verdict, ttl) = store.entries.lock().unwrap()["surbl:listed.test"].clone();
+    assert_eq!(verdict.threat_type(), Some(&ThreatType::Spam));
+    assert_eq!(ttl, Duration::from_secs(60));
+
+    let second = detector.check_domain("listed.test").await.unwrap();
+    assert!(second.from_cache);
+    assert_eq!(second.threat_type, Some(ThreatType::Spam));
+    assert_eq!(detector.cache_stats(), Some(1));
+
+    // The backend survives reloads that change the cache settings
+    detector
+        .reload(MailGuardConfig {
+            cache_ttl: Duration::from_secs(30),
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(
+        detector
+            .check_domain("listed.test")
+            .await
+            .unwrap()
+            .from_cache
+    );
+
+    detector.clear_cache();
+    assert_eq!(detector.cache_stats(), Some(0));
+}
+
+#[tokio::test]
+async fn test_verdicts_get_their_own_ttl() {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "listed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    fixture.insert(
+        "odd.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 200])],
+            ttl_secs: 300,
+        },
+    );
+    let store = SharedStore::default();
+    let detector = MailGuard::builder()
+        .config(MailGuardConfig {
+            cache_ttl: Duration::from_secs(300),
+            threat_cache_ttl: Some(Duration::from_secs(3600)),
+            uncertain_cache_ttl: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .dns_client(DnsClient::playback(fixture))
+        .cache_backend(store.clone())
+        .build()
+        .unwrap();
+
+    for domain in ["listed.test", "odd.test", "safe.test"] {
+        detector.check_domain(domain).await.unwrap();
+    }
+    let entries = store.entries.lock().unwrap();
+    assert_eq!(entries["surbl:listed.test"].1, Duration::from_secs(3600));
+    assert_eq!(entries["surbl:odd.test"].1, Duration::from_secs(30));
+    assert_eq!(entries["surbl:safe.test"].1, Duration::from_secs(300));
+}
+
+#[tokio::test]
+async fn test_backend_errors_count_as_misses() {
+    let detector = MailGuard::builder()
+        .dns_client(playback_client())
+        .cache_backend(Unavailable)
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        let status = detector.check_domain("listed.test").await.unwrap();
+        assert!(status.is_threat);
+        assert!(!status.from_cache);
+    }
+    assert_eq!(detector.cache_stats(), None);
+}
+
+#[tokio::test]
+async fn test_preload_warms_the_cache() {
+    let store = SharedStore::default();
+    let detector = MailGuard::builder()
+        .dns_client(playback_client())
+        .cache_backend(store.clone())
+        .build()
+        .unwrap();
+
+    let checked = detector
+        .preload(["listed.test", "quiet.test", "Listed.Test", "not a domain"])
+        .await;
+    assert_eq!(checked, 2);
+    assert_eq!(detector.cache_stats(), Some(2));
+    assert!(
+        detector
+            .check_domain("listed.test")
+            .await
+            .unwrap()
+            .from_cache
+    );
+
+    let added = detector
+        .preload_verdicts([
+            ("Known-Bad.test", Some(ThreatType::Phishing)),
+            ("known-good.test", None),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(added, 2);
+    let status = detector.check_domain("known-bad.test").await.unwrap();
+    assert!(status.from_cache);
+    assert_eq!(status.threat_type, Some(ThreatType::Phishing));
+    assert_eq!(
+        store.entries.lock().unwrap()["surbl:known-bad.test"].1,
+        MailGuardConfig::default().threat_cache_ttl.unwrap()
+    );
+
+    assert!(
+        detector
+            .preload_verdicts([("bad domain", None)])
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_ttl_jitter_spreads_expiry() {
+    let store = SharedStore::default();
+    let detector = MailGuard::builder()
+        .config(MailGuardConfig {
+            cache_ttl: Duration::from_secs(100),
+            cache_ttl_jitter: 0.2,
+            ..Default::default()
+        })
+        .dns_client(DnsClient::playback(Fixture::new()))
+        .cache_backend(store.clone())
+        .build()
+        .unwrap();
+
+    let domains: Vec<String> = (0..50).map(|i| format!("domain{i}.test")).collect();
+    detector.check_domains(&domains).await;
+
+    let entries = store.entries.lock().unwrap();
+    let ttls: Vec<Duration> = entries.values().map(|(_, ttl)| *ttl).collect();
+    assert_eq!(ttls.len(), 50);
+    assert!(
+        ttls.iter()
+            .all(|ttl| (Duration::from_secs(80)..=Duration::from_secs(120)).contains(ttl))
+    );
+    assert!(ttls.iter().any(|ttl| *ttl != ttls[0]));
+}
+
+#[tokio::test]
+async fn test_checks_are_cached_under_their_own_keys() {
+    let mut fixture = Fixture::new();
+    fixture.insert_mx(
+        "throwaway.test",
+        FixtureAnswer::Exchanges {
+            hosts: vec!["mail.mailinator.com".to_string()],
+            ttl_secs: 300,
+        },
+    );
+    let store = SharedStore::default();
+    let detector = MailGuard::builder()
+        .config(MailGuardConfig {
+            cache_ttl: Duration::from_secs(300),
+            detect_disposable_mx: true,
+            check_cache_ttls: [(
+                check::MX_FINGERPRINT.to_string(),
+                Duration::from_secs(86400),
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        })
+        .dns_client(DnsClient::playback(fixture))
+        .cache_backend(store.clone())
+        .build()
+        .unwrap();
+
+    let status = detector.check_domain("throwaway.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Disposable));
+    {
+        let entries = store.entries.lock().unwrap();
+        assert_eq!(
+            entries["surbl:throwaway.test"],
+            (CachedVerdict::pass(), Duration::from_secs(300))
+        );
+        let (verdict, ttl) = &entries["mx_fingerprint:throwaway.test"];
+        assert_eq!(verdict.threat_type(), Some(&ThreatType::Disposable));
+        assert_eq!(*ttl, Duration::from_secs(86400));
+    }
+
+    // Once the SURBL entry expires, only SURBL is asked again
+    store.entries.lock().unwrap().remove("surbl:throwaway.test");
+    let status = detector.check_domain("throwaway.test").await.unwrap();
+    assert!(status.from_cache);
+    assert_eq!(status.threat_type, Some(ThreatType::Disposable));
+    let checks: Vec<&str> = status
+        .check_results
+        .iter()
+        .map(|result| result.check.as_str())
+        .collect();
+    assert!(!checks.contains(&check::MX_FINGERPRINT));
+    assert!(
+        store
+            .entries
+            .lock()
+            .unwrap()
+            .contains_key("surbl:throwaway.test")
+    );
+
+    let config = MailGuardConfig {
+        check_cache_ttls: [(check::SURBL.to_string(), Duration::ZERO)]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
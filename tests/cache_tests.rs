@@ -4,7 +4,7 @@ use std::time::Duration;
 #[cfg(feature = "cache")]
 use mailguard_rs::ThreatType;
 #[cfg(feature = "cache")]
-use mailguard_rs::cache::{Cache, CacheEntry};
+use mailguard_rs::cache::{CLOCK_SKEW_TOLERANCE, Cache, CacheEntry, CachedVerdict};
 
 #[cfg(feature = "cache")]
 #[test]
@@ -27,13 +27,13 @@ fn test_cache_set_and_get() {
     let cache = Cache::new();
 
     // 设置一个条目
-    cache.set("example.com".to_string(), Some(ThreatType::Spam));
+    cache.set("example.com".to_string(), Some(ThreatType::Spam).into());
     assert_eq!(cache.size(), 1);
 
     // 获取条目
     let result = cache.get("example.com");
     assert!(result.is_some());
-    assert_eq!(result.unwrap(), Some(ThreatType::Spam));
+    assert_eq!(result.unwrap().threat_type(), Some(&ThreatType::Spam));
 }
 
 #[cfg(feature = "cache")]
@@ -49,8 +49,8 @@ fn test_cache_get_nonexistent() {
 fn test_cache_clear() {
     let cache = Cache::new();
 
-    cache.set("example1.com".to_string(), Some(ThreatType::Spam));
-    cache.set("example2.com".to_string(), None);
+    cache.set("example1.com".to_string(), Some(ThreatType::Spam).into());
+    cache.set("example2.com".to_string(), CachedVerdict::pass());
     assert_eq!(cache.size(), 2);
 
     cache.clear();
@@ -61,7 +61,7 @@ fn test_cache_clear() {
 #[test]
 fn test_cache_entry_expiration() {
     let ttl = Duration::from_millis(1);
-    let entry = CacheEntry::new(Some(ThreatType::Spam), ttl);
+    let entry = CacheEntry::new(CachedVerdict::listed(ThreatType::Spam, []), ttl);
 
     // 立即检查应该未过期
     assert!(!entry.is_expired());
@@ -73,14 +73,60 @@ fn test_cache_entry_expiration() {
     assert!(entry.is_expired());
 }
 
+#[cfg(feature = "cache")]
+#[test]
+fn test_cache_entry_wall_clock_expiration() {
+    use std::time::{Instant, SystemTime};
+
+    let ttl = Duration::from_secs(300);
+    let entry = CacheEntry::new(CachedVerdict::listed(ThreatType::Spam, []), ttl);
+    let now = Instant::now();
+    let wall_now = SystemTime::now();
+
+    assert!(!entry.is_expired_at(now, wall_now));
+
+    // The monotonic clock stood still during a suspend; the wall clock didn't
+    assert!(entry.is_expired_at(now, wall_now + Duration::from_secs(3600)));
+
+    // Small backwards adjustments are tolerated, large ones are not
+    assert!(!entry.is_expired_at(now, wall_now - Duration::from_secs(1)));
+    assert!(entry.is_expired_at(
+        now,
+        wall_now - CLOCK_SKEW_TOLERANCE - Duration::from_secs(1)
+    ));
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_cache_entry_restore() {
+    use std::time::SystemTime;
+
+    let ttl = Duration::from_secs(300);
+
+    let fresh = CacheEntry::restore(
+        Some(ThreatType::Spam),
+        SystemTime::now() - Duration::from_secs(10),
+        ttl,
+    );
+    assert!(!fresh.is_expired());
+    assert!(fresh.timestamp.elapsed() >= Duration::from_secs(10));
+
+    let stale: CacheEntry = CacheEntry::restore(
+        CachedVerdict::pass(),
+        SystemTime::now() - Duration::from_secs(600),
+        ttl,
+    );
+    assert!(stale.is_expired());
+}
+
 #[cfg(feature = "cache")]
 #[test]
 fn test_cache_cleanup_expired() {
     let cache = Cache::with_ttl(Duration::from_millis(1));
 
     // 添加一些条目
-    cache.set("example1.com".to_string(), Some(ThreatType::Spam));
-    cache.set("example2.com".to_string(), None);
+    cache.set("example1.com".to_string(), Some(ThreatType::Spam).into());
+    cache.set("example2.com".to_string(), CachedVerdict::pass());
     assert_eq!(cache.size(), 2);
 
     // 等待过期
@@ -104,7 +150,7 @@ fn test_cache_thread_safety() {
         let cache_clone = Arc::clone(&cache);
         let handle = thread::spawn(move || {
             let key = format!("example{}.com", i);
-            cache_clone.set(key, Some(ThreatType::Spam));
+            cache_clone.set(key, Some(ThreatType::Spam).into());
         });
         handles.push(handle);
     }
@@ -117,3 +163,651 @@ fn test_cache_thread_safety() {
     // 验证所有条目都已添加
     assert_eq!(cache.size(), 10);
 }
+
+#[cfg(feature = "cache")]
+struct CountingCheck(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+#[cfg(feature = "cache")]
+#[mailguard_rs::check::async_trait]
+impl mailguard_rs::Check for CountingCheck {
+    fn name(&self) -> &str {
+        "counting"
+    }
+
+    async fn check(
+        &self,
+        _domain: &str,
+    ) -> mailguard_rs::error::Result<mailguard_rs::CheckOutcome> {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(mailguard_rs::CheckOutcome::Pass)
+    }
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_email_cache() {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use mailguard_rs::{Fixture, MailGuard, MailGuardConfig, dns::DnsClient};
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let config = MailGuardConfig {
+        enable_cache: false,
+        email_cache_ttl: Some(Duration::from_secs(60)),
+        // Let gmail.com reach the counting check
+        trusted_providers: Default::default(),
+        ..Default::default()
+    };
+    let detector = MailGuard::builder()
+        .config(config)
+        .dns_client(DnsClient::playback(Fixture::new()))
+        .with_check(CountingCheck(calls.clone()))
+        .build()
+        .unwrap();
+
+    let first = detector.check_email("J.Doe@gmail.com").await.unwrap();
+    assert!(!first.from_cache);
+
+    // Same mailbox, different spelling
+    let second = detector.check_email("jdoe+news@gmail.com").await.unwrap();
+    assert!(second.from_cache);
+    assert_eq!(second.email, "jdoe+news@gmail.com");
+    assert_eq!(second.subaddress.unwrap().tag, "news");
+    assert_eq!(second.normalized_email, first.normalized_email);
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(detector.email_cache_stats(), Some(1));
+    assert_eq!(detector.cache_stats(), None);
+
+    detector.clear_cache();
+    assert_eq!(detector.email_cache_stats(), Some(0));
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_cache_evicts_least_recently_used() {
+    let cache = Cache::new().with_max_entries(2);
+    cache.set("a.com".to_string(), CachedVerdict::pass());
+    cache.set("b.com".to_string(), CachedVerdict::pass());
+
+    // Reading a.com makes b.com the eviction candidate
+    assert!(cache.get("a.com").is_some());
+    cache.set("c.com".to_string(), Some(ThreatType::Spam).into());
+    assert_eq!(cache.size(), 2);
+    assert!(cache.get("b.com").is_none());
+    assert!(cache.get("a.com").is_some());
+    assert!(cache.get("c.com").is_some());
+
+    // Overwriting an entry doesn't evict another
+    cache.set("c.com".to_string(), CachedVerdict::pass());
+    assert_eq!(cache.size(), 2);
+    assert!(cache.get("a.com").is_some());
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_cache_snapshot_round_trip() {
+    let path = std::env::temp_dir().join(format!("mailguard-snapshot-{}.json", std::process::id()));
+    let cache = Cache::with_ttl(Duration::from_secs(60));
+    cache.set("spam.test".to_string(), Some(ThreatType::Spam).into());
+    cache.set("safe.test".to_string(), CachedVerdict::pass());
+    cache.set_with_ttl(
+        "expired.test".to_string(),
+        CachedVerdict::pass(),
+        Duration::from_millis(1),
+    );
+    std::thread::sleep(Duration::from_millis(10));
+    cache.save_to(&path).unwrap();
+
+    let restored = Cache::new();
+    assert_eq!(restored.load_from(&path).unwrap(), 2);
+    assert_eq!(
+        restored.get("spam.test"),
+        Some(Some(ThreatType::Spam).into())
+    );
+    assert_eq!(restored.get("safe.test"), Some(CachedVerdict::pass()));
+    assert_eq!(restored.get("expired.test"), None);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_detector_cache_snapshot() {
+    use std::net::IpAddr;
+
+    use mailguard_rs::{Fixture, FixtureAnswer, MailGuard, MailGuardConfig, dns::DnsClient};
+
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "listed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    let path = std::env::temp_dir().join(format!("mailguard-seed-{}.json", std::process::id()));
+
+    let detector =
+        MailGuard::with_dns_client(MailGuardConfig::default(), DnsClient::playback(fixture));
+    detector.check_domain("listed.test").await.unwrap();
+    assert_eq!(detector.save_cache(&path).unwrap(), 1);
+
+    // A fresh detector seeded from the snapshot answers without DNS
+    let seeded = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(Fixture::new()),
+    );
+    assert_eq!(seeded.load_cache(&path).await.unwrap(), 1);
+    let status = seeded.check_domain("listed.test").await.unwrap();
+    assert!(status.from_cache);
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_large_cache_stays_bounded() {
+    let cache = Cache::new().with_max_entries(5000);
+    for i in 0..20_000 {
+        cache.set(format!("domain{i}.test"), CachedVerdict::pass());
+    }
+    assert!(cache.size() <= 5000);
+    assert!(cache.get("domain19999.test").is_some());
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_panic_does_not_poison_cache() {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    };
+
+    use mailguard_rs::cache::TtlCache;
+
+    /// Panics when cloned while armed, i.e. with the shard lock held
+    struct Fragile(Arc<AtomicBool>);
+
+    impl Clone for Fragile {
+        fn clone(&self) -> Self {
+            assert!(!self.0.load(Ordering::SeqCst), "clone failed");
+            Fragile(self.0.clone())
+        }
+    }
+
+    let armed = Arc::new(AtomicBool::new(false));
+    let cache = Arc::new(TtlCache::new());
+    cache.set("fragile.test".to_string(), Fragile(armed.clone()));
+
+    armed.store(true, Ordering::SeqCst);
+    let reader = cache.clone();
+    assert!(
+        std::thread::spawn(move || reader.get("fragile.test").is_some())
+            .join()
+            .is_err()
+    );
+    armed.store(false, Ordering::SeqCst);
+
+    let recovered = mailguard_rs::cache::poison_recoveries();
+    assert!(cache.get("fragile.test").is_some());
+    assert_eq!(cache.size(), 1);
+    // Counted when first recovered, not on every later lock
+    assert!(mailguard_rs::cache::poison_recoveries() > 0);
+    assert!(cache.get("fragile.test").is_some());
+    assert!(mailguard_rs::cache::poison_recoveries() <= recovered + 1);
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_sweeper_drops_expired_entries() {
+    use mailguard_rs::{Fixture, MailGuard, MailGuardConfig, dns::DnsClient};
+
+    let config = MailGuardConfig {
+        cache_ttl: Duration::from_millis(20),
+        cache_sweep_interval: Some(Duration::from_millis(10)),
+        ..Default::default()
+    };
+    let detector = MailGuard::with_dns_client(config, DnsClient::playback(Fixture::new()));
+    detector
+        .check_domain("never-read-again.test")
+        .await
+        .unwrap();
+    assert_eq!(detector.cache_stats(), Some(1));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(detector.cache_stats(), Some(0));
+
+    // Turned off by a reload, entries stay until they are read
+    detector
+        .reload(MailGuardConfig {
+            cache_ttl: Duration::from_millis(20),
+            ..Default::default()
+        })
+        .unwrap();
+    detector
+        .check_domain("never-read-again.test")
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(detector.cache_stats(), Some(1));
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_invalidate_single_domain() {
+    use mailguard_rs::{Fixture, MailGuard, MailGuardConfig, dns::DnsClient};
+
+    let config = MailGuardConfig {
+        email_cache_ttl: Some(Duration::from_secs(60)),
+        ..Default::default()
+    };
+    let detector = MailGuard::with_dns_client(config, DnsClient::playback(Fixture::new()));
+    detector.check_email("user@flagged.test").await.unwrap();
+    detector.check_email("user@other.test").await.unwrap();
+    assert_eq!(detector.cache_stats(), Some(2));
+    assert_eq!(detector.email_cache_stats(), Some(2));
+
+    detector.invalidate("Flagged.TEST").await.unwrap();
+    assert_eq!(detector.cache_stats(), Some(1));
+    assert_eq!(detector.email_cache_stats(), Some(1));
+    assert!(
+        !detector
+            .check_email("user@flagged.test")
+            .await
+            .unwrap()
+            .from_cache
+    );
+    assert!(
+        detector
+            .check_email("user@other.test")
+            .await
+            .unwrap()
+            .from_cache
+    );
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_export_and_import_cache_json() {
+    use mailguard_rs::{CacheRecord, Fixture, MailGuard, MailGuardConfig, dns::DnsClient};
+
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(Fixture::new()),
+    );
+    detector.check_domain("learned.test").await.unwrap();
+
+    let json = detector.export_cache_json();
+    let records: Vec<CacheRecord> = serde_json::from_str(&json).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].key, "surbl:learned.test");
+    assert_eq!(records[0].value, CachedVerdict::pass());
+    assert_eq!(records[0].ttl, Duration::from_secs(300));
+
+    let staging = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(Fixture::new()),
+    );
+    assert_eq!(staging.import_cache_json(&json).await.unwrap(), 1);
+    assert!(
+        staging
+            .check_domain("learned.test")
+            .await
+            .unwrap()
+            .from_cache
+    );
+
+    assert!(staging.import_cache_json("{").await.is_err());
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_cache_memory_bound() {
+    let cache = Cache::new();
+    cache.set("a.test".to_string(), CachedVerdict::pass());
+    let per_entry = cache.memory_usage();
+    assert!(per_entry > "a.test".len());
+
+    // Room for three entries: adding a fourth evicts the oldest
+    let cache = Cache::new().with_max_memory_bytes(per_entry * 3);
+    for domain in ["a.test", "b.test", "c.test", "d.test"] {
+        cache.set(domain.to_string(), CachedVerdict::pass());
+    }
+    assert_eq!(cache.size(), 3);
+    assert!(cache.memory_usage() <= per_entry * 3);
+    assert!(cache.get("a.test").is_none());
+    assert!(cache.get("d.test").is_some());
+
+    cache.remove("d.test");
+    assert_eq!(cache.memory_usage(), per_entry * 2);
+    cache.clear();
+    assert_eq!(cache.memory_usage(), 0);
+
+    // Heap data counted by a weigher takes up the budget too
+    let cache = mailguard_rs::cache::TtlCache::<String>::new()
+        .with_weigher(String::len)
+        .with_max_memory_bytes(per_entry * 3);
+    cache.set("big.test".to_string(), "x".repeat(per_entry * 4));
+    assert_eq!(cache.size(), 0);
+    assert_eq!(cache.memory_usage(), 0);
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_detector_cache_memory_usage() {
+    use mailguard_rs::{Fixture, MailGuard, MailGuardConfig, dns::DnsClient};
+
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig {
+            email_cache_ttl: Some(Duration::from_secs(60)),
+            ..Default::default()
+        },
+        DnsClient::playback(Fixture::new()),
+    );
+    assert_eq!(detector.cache_memory_usage(), Some(0));
+
+    detector.check_email("user@unknown.test").await.unwrap();
+    let usage = detector.cache_memory_usage().unwrap();
+    // The status weighs more than the domain verdict and the address
+    assert!(usage > 2 * "user@unknown.test".len());
+
+    detector.clear_cache();
+    assert_eq!(detector.cache_memory_usage(), Some(0));
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_configurable_shard_count() {
+    use mailguard_rs::{Fixture, MailGuard, MailGuardConfig, dns::DnsClient};
+
+    let cache = Cache::new().with_shards(64);
+    for i in 0..1000 {
+        cache.set(format!("domain{i}.test"), CachedVerdict::pass());
+    }
+    let stats = cache.shard_stats();
+    assert_eq!(stats.len(), 64);
+    assert_eq!(stats.iter().map(|shard| shard.entries).sum::<usize>(), 1000);
+    assert_eq!(
+        stats.iter().map(|shard| shard.memory_bytes).sum::<usize>(),
+        cache.memory_usage()
+    );
+
+    // Every shard holds at least one entry
+    assert_eq!(
+        Cache::new()
+            .with_max_entries(4)
+            .with_shards(8)
+            .shard_stats()
+            .len(),
+        4
+    );
+
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig {
+            cache_shards: Some(4),
+            ..Default::default()
+        },
+        DnsClient::playback(Fixture::new()),
+    );
+    detector.check_domain("quiet.test").await.unwrap();
+    let stats = detector.cache_shard_stats();
+    assert_eq!(stats.len(), 4);
+    assert_eq!(stats.iter().map(|shard| shard.entries).sum::<usize>(), 1);
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_dns_failures_are_cached_briefly() {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    };
+
+    use mailguard_rs::{
+        Check, CheckOutcome, Fixture, MailGuard, MailGuardConfig, MailGuardError,
+        check::async_trait, dns::DnsClient, error::Result,
+    };
+
+    /// A resolver that is down
+    struct Outage(Arc<AtomicU32>);
+
+    #[async_trait]
+    impl Check for Outage {
+        fn name(&self) -> &str {
+            "outage"
+        }
+
+        async fn check(&self, _domain: &str) -> Result<CheckOutcome> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Err(MailGuardError::DnsError("SERVFAIL".into()))
+        }
+    }
+
+    let calls = Arc::new(AtomicU32::new(0));
+    let detector = MailGuard::builder()
+        .config(MailGuardConfig {
+            failure_cache_ttl: Some(Duration::from_millis(50)),
+            ..Default::default()
+        })
+        .dns_client(DnsClient::playback(Fixture::new()))
+        .with_check(Outage(calls.clone()))
+        .build()
+        .unwrap();
+
+    assert!(matches!(
+        detector
+            .check_domain("down.test")
+            .await
+            .unwrap_err()
+            .root_cause(),
+        MailGuardError::DnsError(_)
+    ));
+    let cached = detector.check_domain("down.test").await.unwrap_err();
+    assert!(
+        matches!(&cached, MailGuardError::CachedFailure(err) if matches!(**err, MailGuardError::CheckFailed { .. }))
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(matches!(
+        detector
+            .check_domain("down.test")
+            .await
+            .unwrap_err()
+            .root_cause(),
+        MailGuardError::DnsError(_)
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    detector.invalidate("down.test").await.unwrap();
+    assert!(matches!(
+        detector
+            .check_domain("down.test")
+            .await
+            .unwrap_err()
+            .root_cause(),
+        MailGuardError::DnsError(_)
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_cache_hooks() {
+    use std::sync::{Arc, Mutex};
+
+    use mailguard_rs::{CacheHooks, Fixture, MailGuard, MailGuardConfig, dns::DnsClient};
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let log = |event: &'static str| {
+        let events = events.clone();
+        move |domain: &str, _: &CachedVerdict| {
+            events.lock().unwrap().push(format!("{event} {domain}"))
+        }
+    };
+    let hooks = CacheHooks::new()
+        .on_insert(log("insert"))
+        .on_hit(log("hit"))
+        .on_evict(log("evict"))
+        .on_expire(log("expire"));
+
+    let cache = Cache::with_ttl(Duration::from_millis(20))
+        .with_hooks(hooks.clone())
+        .with_max_entries(1);
+    cache.set("a.test".to_string(), CachedVerdict::pass());
+    assert!(cache.get("a.test").is_some());
+    cache.set("b.test".to_string(), CachedVerdict::pass());
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(cache.get("b.test").is_none());
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "insert a.test",
+            "hit a.test",
+            "insert b.test",
+            "evict a.test",
+            "expire b.test"
+        ]
+    );
+
+    events.lock().unwrap().clear();
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig {
+            cache_hooks: hooks,
+            ..Default::default()
+        },
+        DnsClient::playback(Fixture::new()),
+    );
+    detector.check_domain("quiet.test").await.unwrap();
+    detector.check_domain("quiet.test").await.unwrap();
+    assert_eq!(
+        *events.lock().unwrap(),
+        ["insert surbl:quiet.test", "hit surbl:quiet.test"]
+    );
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_get_or_try_insert_with_runs_once_per_key() {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    };
+
+    let cache = Cache::new();
+    let calls = Arc::new(AtomicU32::new(0));
+    let lookup = |fail: bool| {
+        let cache = cache.clone();
+        let calls = calls.clone();
+        async move {
+            cache
+                .get_or_try_insert_with("slow.test", || async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    if fail {
+                        Err("SERVFAIL")
+                    } else {
+                        Ok(Some(ThreatType::Spam).into())
+                    }
+                })
+                .await
+        }
+    };
+
+    // A failure is only seen by the caller whose closure failed
+    let (failed, retried) = tokio::join!(lookup(true), lookup(false));
+    assert_eq!(failed, Err("SERVFAIL"));
+    assert_eq!(retried, Ok(Some(ThreatType::Spam).into()));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    cache.clear();
+    calls.store(0, Ordering::SeqCst);
+    let tasks: Vec<_> = (0..10).map(|_| tokio::spawn(lookup(false))).collect();
+    for task in tasks {
+        assert_eq!(task.await.unwrap(), Ok(Some(ThreatType::Spam).into()));
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_concurrent_checks_of_a_domain_query_once() {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    };
+
+    use mailguard_rs::{
+        Check, CheckOutcome, Fixture, MailGuard, MailGuardConfig, check::async_trait,
+        dns::DnsClient, error::Result,
+    };
+
+    struct Slow(Arc<AtomicU32>);
+
+    #[async_trait]
+    impl Check for Slow {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        async fn check(&self, _domain: &str) -> Result<CheckOutcome> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(CheckOutcome::Threat(Some(ThreatType::Phishing)))
+        }
+
+        fn cacheable(&self) -> bool {
+            true
+        }
+    }
+
+    let calls = Arc::new(AtomicU32::new(0));
+    let detector = Arc::new(
+        MailGuard::builder()
+            .config(MailGuardConfig::default())
+            .dns_client(DnsClient::playback(Fixture::new()))
+            .with_check(Slow(calls.clone()))
+            .build()
+            .unwrap(),
+    );
+
+    let tasks: Vec<_> = (0..10)
+        .map(|_| {
+            let detector = detector.clone();
+            tokio::spawn(async move { detector.check_domain("hot.test").await })
+        })
+        .collect();
+    let mut from_cache = 0;
+    for task in tasks {
+        let status = task.await.unwrap().unwrap();
+        assert_eq!(status.threat_type, Some(ThreatType::Phishing));
+        from_cache += usize::from(status.from_cache);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(from_cache, 9);
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_sliding_expiration() {
+    let cache = Cache::with_ttl(Duration::from_millis(100))
+        .with_sliding_expiration(Duration::from_millis(250));
+    cache.set("hot.test".to_string(), CachedVerdict::pass());
+    cache.set("idle.test".to_string(), CachedVerdict::pass());
+
+    // Each read keeps the hot entry for another TTL...
+    for _ in 0..3 {
+        std::thread::sleep(Duration::from_millis(70));
+        assert!(cache.get("hot.test").is_some());
+    }
+    assert!(cache.get("idle.test").is_none());
+
+    // ...until it reaches the max age
+    std::thread::sleep(Duration::from_millis(70));
+    assert!(cache.get("hot.test").is_none());
+}
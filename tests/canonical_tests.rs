@@ -0,0 +1,58 @@
+use mailguard_rs::{
+    Fixture, MailGuard, MailGuardConfig, dns::DnsClient, to_canonical_json,
+    to_canonical_json_pretty,
+};
+
+#[test]
+fn test_keys_are_sorted() {
+    #[derive(serde::Serialize)]
+    struct Unsorted {
+        zeta: u8,
+        alpha: Nested,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Nested {
+        mid: bool,
+        beta: Vec<u8>,
+    }
+
+    let value = Unsorted {
+        zeta: 1,
+        alpha: Nested {
+            mid: true,
+            beta: vec![3, 1, 2],
+        },
+    };
+
+    assert_eq!(
+        to_canonical_json(&value).unwrap(),
+        r#"{"alpha":{"beta":[3,1,2],"mid":true},"zeta":1}"#
+    );
+}
+
+#[tokio::test]
+async fn test_email_status_is_stable() {
+    // A cached second answer would differ in from_cache and the checks run
+    let config = MailGuardConfig {
+        enable_cache: false,
+        ..Default::default()
+    };
+    let detector = MailGuard::with_dns_client(config, DnsClient::playback(Fixture::new()));
+    let first = detector.check_email("User+tag@example.com").await.unwrap();
+    let mut second = detector.check_email("User+tag@example.com").await.unwrap();
+    // Only the check time differs between runs
+    second.checked_at = first.checked_at;
+
+    let json = to_canonical_json_pretty(&first).unwrap();
+    assert_eq!(json, to_canonical_json_pretty(&second).unwrap());
+
+    let keys: Vec<&str> = json
+        .lines()
+        .filter(|line| line.starts_with("  \""))
+        .filter_map(|line| line.trim().split('"').nth(1))
+        .collect();
+    let mut sorted = keys.clone();
+    sorted.sort_unstable();
+    assert_eq!(keys, sorted);
+}
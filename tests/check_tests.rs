@@ -0,0 +1,304 @@
+use std::{
+    net::IpAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use mailguard_rs::{
+    Check, CheckOutcome, Fixture, FixtureAnswer, MailGuard, MailGuardConfig, MailGuardError,
+    Reason, ThreatType,
+    check::{self, async_trait},
+    dns::DnsClient,
+    error::Result,
+};
+
+/// Flags `*.bad.test` as phishing and counts its invocations
+struct Reputation {
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Check for Reputation {
+    fn name(&self) -> &str {
+        "reputation"
+    }
+
+    async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if domain.ends_with(".bad.test") {
+            return Ok(CheckOutcome::Threat(Some(ThreatType::Phishing)));
+        }
+        Ok(CheckOutcome::Pass)
+    }
+}
+
+struct Failing;
+
+#[async_trait]
+impl Check for Failing {
+    fn name(&self) -> &str {
+        "failing"
+    }
+
+    async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+        Err(MailGuardError::invalid_domain(
+            domain,
+            "rejected by the check",
+        ))
+    }
+}
+
+fn playback_client() -> DnsClient {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "spam.bad.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    DnsClient::playback(fixture)
+}
+
+#[tokio::test]
+async fn test_custom_check_decides() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let detector = MailGuard::builder()
+        .dns_client(playback_client())
+        .with_check(Reputation {
+            calls: calls.clone(),
+        })
+        .build()
+        .unwrap();
+
+    let status = detector.check_email("user@mail.bad.test").await.unwrap();
+    assert!(status.is_threat);
+    assert_eq!(status.threat_type, Some(ThreatType::Phishing));
+
+    let status = detector.check_domain("good.test").await.unwrap();
+    assert!(!status.is_threat);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_built_in_checks_run_first() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let config = MailGuardConfig {
+        allowlist: ["allowed.bad.test".to_string()].into_iter().collect(),
+        ..Default::default()
+    };
+    let detector = MailGuard::builder()
+        .config(config)
+        .dns_client(playback_client())
+        .with_check(Reputation {
+            calls: calls.clone(),
+        })
+        .build()
+        .unwrap();
+
+    // Allowlist wins over the custom check
+    let status = detector.check_domain("allowed.bad.test").await.unwrap();
+    assert!(!status.is_threat);
+
+    // SURBL decides before the custom check
+    let status = detector.check_domain("spam.bad.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_check_error_aborts() {
+    let detector = MailGuard::builder()
+        .dns_client(playback_client())
+        .with_check(Failing)
+        .build()
+        .unwrap();
+
+    let err = detector.check_domain("good.test").await.unwrap_err();
+    let MailGuardError::CheckFailed {
+        input, provider, ..
+    } = &err
+    else {
+        panic!("expected a check failure, got {err:?}");
+    };
+    assert_eq!(
+        (input.as_str(), provider.as_str()),
+        ("good.test", "failing")
+    );
+    assert!(matches!(
+        err.root_cause(),
+        MailGuardError::InvalidDomain { .. }
+    ));
+    assert_eq!(
+        err.to_string(),
+        r#"Check failing failed for good.test: Invalid domain "good.test": rejected by the check"#
+    );
+}
+
+#[test]
+fn test_builder_validates_config() {
+    let config = MailGuardConfig {
+        dns_timeout: std::time::Duration::ZERO,
+        ..Default::default()
+    };
+    let result = MailGuard::builder()
+        .config(config)
+        .dns_client(playback_client())
+        .build();
+    assert!(matches!(result, Err(MailGuardError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn test_check_order() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let config = MailGuardConfig {
+        check_order: vec!["reputation".to_string()],
+        ..Default::default()
+    };
+    let detector = MailGuard::builder()
+        .config(config)
+        .dns_client(playback_client())
+        .with_check(Reputation {
+            calls: calls.clone(),
+        })
+        .build()
+        .unwrap();
+
+    // The custom check now decides before SURBL
+    let status = detector.check_domain("spam.bad.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Phishing));
+    assert_eq!(status.check_results.len(), 1);
+    assert_eq!(status.check_results[0].check, "reputation");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_full_reporting() {
+    let config = MailGuardConfig {
+        blocklist: ["bad.test".to_string()].into_iter().collect(),
+        short_circuit: false,
+        ..Default::default()
+    };
+    let detector = MailGuard::builder()
+        .config(config)
+        .dns_client(playback_client())
+        .with_check(Reputation {
+            calls: Arc::new(AtomicUsize::new(0)),
+        })
+        .build()
+        .unwrap();
+
+    let status = detector.check_domain("spam.bad.test").await.unwrap();
+    // The first verdict in order still decides
+    assert!(status.is_threat);
+    assert_eq!(status.threat_type, None);
+
+    let results: Vec<(&str, &CheckOutcome)> = status
+        .check_results
+        .iter()
+        .map(|result| (result.check.as_str(), &result.outcome))
+        .collect();
+    assert_eq!(
+        results,
+        [
+            (check::ALLOWLIST, &CheckOutcome::Pass),
+            (check::BLOCKLIST, &CheckOutcome::Threat(None)),
+            (check::TRUSTED_PROVIDER, &CheckOutcome::Pass),
+            (check::SURBL, &CheckOutcome::Threat(Some(ThreatType::Spam))),
+            (
+                "reputation",
+                &CheckOutcome::Threat(Some(ThreatType::Phishing))
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_short_circuit_reports_checks_run() {
+    let config = MailGuardConfig {
+        blocklist: ["bad.test".to_string()].into_iter().collect(),
+        ..Default::default()
+    };
+    let detector = MailGuard::with_dns_client(config, playback_client());
+
+    let status = detector.check_domain("spam.bad.test").await.unwrap();
+    assert_eq!(status.check_results.len(), 2);
+    assert_eq!(status.check_results[1].check, check::BLOCKLIST);
+}
+
+#[test]
+fn test_unknown_check_order_name() {
+    let config = MailGuardConfig {
+        check_order: vec!["nonexistent".to_string()],
+        ..Default::default()
+    };
+    let result = MailGuard::builder()
+        .config(config)
+        .dns_client(playback_client())
+        .build();
+    assert!(matches!(result, Err(MailGuardError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn test_status_provenance() {
+    let before = std::time::SystemTime::now();
+    let detector = MailGuard::with_dns_client(MailGuardConfig::default(), playback_client());
+
+    let status = detector.check_email("user@spam.bad.test").await.unwrap();
+    assert_eq!(
+        status.sources_checked,
+        [
+            check::ALLOWLIST,
+            check::BLOCKLIST,
+            check::TRUSTED_PROVIDER,
+            check::SURBL
+        ]
+    );
+    assert!(status.checked_at >= before);
+    assert_eq!(status.crate_version, env!("CARGO_PKG_VERSION"));
+
+    let json = serde_json::to_value(&status).unwrap();
+    assert!(json["checked_at"].is_object());
+    assert_eq!(json["crate_version"], env!("CARGO_PKG_VERSION"));
+}
+
+#[tokio::test]
+async fn test_trusted_providers_skip_dns() {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "gmail.com.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(fixture.clone()),
+    );
+    let status = detector.check_email("user@gmail.com").await.unwrap();
+    assert!(!status.is_threat);
+    assert_eq!(
+        status.sources_checked,
+        [check::ALLOWLIST, check::BLOCKLIST, check::TRUSTED_PROVIDER]
+    );
+    assert_eq!(status.reasons, [Reason::TrustedProvider]);
+
+    // Cleared, every domain is looked up
+    let config = MailGuardConfig {
+        trusted_providers: Default::default(),
+        ..Default::default()
+    };
+    let detector = MailGuard::with_dns_client(config, DnsClient::playback(fixture));
+    assert!(
+        detector
+            .check_email("user@gmail.com")
+            .await
+            .unwrap()
+            .is_threat
+    );
+}
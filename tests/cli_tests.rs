@@ -0,0 +1,178 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+fn temp_file(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("mailguard-cli-{}-{name}", std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn mailguard(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mailguard"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_help_lists_the_commands() {
+    let output = mailguard(&["--help"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for command in ["check <email>", "domain <domain>", "batch <file>"] {
+        assert!(stdout.contains(command), "{stdout}");
+    }
+}
+
+#[test]
+fn test_usage_errors_exit_with_2() {
+    for args in [
+        &[][..],
+        &["lookup", "a@example.com"],
+        &["check"],
+        &["batch", "a.txt", "b.txt"],
+        &["check", "a@example.com", "--format", "xml"],
+        &["check", "a@example.com", "--timeout", "soon"],
+    ] {
+        let output = mailguard(args);
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Usage:"));
+    }
+}
+
+#[test]
+fn test_invalid_addresses_are_reported_on_stderr() {
+    let output = mailguard(&["check", "not-an-email", "--no-cache", "--format", "json"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), "[]");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("not-an-email: Invalid email address"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_batch_reads_one_address_per_line() {
+    let path = temp_file("lines.txt", "# signups\nfirst\n\nsecond@\n");
+
+    let output = mailguard(&["batch", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let lines: Vec<_> = stderr.lines().collect();
+    assert_eq!(lines.len(), 3, "{stderr}");
+    assert!(lines[0].starts_with("first: "));
+    assert!(lines[1].starts_with("second@: "));
+    assert!(lines[2].starts_with("Checked 2 addresses in "));
+    assert!(lines[2].ends_with(": 0 clean, 0 threats, 2 errors"));
+
+    let output = mailguard(&["batch", "/nonexistent/emails.txt"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_batch_reads_a_csv_column() {
+    let path = temp_file(
+        "signups.csv",
+        "id,email,name\n1,\"smith, john\",John\n2,nobody,Ann\n",
+    );
+
+    // .csv inputs default to the email column
+    let output = mailguard(&["batch", "--input", path.to_str().unwrap()]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("smith, john: "), "{stderr}");
+    assert!(stderr.contains("\nnobody: "), "{stderr}");
+
+    let output = mailguard(&["batch", path.to_str().unwrap(), "--column", "phone"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        stderr.contains(r#"no "phone" column in header"#),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_batch_writes_results_to_the_output_file() {
+    let input = temp_file("input.txt", "not-an-email\n");
+    let output_path =
+        std::env::temp_dir().join(format!("mailguard-cli-{}-out.json", std::process::id()));
+
+    let output = mailguard(&[
+        "batch",
+        input.to_str().unwrap(),
+        "--output",
+        output_path.to_str().unwrap(),
+        "--format",
+        "json",
+        "--concurrency",
+        "2",
+    ]);
+    let written = std::fs::read_to_string(&output_path).unwrap();
+    std::fs::remove_file(&input).unwrap();
+    std::fs::remove_file(&output_path).unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert_eq!(written.trim(), "[]");
+}
+
+/// Trusted providers are answered without DNS, so these run offline
+fn formatted(command: &str, input: &str, format: &str) -> String {
+    let output = mailguard(&[command, input, "--format", format]);
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_csv_output_has_a_stable_header() {
+    let csv = formatted("check", "User@Gmail.com", "csv");
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "input,email,normalized_email,domain,is_threat,threat_type,threats,confidence,\
+         from_cache,typo_of,rank,reasons,sources_checked,checked_at,crate_version"
+    );
+    assert!(
+        lines[1].starts_with("User@Gmail.com,User@Gmail.com,user@gmail.com,gmail.com,false,,,"),
+        "{csv}"
+    );
+    assert!(lines[1].contains(",trusted_provider,"), "{csv}");
+
+    let csv = formatted("domain", "gmail.com", "csv");
+    assert!(
+        csv.starts_with("input,domain,is_threat,threat_type,"),
+        "{csv}"
+    );
+}
+
+#[test]
+fn test_json_outputs_mirror_the_status() {
+    let ndjson = formatted("check", "user@gmail.com", "ndjson");
+    assert_eq!(ndjson.lines().count(), 1);
+    let status: serde_json::Value = serde_json::from_str(&ndjson).unwrap();
+    assert_eq!(status["email"], "user@gmail.com");
+    assert_eq!(status["is_threat"], false);
+
+    let json = formatted("domain", "gmail.com", "json");
+    let statuses: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(statuses[0]["domain"], "gmail.com");
+}
+
+#[test]
+fn test_table_output_aligns_columns() {
+    let table = formatted("check", "user@gmail.com", "table");
+    let lines: Vec<_> = table.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "INPUT           VERDICT  THREAT  CONFIDENCE  CACHED",
+            "user@gmail.com  clean            0.00        no",
+        ]
+    );
+}
@@ -0,0 +1,40 @@
+use mailguard_rs::VerdictComparison;
+
+#[test]
+fn test_empty_comparison() {
+    let comparison = VerdictComparison::new();
+    assert_eq!(comparison.total(), 0);
+    assert_eq!(comparison.agreement_rate(), 1.0);
+    assert_eq!(comparison.cohen_kappa(), 1.0);
+}
+
+#[test]
+fn test_comparison_counts() {
+    let mut comparison = VerdictComparison::new();
+    comparison.record("a@tempmail.com", true, true);
+    comparison.record("b@example.com", false, false);
+    comparison.record("c@example.org", false, false);
+    comparison.record("d@suspicious.net", true, false);
+    comparison.record("e@vendor-only.com", false, true);
+
+    assert_eq!(comparison.total(), 5);
+    assert_eq!(comparison.agreements(), 3);
+    assert_eq!(comparison.disagreements(), 2);
+    assert_eq!(comparison.only_ours, vec!["d@suspicious.net"]);
+    assert_eq!(comparison.only_external, vec!["e@vendor-only.com"]);
+    assert!((comparison.agreement_rate() - 0.6).abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_cohen_kappa() {
+    let mut comparison = VerdictComparison::new();
+    for i in 0..10 {
+        comparison.record(&format!("{i}@example.com"), i < 5, i < 5);
+    }
+    assert!((comparison.cohen_kappa() - 1.0).abs() < 1e-9);
+
+    let mut comparison = VerdictComparison::new();
+    comparison.record("a", true, false);
+    comparison.record("b", false, true);
+    assert!(comparison.cohen_kappa() < 0.0);
+}
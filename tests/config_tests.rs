@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use mailguard_rs::{MailGuard, MailGuardConfig, MailGuardError, ThreatType};
+
+fn assert_invalid_config(config: MailGuardConfig) {
+    let result = config.validate();
+    assert!(
+        matches!(
+            result,
+            Err(MailGuardError::InvalidConfig(_) | MailGuardError::InvalidRule(_))
+        ),
+        "{result:?}"
+    );
+    assert!(MailGuard::try_with_config(config).is_err());
+}
+
+#[test]
+fn test_default_config_is_valid() {
+    assert!(MailGuardConfig::default().validate().is_ok());
+    assert!(MailGuard::try_with_config(MailGuardConfig::default()).is_ok());
+}
+
+#[test]
+fn test_zero_timeouts_rejected() {
+    assert_invalid_config(MailGuardConfig {
+        dns_timeout: Duration::ZERO,
+        ..Default::default()
+    });
+
+    assert_invalid_config(MailGuardConfig {
+        cache_ttl: Duration::ZERO,
+        ..Default::default()
+    });
+
+    // A zero TTL is irrelevant with caching disabled
+    let config = MailGuardConfig {
+        enable_cache: false,
+        cache_ttl: Duration::ZERO,
+        ..Default::default()
+    };
+    assert!(config.validate().is_ok());
+
+    assert_invalid_config(MailGuardConfig {
+        threat_cache_ttl: Some(Duration::ZERO),
+        ..Default::default()
+    });
+
+    assert_invalid_config(MailGuardConfig {
+        cache_max_entries: Some(0),
+        ..Default::default()
+    });
+
+    assert_invalid_config(MailGuardConfig {
+        cache_max_memory_bytes: Some(0),
+        ..Default::default()
+    });
+
+    assert_invalid_config(MailGuardConfig {
+        cache_shards: Some(0),
+        ..Default::default()
+    });
+
+    assert_invalid_config(MailGuardConfig {
+        failure_cache_ttl: Some(Duration::ZERO),
+        ..Default::default()
+    });
+
+    assert_invalid_config(MailGuardConfig {
+        cache_ttl_jitter: 1.5,
+        ..Default::default()
+    });
+
+    assert_invalid_config(MailGuardConfig {
+        cache_sweep_interval: Some(Duration::ZERO),
+        ..Default::default()
+    });
+}
+
+#[test]
+fn test_overlapping_lists_rejected() {
+    let config = MailGuardConfig {
+        allowlist: ["Example.com".to_string()].into_iter().collect(),
+        blocklist: ["example.com".to_string()].into_iter().collect(),
+        ..Default::default()
+    };
+    let err = config.validate().unwrap_err();
+    assert!(err.to_string().contains("example.com"));
+}
+
+#[test]
+fn test_invalid_list_pattern_rejected() {
+    assert_invalid_config(MailGuardConfig {
+        blocklist: ["regex:[".to_string()].into_iter().collect(),
+        ..Default::default()
+    });
+}
+
+#[test]
+fn test_non_listing_octet_override_rejected() {
+    assert_invalid_config(MailGuardConfig {
+        octet_overrides: [(1, ThreatType::Spam)].into_iter().collect(),
+        ..Default::default()
+    });
+}
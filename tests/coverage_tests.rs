@@ -0,0 +1,83 @@
+use mailguard_rs::{
+    Check, CheckCoverage, CheckOutcome, Fixture, MailGuard, MailGuardConfig, QueryType, ThreatType,
+    check::{self, async_trait},
+    dns::DnsClient,
+    error::Result,
+};
+
+struct Reputation;
+
+#[async_trait]
+impl Check for Reputation {
+    fn name(&self) -> &str {
+        "reputation"
+    }
+
+    async fn check(&self, _domain: &str) -> Result<CheckOutcome> {
+        Ok(CheckOutcome::Pass)
+    }
+
+    fn coverage(&self) -> CheckCoverage {
+        CheckCoverage::new(self.name(), "internal feed").with_categories([ThreatType::Phishing])
+    }
+}
+
+#[test]
+fn test_default_coverage() {
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(Fixture::new()),
+    );
+    let report = detector.coverage();
+
+    let names: Vec<&str> = report.checks.iter().map(|c| c.check.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            check::ALLOWLIST,
+            check::BLOCKLIST,
+            check::TRUSTED_PROVIDER,
+            check::SURBL
+        ]
+    );
+
+    let surbl = &report.checks[3];
+    assert!(surbl.categories.contains(&ThreatType::Phishing));
+    assert!(surbl.source.contains("tempmail.so.multi.surbl.org"));
+
+    // Disposable backends are only recognized with MX fingerprinting on
+    assert_eq!(report.missing_categories, [ThreatType::Disposable]);
+    assert!(report.gaps.iter().any(|gap| gap.contains("TXT")));
+    assert!(report.gaps.iter().any(|gap| gap.contains("Typo detection")));
+}
+
+#[test]
+fn test_coverage_follows_config() {
+    let config = MailGuardConfig {
+        blocklist: ["spam.test".to_string()].into_iter().collect(),
+        detect_typos: true,
+        query_type: QueryType::ATxt,
+        octet_overrides: [(42, ThreatType::Unknown(42))].into_iter().collect(),
+        ..Default::default()
+    };
+    let detector = MailGuard::builder()
+        .config(config)
+        .dns_client(DnsClient::playback(Fixture::new()))
+        .with_check(Reputation)
+        .build()
+        .unwrap();
+    let report = detector.coverage();
+
+    assert_eq!(report.checks[1].source, "local list, 1 rule(s)");
+    assert!(
+        report.checks[3]
+            .categories
+            .contains(&ThreatType::Unknown(42))
+    );
+    assert_eq!(report.checks[4].check, "reputation");
+    assert_eq!(report.checks[4].categories, [ThreatType::Phishing]);
+
+    assert!(!report.gaps.iter().any(|gap| gap.contains("TXT")));
+    assert!(!report.gaps.iter().any(|gap| gap.contains("Typo detection")));
+    assert!(!report.gaps.iter().any(|gap| gap.contains("Blocklist")));
+}
@@ -0,0 +1,109 @@
+use std::{fs, net::IpAddr, path::PathBuf, time::Duration};
+
+use mailguard_rs::{
+    CacheBackend, CachedVerdict, DiskCache, Fixture, FixtureAnswer, MailGuard, ThreatType,
+    dns::DnsClient,
+};
+
+/// A fresh journal path for one test
+fn journal(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("mailguard-{name}-{}.jsonl", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn playback_client() -> DnsClient {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "listed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    DnsClient::playback(fixture)
+}
+
+#[tokio::test]
+async fn test_verdicts_survive_restart() {
+    let path = journal("restart");
+    let detector = MailGuard::builder()
+        .dns_client(playback_client())
+        .cache_backend(DiskCache::open(&path).unwrap())
+        .build()
+        .unwrap();
+    assert!(
+        !detector
+            .check_domain("listed.test")
+            .await
+            .unwrap()
+            .from_cache
+    );
+    drop(detector);
+
+    let detector = MailGuard::builder()
+        .dns_client(playback_client())
+        .cache_backend(DiskCache::open(&path).unwrap())
+        .build()
+        .unwrap();
+    let status = detector.check_domain("listed.test").await.unwrap();
+    assert!(status.from_cache);
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_journal_replay() {
+    let path = journal("replay");
+    let cache = DiskCache::open(&path).unwrap();
+    cache
+        .set("kept.test", CachedVerdict::pass(), Duration::from_secs(60))
+        .await
+        .unwrap();
+    cache
+        .set(
+            "removed.test",
+            CachedVerdict::pass(),
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+    cache.remove("removed.test").await.unwrap();
+    cache
+        .set(
+            "expired.test",
+            CachedVerdict::pass(),
+            Duration::from_millis(1),
+        )
+        .await
+        .unwrap();
+    drop(cache);
+
+    // A line written by an earlier version, then one cut short by a crash
+    let mut content =
+        fs::read_to_string(&path)
+            .unwrap()
+            .replacen(r#""value":[]"#, r#""value":null"#, 1);
+    content.push_str("{\"op\":\"set\",\"key\":");
+    fs::write(&path, content).unwrap();
+    std::thread::sleep(Duration::from_millis(10));
+
+    let cache = DiskCache::open(&path).unwrap();
+    assert_eq!(
+        cache.get("kept.test").await.unwrap(),
+        Some(CachedVerdict::pass())
+    );
+    assert_eq!(cache.get("removed.test").await.unwrap(), None);
+    assert_eq!(cache.get("expired.test").await.unwrap(), None);
+    assert_eq!(cache.size(), Some(1));
+
+    // Opening compacted the journal to the live entry
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+
+    cache.clear();
+    drop(cache);
+    assert_eq!(DiskCache::open(&path).unwrap().size(), Some(0));
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,117 @@
+use std::net::IpAddr;
+
+use mailguard_rs::{
+    Check, CheckOutcome, Ensemble, Fixture, FixtureAnswer, MailGuard, MailGuardConfig,
+    MailGuardError, ThreatType,
+    check::{self, async_trait},
+    dns::DnsClient,
+    error::Result,
+};
+
+/// Flags `*.bad.test` as phishing
+struct Heuristic;
+
+#[async_trait]
+impl Check for Heuristic {
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+
+    async fn check(&self, domain: &str) -> Result<CheckOutcome> {
+        if domain.ends_with("bad.test") {
+            return Ok(CheckOutcome::Threat(Some(ThreatType::Phishing)));
+        }
+        Ok(CheckOutcome::Pass)
+    }
+}
+
+/// SURBL lists `listed.test` and `bad.test` as spam
+fn playback_client() -> DnsClient {
+    let mut fixture = Fixture::new();
+    for domain in ["listed.test", "bad.test"] {
+        fixture.insert(
+            format!("{domain}.tempmail.so.multi.surbl.org"),
+            FixtureAnswer::Records {
+                ips: vec![IpAddr::from([127, 0, 0, 2])],
+                ttl_secs: 300,
+            },
+        );
+    }
+    DnsClient::playback(fixture)
+}
+
+fn detector(ensemble: Ensemble, blocklist: &[&str]) -> Result<MailGuard> {
+    let config = MailGuardConfig {
+        enable_cache: false,
+        blocklist: blocklist.iter().map(|domain| domain.to_string()).collect(),
+        ensemble: Some(ensemble),
+        ..Default::default()
+    };
+    MailGuard::builder()
+        .config(config)
+        .dns_client(playback_client())
+        .with_check(Heuristic)
+        .build()
+}
+
+#[tokio::test]
+async fn test_ensemble_needs_threshold_weight() {
+    let ensemble = Ensemble::new(2)
+        .weight(check::SURBL, 1)
+        .weight("heuristic", 1);
+    let detector = detector(ensemble, &[]).unwrap();
+
+    // One of two members listing it isn't enough
+    let status = detector.check_domain("listed.test").await.unwrap();
+    assert!(!status.is_threat);
+    let vote = status.ensemble.unwrap();
+    assert_eq!((vote.listed_weight, vote.total_weight), (1, 2));
+    assert!(!vote.is_listed());
+    assert_eq!(vote.votes.len(), 2);
+    assert!(vote.votes[0].listed);
+    assert!(!vote.votes[1].listed);
+
+    let status = detector.check_email("user@bad.test").await.unwrap();
+    assert!(status.is_threat);
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+    assert_eq!(status.ensemble.unwrap().listed_weight, 2);
+}
+
+#[tokio::test]
+async fn test_ensemble_threat_type_from_heaviest_member() {
+    let ensemble = Ensemble::new(1)
+        .weight(check::SURBL, 1)
+        .weight("heuristic", 3);
+    let detector = detector(ensemble, &[]).unwrap();
+
+    let status = detector.check_domain("bad.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Phishing));
+}
+
+#[tokio::test]
+async fn test_non_members_decide_alone() {
+    let ensemble = Ensemble::new(2)
+        .weight(check::SURBL, 1)
+        .weight("heuristic", 1);
+    let detector = detector(ensemble, &["listed.test"]).unwrap();
+
+    let status = detector.check_domain("listed.test").await.unwrap();
+    assert!(status.is_threat);
+    assert_eq!(status.threat_type, None);
+    assert!(status.ensemble.is_none());
+}
+
+#[test]
+fn test_build_rejects_invalid_ensembles() {
+    let unknown = Ensemble::new(1).weight("missing", 1);
+    assert!(matches!(
+        detector(unknown, &[]),
+        Err(MailGuardError::InvalidConfig(_))
+    ));
+
+    let unreachable = Ensemble::new(3).weight(check::SURBL, 1);
+    assert!(matches!(
+        detector(unreachable, &[]),
+        Err(MailGuardError::InvalidConfig(_))
+    ));
+}
@@ -0,0 +1,48 @@
+use std::{io, time::Duration};
+
+use mailguard_rs::{MailGuardError, OfflineMailGuard, ThreatType};
+
+#[test]
+fn test_transient_failures_are_retryable() {
+    let transient = [
+        MailGuardError::DnsError("SERVFAIL".into()),
+        MailGuardError::Timeout {
+            operation: "A query for example.com".to_string(),
+            elapsed: Duration::from_secs(5),
+        },
+        MailGuardError::RetryAfter(Duration::from_secs(1)),
+        MailGuardError::Storage(io::ErrorKind::BrokenPipe.into()),
+        MailGuardError::CachedFailure(Box::new(MailGuardError::DnsError("SERVFAIL".into()))),
+        MailGuardError::CheckFailed {
+            input: "example.com".to_string(),
+            provider: "surbl".to_string(),
+            source: Box::new(MailGuardError::DnsError("SERVFAIL".into())),
+        },
+    ];
+    for err in transient {
+        assert!(err.is_retryable(), "{err}");
+        assert!(err.is_infrastructure_error(), "{err}");
+        assert!(!err.is_input_error(), "{err}");
+    }
+}
+
+#[test]
+fn test_bad_input_is_permanent() {
+    let err = OfflineMailGuard::new()
+        .check_email("not-an-address")
+        .unwrap_err();
+    assert!(err.is_input_error());
+    assert!(!err.is_retryable());
+    assert!(!err.is_infrastructure_error());
+
+    let err = "scam".parse::<ThreatType>().unwrap_err();
+    assert!(err.is_input_error());
+}
+
+#[test]
+fn test_configuration_errors_are_neither() {
+    let err = MailGuardError::InvalidConfig("dns_timeout must be greater than zero".to_string());
+    assert!(!err.is_retryable());
+    assert!(!err.is_input_error());
+    assert!(!err.is_infrastructure_error());
+}
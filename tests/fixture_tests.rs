@@ -0,0 +1,293 @@
+use std::{net::IpAddr, time::Duration};
+
+use mailguard_rs::{
+    Fixture, FixtureAnswer, MailGuard, MailGuardConfig, MailGuardError, ThreatType,
+    dns::{DnsClient, QueryType, UNCATEGORIZED_LISTING},
+};
+
+fn listed(last_octet: u8) -> FixtureAnswer {
+    FixtureAnswer::Records {
+        ips: vec![IpAddr::from([127, 0, 0, last_octet])],
+        ttl_secs: 300,
+    }
+}
+
+fn sample_fixture() -> Fixture {
+    let mut fixture = Fixture::new();
+    fixture.insert("tempmail.test.tempmail.so.multi.surbl.org", listed(2));
+    fixture.insert("phish.test.tempmail.so.multi.surbl.org", listed(3));
+    fixture.insert("throttled.test.tempmail.so.multi.surbl.org", listed(1));
+    fixture.insert(
+        "clean.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::NoRecords,
+    );
+    fixture
+}
+
+#[tokio::test]
+async fn test_playback_answers() {
+    let client = DnsClient::playback(sample_fixture());
+
+    assert_eq!(
+        client.query_surbl("tempmail.test").await.unwrap(),
+        Some(ThreatType::Spam)
+    );
+    assert_eq!(
+        client.query_surbl("phish.test").await.unwrap(),
+        Some(ThreatType::Phishing)
+    );
+    assert_eq!(client.query_surbl("clean.test").await.unwrap(), None);
+    // Unrecorded queries are treated as not listed
+    assert_eq!(client.query_surbl("unknown.test").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_playback_throttled_answer() {
+    let client = DnsClient::playback(sample_fixture());
+
+    let err = client.query_surbl("throttled.test").await.unwrap_err();
+    assert!(matches!(err, MailGuardError::RetryAfter(_)));
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(300)));
+}
+
+#[tokio::test]
+async fn test_detector_with_playback_client() {
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(sample_fixture()),
+    );
+
+    let status = detector.check_email("user@tempmail.test").await.unwrap();
+    assert!(status.is_threat);
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+
+    let status = detector.check_email("user@clean.test").await.unwrap();
+    assert!(!status.is_threat);
+}
+
+#[test]
+fn test_fixture_save_and_load() {
+    let fixture = sample_fixture();
+    let path = std::env::temp_dir().join(format!("mailguard-fixture-{}.json", std::process::id()));
+
+    fixture.save(&path).unwrap();
+    let loaded = Fixture::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, fixture);
+}
+
+#[test]
+fn test_fixture_keys_are_case_insensitive() {
+    let mut fixture = Fixture::new();
+    fixture.insert("Example.TEST", FixtureAnswer::NoRecords);
+    assert_eq!(fixture.get("example.test"), Some(&FixtureAnswer::NoRecords));
+}
+
+#[tokio::test]
+async fn test_unknown_octet_overrides() {
+    let mut fixture = Fixture::new();
+    fixture.insert("newcode.test.tempmail.so.multi.surbl.org", listed(42));
+    fixture.insert("spam.test.tempmail.so.multi.surbl.org", listed(2));
+
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(fixture.clone()),
+    );
+    let status = detector.check_domain("newcode.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Unknown(42)));
+
+    let config = MailGuardConfig {
+        octet_overrides: [(42, ThreatType::Phishing), (2, ThreatType::Malware)]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    let detector = MailGuard::with_dns_client(config, DnsClient::playback(fixture));
+
+    let status = detector.check_domain("newcode.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Phishing));
+
+    // Known return codes are not remapped
+    let status = detector.check_domain("spam.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+}
+
+#[tokio::test]
+async fn test_ipv6_answers() {
+    let mut fixture = Fixture::new();
+    let mapped: IpAddr = "::ffff:127.0.0.3".parse().unwrap();
+    let compatible: IpAddr = "::127.0.0.4".parse().unwrap();
+    let unrelated: IpAddr = "2001:db8::2".parse().unwrap();
+    for (name, ip) in [
+        ("mapped", mapped),
+        ("compatible", compatible),
+        ("unrelated", unrelated),
+    ] {
+        fixture.insert(
+            format!("{name}.test.tempmail.so.multi.surbl.org"),
+            FixtureAnswer::Records {
+                ips: vec![ip],
+                ttl_secs: 300,
+            },
+        );
+    }
+    let client = DnsClient::playback(fixture);
+
+    assert_eq!(
+        client.query_surbl("mapped.test").await.unwrap(),
+        Some(ThreatType::Phishing)
+    );
+    assert_eq!(
+        client.query_surbl("compatible.test").await.unwrap(),
+        Some(ThreatType::Malware)
+    );
+    assert_eq!(client.query_surbl("unrelated.test").await.unwrap(), None);
+}
+
+fn txt_listed() -> FixtureAnswer {
+    FixtureAnswer::Text {
+        texts: vec!["Blocked, see https://www.surbl.org/lists".to_string()],
+        ttl_secs: 300,
+    }
+}
+
+#[tokio::test]
+async fn test_query_type_a_ignores_txt() {
+    let mut fixture = Fixture::new();
+    fixture.insert_txt("txtonly.test.tempmail.so.multi.surbl.org", txt_listed());
+
+    let client = DnsClient::playback(fixture);
+    assert_eq!(client.query_surbl("txtonly.test").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_query_type_a_txt() {
+    let mut fixture = Fixture::new();
+    fixture.insert_txt("txtonly.test.tempmail.so.multi.surbl.org", txt_listed());
+    fixture.insert("both.test.tempmail.so.multi.surbl.org", listed(3));
+    fixture.insert_txt("both.test.tempmail.so.multi.surbl.org", txt_listed());
+
+    let client = DnsClient::playback(fixture).with_query_type(QueryType::ATxt);
+    assert_eq!(
+        client.query_surbl("txtonly.test").await.unwrap(),
+        Some(UNCATEGORIZED_LISTING)
+    );
+    assert_eq!(
+        client.query_surbl("both.test").await.unwrap(),
+        Some(ThreatType::Phishing)
+    );
+    assert_eq!(client.query_surbl("clean.test").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_query_type_txt_first() {
+    let mut fixture = Fixture::new();
+    // An A listing without TXT is not consulted in TXT-first mode
+    fixture.insert("aonly.test.tempmail.so.multi.surbl.org", listed(2));
+    fixture.insert("both.test.tempmail.so.multi.surbl.org", listed(4));
+    fixture.insert_txt("both.test.tempmail.so.multi.surbl.org", txt_listed());
+    fixture.insert_txt("txtonly.test.tempmail.so.multi.surbl.org", txt_listed());
+
+    let client = DnsClient::playback(fixture).with_query_type(QueryType::TxtFirst);
+    assert_eq!(client.query_surbl("aonly.test").await.unwrap(), None);
+    assert_eq!(
+        client.query_surbl("both.test").await.unwrap(),
+        Some(ThreatType::Malware)
+    );
+    assert_eq!(
+        client.query_surbl("txtonly.test").await.unwrap(),
+        Some(UNCATEGORIZED_LISTING)
+    );
+}
+
+#[tokio::test]
+async fn test_answer_with_several_categories() {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "mixed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![
+                IpAddr::from([127, 0, 0, 2]),
+                IpAddr::from([127, 0, 0, 3]),
+                IpAddr::from([127, 0, 0, 9]),
+            ],
+            ttl_secs: 300,
+        },
+    );
+    let client = DnsClient::playback(fixture);
+
+    assert_eq!(
+        client.query_surbl_all("mixed.test").await.unwrap(),
+        [
+            ThreatType::Phishing,
+            ThreatType::Spam,
+            ThreatType::AbusedLegit
+        ]
+    );
+    assert_eq!(
+        client.query_surbl("mixed.test").await.unwrap(),
+        Some(ThreatType::Phishing)
+    );
+    assert!(
+        client
+            .query_surbl_all("unlisted.test")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_threats_carry_the_list_answer() {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "mixed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2]), IpAddr::from([127, 0, 0, 4])],
+            ttl_secs: 300,
+        },
+    );
+    let detector =
+        MailGuard::with_dns_client(MailGuardConfig::default(), DnsClient::playback(fixture));
+
+    let status = detector.check_domain("mixed.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Malware));
+    assert_eq!(status.threats.len(), 2);
+    let malware = &status.threats[0];
+    assert_eq!(malware.kind, ThreatType::Malware);
+    assert_eq!(malware.source_list, "surbl");
+    assert_eq!(
+        malware.queried_zone.as_deref(),
+        Some("tempmail.so.multi.surbl.org")
+    );
+    assert_eq!(malware.raw_ip, Some(IpAddr::from([127, 0, 0, 4])));
+    assert!(malware.observed_at <= status.checked_at);
+    assert_eq!(status.threats[1].kind, ThreatType::Spam);
+    assert_eq!(status.threats[1].raw_ip, Some(IpAddr::from([127, 0, 0, 2])));
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_cached_threats_keep_their_evidence() {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "mixed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2]), IpAddr::from([127, 0, 0, 4])],
+            ttl_secs: 300,
+        },
+    );
+    let detector =
+        MailGuard::with_dns_client(MailGuardConfig::default(), DnsClient::playback(fixture));
+
+    let first = detector.check_domain("mixed.test").await.unwrap();
+    assert!(!first.from_cache);
+    let second = detector.check_domain("mixed.test").await.unwrap();
+    assert!(second.from_cache);
+    assert_eq!(second.threat_type, Some(ThreatType::Malware));
+    // Every category, with the zone, answer and time of the original lookup
+    assert_eq!(second.threats, first.threats);
+    assert_eq!(second.threats[1].kind, ThreatType::Spam);
+    assert_eq!(second.threats[1].raw_ip, Some(IpAddr::from([127, 0, 0, 2])));
+}
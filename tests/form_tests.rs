@@ -0,0 +1,136 @@
+use std::net::IpAddr;
+
+use mailguard_rs::{
+    Check, CheckOutcome, EmailForm, FieldPolicy, FieldRejection, Fixture, FixtureAnswer, MailGuard,
+    MailGuardConfig, MailGuardError, ThreatType, check::async_trait, dns::DnsClient, error::Result,
+};
+
+fn guard() -> MailGuard {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "tempmail.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    let config = MailGuardConfig {
+        detect_typos: true,
+        ..Default::default()
+    };
+    MailGuard::with_dns_client(config, DnsClient::playback(fixture))
+}
+
+fn form() -> EmailForm {
+    EmailForm::new()
+        .field("billing", FieldPolicy::required())
+        .field("technical", FieldPolicy::optional().rejecting_typos())
+        .field("abuse", FieldPolicy::optional())
+}
+
+#[tokio::test]
+async fn test_accepted_form() {
+    let report = form()
+        .validate(
+            &guard(),
+            [
+                ("billing", "ap@example.com"),
+                ("technical", " ops@example.com "),
+                ("unrelated", "not checked"),
+            ],
+        )
+        .await;
+
+    assert!(report.is_accepted());
+    assert_eq!(report.fields.len(), 3);
+    assert!(report.fields["technical"].status.is_some());
+    assert!(report.fields["abuse"].status.is_none());
+}
+
+#[tokio::test]
+async fn test_rejections_per_field() {
+    let report = form()
+        .validate(
+            &guard(),
+            [
+                ("billing", ""),
+                ("technical", "ops@gmial.com"),
+                ("abuse", "abuse@tempmail.test"),
+            ],
+        )
+        .await;
+
+    assert!(!report.is_accepted());
+    let rejections: Vec<_> = report.rejections().collect();
+    assert_eq!(
+        rejections,
+        [
+            ("abuse", &FieldRejection::Threat(Some(ThreatType::Spam))),
+            ("billing", &FieldRejection::Missing),
+            ("technical", &FieldRejection::Typo("gmail.com".to_string())),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_address() {
+    let report = form()
+        .validate(&guard(), [("billing", "not-an-email")])
+        .await;
+    assert!(matches!(
+        report.fields["billing"].rejection,
+        Some(FieldRejection::Invalid { .. })
+    ));
+}
+
+struct Unavailable;
+
+#[async_trait]
+impl Check for Unavailable {
+    fn name(&self) -> &str {
+        "unavailable"
+    }
+
+    async fn check(&self, _domain: &str) -> Result<CheckOutcome> {
+        Err(MailGuardError::RetryAfter(std::time::Duration::from_secs(
+            60,
+        )))
+    }
+}
+
+#[tokio::test]
+async fn test_lookup_failures_follow_policy() {
+    let guard = MailGuard::builder()
+        .config(MailGuardConfig {
+            check_order: vec!["unavailable".to_string()],
+            ..Default::default()
+        })
+        .dns_client(DnsClient::playback(Fixture::new()))
+        .with_check(Unavailable)
+        .build()
+        .unwrap();
+
+    let strict = FieldPolicy {
+        accept_on_error: false,
+        ..FieldPolicy::required()
+    };
+    let form = EmailForm::new()
+        .field("billing", strict)
+        .field("abuse", FieldPolicy::optional());
+
+    let report = form
+        .validate(
+            &guard,
+            [
+                ("billing", "ap@example.com"),
+                ("abuse", "abuse@example.com"),
+            ],
+        )
+        .await;
+
+    assert!(matches!(
+        report.fields["billing"].rejection,
+        Some(FieldRejection::CheckFailed(_))
+    ));
+    assert!(report.fields["abuse"].is_accepted());
+}
@@ -0,0 +1,40 @@
+use mailguard_rs::{
+    Fixture, MailGuard, MailGuardConfig, MailGuardError, blocking, check_domain, check_email,
+    dns::DnsClient,
+};
+
+fn config() -> MailGuardConfig {
+    MailGuardConfig {
+        blocklist: ["burner.test".to_string()].into_iter().collect(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_free_functions_share_the_global_detector() {
+    MailGuard::set_global(MailGuard::with_dns_client(
+        config(),
+        DnsClient::playback(Fixture::new()),
+    ))
+    .unwrap();
+
+    assert!(check_domain("burner.test").await.unwrap().is_threat);
+    assert!(check_email("user@burner.test").await.unwrap().is_threat);
+    assert!(std::ptr::eq(MailGuard::global(), MailGuard::global()));
+
+    assert!(matches!(
+        MailGuard::set_global(MailGuard::new()),
+        Err(MailGuardError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn test_blocking_global_detector() {
+    let detector =
+        blocking::MailGuard::with_dns_client(config(), DnsClient::playback(Fixture::new()))
+            .unwrap();
+    blocking::MailGuard::set_global(detector).unwrap();
+
+    assert!(blocking::check_domain("burner.test").unwrap().is_threat);
+    assert!(blocking::MailGuard::set_global(blocking::MailGuard::new().unwrap()).is_err());
+}
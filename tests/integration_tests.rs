@@ -1,6 +1,8 @@
 use std::time::Duration;
 
-use mailguard_rs::{MailGuard, MailGuardConfig, ThreatType, check_domain, check_email};
+use mailguard_rs::{
+    MailGuard, MailGuardConfig, MailGuardError, ThreatType, check_domain, check_email,
+};
 
 #[tokio::test]
 async fn test_valid_email_format() {
@@ -62,6 +64,7 @@ async fn test_cache_functionality() {
         dns_timeout: Duration::from_secs(5),
         enable_cache: true,
         cache_ttl: Duration::from_secs(300),
+        ..Default::default()
     };
 
     let detector = MailGuard::with_config(config);
@@ -129,6 +132,7 @@ async fn test_disabled_cache() {
         dns_timeout: Duration::from_secs(5),
         enable_cache: false,
         cache_ttl: Duration::from_secs(300),
+        ..Default::default()
     };
 
     let detector = MailGuard::with_config(config);
@@ -154,3 +158,12 @@ async fn test_threat_descriptions() {
     );
     assert_eq!(ThreatType::Unknown(42).description(), "Unknown Threat Type");
 }
+
+#[test]
+fn test_retry_after_metadata() {
+    let err = MailGuardError::RetryAfter(Duration::from_secs(30));
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(30)));
+
+    let err = MailGuardError::invalid_domain("bad", "Misplaced '.' in domain");
+    assert_eq!(err.retry_after(), None);
+}
@@ -0,0 +1,230 @@
+use std::{collections::HashSet, net::IpAddr};
+
+use mailguard_rs::{
+    Fixture, FixtureAnswer, MailGuard, MailGuardConfig, MailGuardError, dns::DnsClient,
+    lists::DomainList,
+};
+
+fn domains(entries: &[&str]) -> HashSet<String> {
+    entries.iter().map(|entry| entry.to_string()).collect()
+}
+
+fn playback_detector(config: MailGuardConfig) -> MailGuard {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "ourcompany.com.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    MailGuard::with_dns_client(config, DnsClient::playback(fixture))
+}
+
+#[test]
+fn test_domain_list_matches_parents() {
+    let list = DomainList::new(["Example.com", "blocked.org."]);
+    assert_eq!(list.len(), 2);
+
+    assert!(list.matches("example.com"));
+    assert!(list.matches("mail.example.com"));
+    assert!(list.matches("blocked.org"));
+    assert!(!list.matches("notexample.com"));
+    assert!(!list.matches("com"));
+}
+
+#[test]
+fn test_empty_domain_list() {
+    let list = DomainList::new(Vec::<String>::new());
+    assert!(list.is_empty());
+    assert!(!list.matches("example.com"));
+}
+
+#[tokio::test]
+async fn test_allowlist_overrides_listing() {
+    let detector = playback_detector(MailGuardConfig::default());
+    assert!(
+        detector
+            .check_domain("ourcompany.com")
+            .await
+            .unwrap()
+            .is_threat
+    );
+
+    let detector = playback_detector(MailGuardConfig {
+        allowlist: domains(&["ourcompany.com"]),
+        ..Default::default()
+    });
+    let status = detector.check_email("me@OurCompany.com").await.unwrap();
+    assert!(!status.is_threat);
+    assert_eq!(status.threat_type, None);
+}
+
+#[tokio::test]
+async fn test_blocklist_flags_domain() {
+    let detector = playback_detector(MailGuardConfig {
+        blocklist: domains(&["throwaway.example"]),
+        ..Default::default()
+    });
+
+    let status = detector
+        .check_domain("inbox.throwaway.example")
+        .await
+        .unwrap();
+    assert!(status.is_threat);
+    assert!(!status.from_cache);
+
+    assert!(
+        !detector
+            .check_domain("example.net")
+            .await
+            .unwrap()
+            .is_threat
+    );
+}
+
+#[tokio::test]
+async fn test_allowlist_wins_over_blocklist() {
+    let detector = playback_detector(MailGuardConfig {
+        allowlist: domains(&["example.com"]),
+        blocklist: domains(&["example.com"]),
+        ..Default::default()
+    });
+
+    assert!(
+        !detector
+            .check_domain("example.com")
+            .await
+            .unwrap()
+            .is_threat
+    );
+}
+
+#[test]
+fn test_wildcard_rules() {
+    let list = DomainList::new(["*.temp-mail.*", "mailinator.*"]);
+    assert_eq!(list.len(), 2);
+
+    assert!(list.matches("inbox.temp-mail.org"));
+    assert!(list.matches("a.b.temp-mail.io"));
+    assert!(list.matches("mailinator.net"));
+    assert!(!list.matches("temp-mail.org"));
+    assert!(!list.matches("notmailinator.net"));
+}
+
+#[test]
+fn test_regex_rules() {
+    let list = DomainList::new([r"regex:^tmp[0-9]+\.(com|net)$", "example.org"]);
+
+    assert!(list.matches("tmp42.com"));
+    assert!(list.matches("example.org"));
+    assert!(!list.matches("tmp.com"));
+    assert!(!list.matches("tmp42.org"));
+}
+
+#[test]
+fn test_invalid_regex_rules() {
+    let err = DomainList::try_new(["regex:(unclosed"]).unwrap_err();
+    assert!(matches!(err, MailGuardError::InvalidRule(_)));
+
+    // The lenient constructor skips the bad entry but keeps the rest
+    let list = DomainList::new(["regex:(unclosed", "example.com"]);
+    assert_eq!(list.len(), 1);
+    assert!(list.matches("example.com"));
+}
+
+#[tokio::test]
+async fn test_wildcard_blocklist_in_detector() {
+    let detector = playback_detector(MailGuardConfig {
+        blocklist: domains(&["*.temp-mail.*"]),
+        ..Default::default()
+    });
+
+    assert!(
+        detector
+            .check_domain("x.temp-mail.io")
+            .await
+            .unwrap()
+            .is_threat
+    );
+    assert!(
+        !detector
+            .check_domain("example.com")
+            .await
+            .unwrap()
+            .is_threat
+    );
+}
+
+#[test]
+fn test_domain_list_insert_and_remove() {
+    let mut list = DomainList::new(["example.com"]);
+
+    assert!(list.insert("*.temp-mail.*").unwrap());
+    assert!(!list.insert("*.temp-mail.*").unwrap());
+    assert!(list.insert("Burner.test").unwrap());
+    assert!(list.insert("regex:[").is_err());
+    assert_eq!(list.len(), 3);
+    assert!(list.matches("x.temp-mail.io"));
+    assert!(list.matches("burner.test"));
+
+    assert!(list.remove("*.temp-mail.*"));
+    assert!(!list.remove("*.temp-mail.*"));
+    assert!(list.remove("burner.test"));
+    assert!(!list.matches("x.temp-mail.io"));
+    assert!(!list.matches("burner.test"));
+    assert!(list.matches("example.com"));
+}
+
+#[tokio::test]
+async fn test_runtime_rules() {
+    let detector = playback_detector(MailGuardConfig {
+        email_cache_ttl: Some(std::time::Duration::from_secs(60)),
+        ..Default::default()
+    });
+
+    assert!(
+        !detector
+            .check_email("a@burner.test")
+            .await
+            .unwrap()
+            .is_threat
+    );
+    assert!(detector.add_blocklist_rule("burner.test").unwrap());
+    assert!(
+        detector
+            .check_email("a@burner.test")
+            .await
+            .unwrap()
+            .is_threat
+    );
+    assert!(detector.remove_blocklist_rule("burner.test"));
+    assert!(
+        !detector
+            .check_email("a@burner.test")
+            .await
+            .unwrap()
+            .is_threat
+    );
+
+    assert!(
+        detector
+            .check_domain("ourcompany.com")
+            .await
+            .unwrap()
+            .is_threat
+    );
+    assert!(detector.add_allowlist_rule("*.com").unwrap());
+    assert!(
+        !detector
+            .check_domain("ourcompany.com")
+            .await
+            .unwrap()
+            .is_threat
+    );
+
+    assert!(matches!(
+        detector.add_allowlist_rule("regex:("),
+        Err(MailGuardError::InvalidRule(_))
+    ));
+}
@@ -0,0 +1,208 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use mailguard_rs::{
+    CacheBackend, CachedVerdict, Fixture, FixtureAnswer, MailGuard, MemcachedCache, ThreatType,
+    dns::DnsClient,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+
+/// Items of the fake server: data and expiry field
+type Items = Arc<Mutex<HashMap<String, (Vec<u8>, u64)>>>;
+
+/// A minimal memcached speaking the text protocol, returning its address
+async fn fake_memcached(items: Items) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            let items = items.clone();
+            tokio::spawn(async move {
+                let mut socket = BufReader::new(socket);
+                let mut line = String::new();
+                while socket.read_line(&mut line).await.unwrap_or(0) > 0 {
+                    let words: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+                    line.clear();
+                    let reply = match words.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+                        ["get", key] => match items.lock().unwrap().get(key) {
+                            Some((data, _)) => {
+                                let mut reply =
+                                    format!("VALUE {key} 0 {}\r\n", data.len()).into_bytes();
+                                reply.extend_from_slice(data);
+                                reply.extend_from_slice(b"\r\nEND\r\n");
+                                reply
+                            }
+                            None => b"END\r\n".to_vec(),
+                        },
+                        ["set", key, _, expiry, length] => {
+                            let mut data = vec![0; length.parse::<usize>().unwrap() + 2];
+                            socket.read_exact(&mut data).await.unwrap();
+                            data.truncate(data.len() - 2);
+                            let expiry = expiry.parse().unwrap();
+                            items
+                                .lock()
+                                .unwrap()
+                                .insert(key.to_string(), (data, expiry));
+                            b"STORED\r\n".to_vec()
+                        }
+                        ["delete", key] => match items.lock().unwrap().remove(key) {
+                            Some(_) => b"DELETED\r\n".to_vec(),
+                            None => b"NOT_FOUND\r\n".to_vec(),
+                        },
+                        ["flush_all"] => {
+                            items.lock().unwrap().clear();
+                            b"OK\r\n".to_vec()
+                        }
+                        _ => b"ERROR\r\n".to_vec(),
+                    };
+                    socket.get_mut().write_all(&reply).await.unwrap();
+                }
+            });
+        }
+    });
+    addr
+}
+
+#[tokio::test]
+async fn test_verdicts_round_trip_through_memcached() {
+    let items = Items::default();
+    let addr = fake_memcached(items.clone()).await;
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "listed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    let detector = MailGuard::builder()
+        .dns_client(DnsClient::playback(fixture))
+        .cache_backend(MemcachedCache::connect(&addr).await.unwrap())
+        .build()
+        .unwrap();
+
+    assert!(
+        !detector
+            .check_domain("listed.test")
+            .await
+            .unwrap()
+            .from_cache
+    );
+    let (data, expiry) = items.lock().unwrap()["mailguard:surbl:listed.test"].clone();
+    assert_eq!(expiry, 3600);
+    let stored: serde_json::Value = serde_json::from_slice(&data).unwrap();
+    assert_eq!(stored[0]["kind"], "spam");
+    assert_eq!(stored[0]["raw_ip"], "127.0.0.2");
+
+    // Another instance sharing the server answers from it
+    let other = MailGuard::builder()
+        .dns_client(DnsClient::playback(Fixture::new()))
+        .cache_backend(MemcachedCache::connect(&addr).await.unwrap())
+        .build()
+        .unwrap();
+    let status = other.check_domain("listed.test").await.unwrap();
+    assert!(status.from_cache);
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+
+    other.invalidate("listed.test").await.unwrap();
+    assert!(items.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_prefix_and_flush() {
+    let items = Items::default();
+    let addr = fake_memcached(items.clone()).await;
+    let cache = MemcachedCache::connect(&addr)
+        .await
+        .unwrap()
+        .with_prefix("staging:");
+
+    cache
+        .set(
+            "safe.test",
+            CachedVerdict::pass(),
+            Duration::from_millis(1500),
+        )
+        .await
+        .unwrap();
+    assert_eq!(items.lock().unwrap()["staging:safe.test"].1, 2);
+    assert_eq!(
+        cache.get("safe.test").await.unwrap(),
+        Some(CachedVerdict::pass())
+    );
+    assert_eq!(cache.get("other.test").await.unwrap(), None);
+
+    // Expiries past 30 days are sent as timestamps
+    cache
+        .set(
+            "long.test",
+            CachedVerdict::pass(),
+            Duration::from_secs(60 * 24 * 60 * 60),
+        )
+        .await
+        .unwrap();
+    assert!(items.lock().unwrap()["staging:long.test"].1 > 1_000_000_000);
+
+    cache.clear();
+    for _ in 0..100 {
+        if items.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert!(items.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_error_replies_fail_requests() {
+    let items = Items::default();
+    let addr = fake_memcached(items).await;
+    let cache = MemcachedCache::connect(&addr).await.unwrap();
+
+    // Keys with spaces make the server reply ERROR
+    assert!(cache.get("bad key").await.is_err());
+    // The next request reconnects and works
+    assert_eq!(cache.get("fine.test").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_values_from_earlier_versions_still_read() {
+    let items = Items::default();
+    let addr = fake_memcached(items.clone()).await;
+    items.lock().unwrap().extend([
+        (
+            "mailguard:old-spam.test".to_string(),
+            (br#""spam""#.to_vec(), 0),
+        ),
+        ("mailguard:old-safe.test".to_string(), (b"null".to_vec(), 0)),
+        (
+            "mailguard:old-mixed.test".to_string(),
+            (br#"["malware","spam"]"#.to_vec(), 0),
+        ),
+    ]);
+    let cache = MemcachedCache::connect(&addr).await.unwrap();
+
+    assert_eq!(
+        cache.get("old-spam.test").await.unwrap(),
+        Some(CachedVerdict::listed(ThreatType::Spam, []))
+    );
+    assert_eq!(
+        cache.get("old-safe.test").await.unwrap(),
+        Some(CachedVerdict::pass())
+    );
+    assert_eq!(
+        cache.get("old-mixed.test").await.unwrap(),
+        Some(CachedVerdict::listed(
+            ThreatType::Malware,
+            [ThreatType::Spam]
+        ))
+    );
+}
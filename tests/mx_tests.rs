@@ -0,0 +1,161 @@
+use std::net::IpAddr;
+
+use mailguard_rs::{
+    Fixture, FixtureAnswer, MailGuard, MailGuardConfig, ThreatType, check,
+    dns::DnsClient,
+    threat::{HIGH_CONFIDENCE, MEDIUM_CONFIDENCE},
+};
+
+fn exchanges(hosts: &[&str]) -> FixtureAnswer {
+    FixtureAnswer::Exchanges {
+        hosts: hosts.iter().map(|host| host.to_string()).collect(),
+        ttl_secs: 300,
+    }
+}
+
+fn playback_client() -> DnsClient {
+    let mut fixture = Fixture::new();
+    fixture.insert_mx("fresh-throwaway.test", exchanges(&["mail2.mailinator.com"]));
+    fixture.insert_mx("inhouse-burner.test", exchanges(&["mx.burner.example"]));
+    fixture.insert_mx("example.com", exchanges(&["mx1.example.com"]));
+    fixture.insert(
+        "spam.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    fixture.insert_mx("spam.test", exchanges(&["mail.mailinator.com"]));
+    DnsClient::playback(fixture)
+}
+
+fn detector(config: MailGuardConfig) -> MailGuard {
+    MailGuard::builder()
+        .config(MailGuardConfig {
+            detect_disposable_mx: true,
+            ..config
+        })
+        .dns_client(playback_client())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_disposable_mx_backend_is_flagged() {
+    let detector = detector(MailGuardConfig::default());
+
+    let status = detector
+        .check_email("user@fresh-throwaway.test")
+        .await
+        .unwrap();
+    assert!(status.is_threat);
+    assert_eq!(status.threat_type, Some(ThreatType::Disposable));
+    assert_eq!(
+        status.check_results.last().unwrap().check,
+        check::MX_FINGERPRINT
+    );
+
+    assert!(
+        !detector
+            .check_domain("example.com")
+            .await
+            .unwrap()
+            .is_threat
+    );
+    assert!(!detector.check_domain("no-mx.test").await.unwrap().is_threat);
+}
+
+#[tokio::test]
+async fn test_surbl_listing_decides_first() {
+    let detector = detector(MailGuardConfig::default());
+
+    let status = detector.check_domain("spam.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+    assert_eq!(status.check_results.last().unwrap().check, check::SURBL);
+}
+
+#[tokio::test]
+async fn test_configured_mx_hosts() {
+    let detector = detector(MailGuardConfig {
+        disposable_mx_hosts: ["burner.example".to_string()].into_iter().collect(),
+        ..Default::default()
+    });
+
+    let status = detector.check_domain("inhouse-burner.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Disposable));
+}
+
+#[test]
+fn test_mx_fingerprinting_closes_coverage_gap() {
+    let report = detector(MailGuardConfig::default()).coverage();
+
+    assert_eq!(report.checks.last().unwrap().check, check::MX_FINGERPRINT);
+    assert!(report.missing_categories.is_empty());
+}
+
+#[tokio::test]
+async fn test_every_category_is_reported() {
+    let detector = detector(MailGuardConfig {
+        short_circuit: false,
+        ..Default::default()
+    });
+
+    let status = detector.check_email("user@spam.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+    let reported: Vec<(&ThreatType, &str)> = status
+        .threats
+        .iter()
+        .map(|threat| (&threat.kind, threat.source_list.as_str()))
+        .collect();
+    assert_eq!(
+        reported,
+        [
+            (&ThreatType::Spam, check::SURBL),
+            (&ThreatType::Disposable, check::MX_FINGERPRINT)
+        ]
+    );
+    assert_eq!(status.most_severe_threat(), Some(&ThreatType::Spam));
+
+    let status = detector.check_domain("example.com").await.unwrap();
+    assert!(status.threats.is_empty());
+    assert_eq!(status.most_severe_threat(), None);
+}
+
+#[tokio::test]
+async fn test_listings_are_more_confident_than_fingerprints() {
+    let detector = detector(MailGuardConfig {
+        short_circuit: false,
+        ..Default::default()
+    });
+
+    let status = detector.check_domain("fresh-throwaway.test").await.unwrap();
+    assert_eq!(status.confidence, MEDIUM_CONFIDENCE);
+    assert_eq!(status.threats[0].confidence, MEDIUM_CONFIDENCE);
+
+    let status = detector.check_domain("spam.test").await.unwrap();
+    assert_eq!(status.confidence, HIGH_CONFIDENCE);
+    let surbl = status
+        .threats
+        .iter()
+        .find(|threat| threat.source_list == check::SURBL)
+        .unwrap();
+    assert_eq!(surbl.confidence, HIGH_CONFIDENCE);
+
+    let status = detector.check_domain("example.com").await.unwrap();
+    assert_eq!(status.confidence, 0.0);
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_cached_listings_keep_their_confidence() {
+    let detector = detector(MailGuardConfig::default());
+
+    let first = detector.check_domain("spam.test").await.unwrap();
+    let second = detector.check_domain("spam.test").await.unwrap();
+    assert!(!first.from_cache);
+    assert!(second.from_cache);
+    assert!(second.is_threat);
+    assert_eq!(first.confidence, HIGH_CONFIDENCE);
+    assert_eq!(second.confidence, first.confidence);
+    assert_eq!(second.threats[0].confidence, second.confidence);
+}
@@ -0,0 +1,86 @@
+use mailguard_rs::{
+    Fixture, MailGuard, MailGuardConfig, dns::DnsClient, is_same_mailbox, normalize_email,
+    parse_subaddress,
+};
+
+#[test]
+fn test_normalize_lowercases() {
+    assert_eq!(normalize_email("John@Example.COM"), "john@example.com");
+}
+
+#[test]
+fn test_normalize_gmail_dots_and_tags() {
+    assert_eq!(
+        normalize_email("John.Doe+promo@GoogleMail.com"),
+        "johndoe@gmail.com"
+    );
+    assert_eq!(normalize_email("j.o.h.n@gmail.com"), "john@gmail.com");
+}
+
+#[test]
+fn test_normalize_provider_tag_separators() {
+    assert_eq!(normalize_email("user+news@outlook.com"), "user@outlook.com");
+    assert_eq!(normalize_email("user-shop@yahoo.com"), "user@yahoo.com");
+    assert_eq!(normalize_email("user+x@pm.me"), "user@proton.me");
+    // Dots are significant outside Gmail
+    assert_eq!(
+        normalize_email("first.last@outlook.com"),
+        "first.last@outlook.com"
+    );
+}
+
+#[test]
+fn test_normalize_generic_domain() {
+    assert_eq!(normalize_email("user+tag@example.com"), "user@example.com");
+    assert_eq!(normalize_email("+user@example.com"), "+user@example.com");
+    assert_eq!(normalize_email("not-an-email"), "not-an-email");
+}
+
+#[tokio::test]
+async fn test_email_status_normalized_email() {
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(Fixture::new()),
+    );
+
+    let status = detector
+        .check_email("Some.User+signup@googlemail.com")
+        .await
+        .unwrap();
+    assert_eq!(status.normalized_email, "someuser@gmail.com");
+
+    let subaddress = status.subaddress.unwrap();
+    assert_eq!(subaddress.base_address, "Some.User@googlemail.com");
+    assert_eq!(subaddress.tag, "signup");
+}
+
+#[test]
+fn test_parse_subaddress() {
+    let subaddress = parse_subaddress("User+Promo@example.com").unwrap();
+    assert_eq!(subaddress.base_address, "User@example.com");
+    assert_eq!(subaddress.tag, "Promo");
+
+    let subaddress = parse_subaddress("user-shop@yahoo.com").unwrap();
+    assert_eq!(subaddress.base_address, "user@yahoo.com");
+    assert_eq!(subaddress.tag, "shop");
+
+    assert_eq!(parse_subaddress("user@example.com"), None);
+    assert_eq!(parse_subaddress("first-last@example.com"), None);
+    assert_eq!(parse_subaddress("+user@example.com"), None);
+}
+
+#[test]
+fn test_is_same_mailbox() {
+    assert!(is_same_mailbox(
+        "john.doe@gmail.com",
+        "JohnDoe+spam@googlemail.com"
+    ));
+    assert!(is_same_mailbox("user+a@proton.me", "user@protonmail.com"));
+    assert!(is_same_mailbox("user-1@yahoo.com", "user@yahoo.com"));
+
+    assert!(!is_same_mailbox(
+        "john.doe@outlook.com",
+        "johndoe@outlook.com"
+    ));
+    assert!(!is_same_mailbox("user@gmail.com", "user@outlook.com"));
+}
@@ -0,0 +1,103 @@
+use mailguard_rs::{
+    CheckOutcome, Fixture, MailGuard, MailGuardConfig, MailGuardError, OfflineMailGuard,
+    ThreatType, check, dns::DnsClient, offline,
+};
+
+fn lists_config() -> MailGuardConfig {
+    MailGuardConfig {
+        allowlist: ["trusted.tempmail.test".to_string()].into_iter().collect(),
+        blocklist: ["blocked.test".to_string()].into_iter().collect(),
+        detect_typos: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_offline_lists_and_verdicts() {
+    let mut guard = OfflineMailGuard::with_config(&lists_config()).unwrap();
+    guard.import_verdicts([
+        ("TempMail.test", Some(ThreatType::Spam)),
+        ("clean.test", None),
+    ]);
+    assert_eq!(guard.len(), 2);
+
+    let status = guard.check_email("user@mail.blocked.test").unwrap();
+    assert!(status.is_threat);
+    assert_eq!(status.threat_type, None);
+
+    let status = guard.check_domain("tempmail.test").unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+    assert!(status.from_cache);
+
+    // Lists win over imported verdicts
+    assert!(
+        !guard
+            .check_domain("trusted.tempmail.test")
+            .unwrap()
+            .is_threat
+    );
+
+    let status = guard.check_domain("clean.test").unwrap();
+    assert!(!status.is_threat);
+    assert_eq!(
+        status.check_results.last().unwrap().outcome,
+        CheckOutcome::Safe
+    );
+
+    // Unknown domains pass every check
+    let status = guard.check_domain("gmial.com").unwrap();
+    assert!(!status.is_threat);
+    assert_eq!(status.typo_of.as_deref(), Some("gmail.com"));
+    let checks: Vec<&str> = status
+        .check_results
+        .iter()
+        .map(|result| result.check.as_str())
+        .collect();
+    assert_eq!(
+        checks,
+        [check::ALLOWLIST, check::BLOCKLIST, offline::IMPORTED]
+    );
+    assert!(
+        status
+            .check_results
+            .iter()
+            .all(|result| result.outcome == CheckOutcome::Pass)
+    );
+}
+
+#[test]
+fn test_offline_rejects_invalid_input() {
+    let guard = OfflineMailGuard::new();
+    assert!(matches!(
+        guard.check_email("not-an-email"),
+        Err(MailGuardError::InvalidEmail { .. })
+    ));
+    assert!(matches!(
+        guard.check_domain("bad..domain"),
+        Err(MailGuardError::InvalidDomain { .. })
+    ));
+
+    let config = MailGuardConfig {
+        blocklist: ["regex:(".to_string()].into_iter().collect(),
+        ..Default::default()
+    };
+    assert!(OfflineMailGuard::with_config(&config).is_err());
+}
+
+#[tokio::test]
+async fn test_import_online_statuses() {
+    let online = MailGuard::with_dns_client(lists_config(), DnsClient::playback(Fixture::new()));
+    let statuses: Vec<_> = online
+        .check_domains_batch(&["example.com", "blocked.test"])
+        .await
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let mut guard = OfflineMailGuard::new();
+    guard.import_statuses(&statuses);
+
+    // The untyped blocklist hit is not imported
+    assert_eq!(guard.len(), 1);
+    assert!(guard.check_domain("example.com").unwrap().from_cache);
+}
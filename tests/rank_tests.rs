@@ -0,0 +1,88 @@
+use std::{net::IpAddr, sync::Arc};
+
+use mailguard_rs::{
+    CheckOutcome, DomainRanks, Fixture, FixtureAnswer, MailGuard, MailGuardConfig, MailGuardError,
+    check, dns::DnsClient,
+};
+
+fn ranks() -> DomainRanks {
+    DomainRanks::parse("1,google.com\n2,example.com\n\n50000,Listed.test\n").unwrap()
+}
+
+fn detector(trusted_rank: Option<u32>) -> MailGuard {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "listed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+
+    MailGuard::builder()
+        .config(MailGuardConfig {
+            domain_ranks: Some(Arc::new(ranks())),
+            trusted_rank,
+            ..Default::default()
+        })
+        .dns_client(DnsClient::playback(fixture))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_parse_and_rank() {
+    let mut ranks = ranks();
+    assert_eq!(ranks.len(), 3);
+    assert_eq!(ranks.rank("example.com"), Some(2));
+    assert_eq!(ranks.rank("mail.google.com"), Some(1));
+    assert_eq!(ranks.rank("LISTED.TEST"), Some(50000));
+    assert_eq!(ranks.rank("unranked.test"), None);
+
+    ranks.insert("example.com", 10);
+    assert_eq!(ranks.rank("example.com"), Some(2));
+
+    assert!(matches!(
+        DomainRanks::parse("1,google.com\nnot-a-rank,example.com"),
+        Err(MailGuardError::InvalidRule(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_status_reports_rank() {
+    let detector = detector(None);
+
+    let status = detector.check_email("user@mail.google.com").await.unwrap();
+    assert_eq!(status.rank, Some(1));
+
+    let status = detector.check_domain("unranked.test").await.unwrap();
+    assert_eq!(status.rank, None);
+
+    // Without trusted_rank, ranks are only reported
+    let status = detector.check_domain("listed.test").await.unwrap();
+    assert!(status.is_threat);
+    assert_eq!(status.rank, Some(50000));
+}
+
+#[tokio::test]
+async fn test_trusted_rank_is_safe() {
+    let detector = detector(Some(100_000));
+
+    let status = detector.check_domain("listed.test").await.unwrap();
+    assert!(!status.is_threat);
+    let last = status.check_results.last().unwrap();
+    assert_eq!(last.check, check::RANK);
+    assert_eq!(last.outcome, CheckOutcome::Safe);
+}
+
+#[test]
+fn test_trusted_rank_needs_ranks() {
+    let config = MailGuardConfig {
+        trusted_rank: Some(1000),
+        ..Default::default()
+    };
+    assert!(matches!(
+        config.validate(),
+        Err(MailGuardError::InvalidConfig(_))
+    ));
+}
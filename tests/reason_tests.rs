@@ -0,0 +1,105 @@
+use std::net::IpAddr;
+
+use mailguard_rs::{
+    Fixture, FixtureAnswer, MailGuard, MailGuardConfig, OfflineMailGuard, Reason, ThreatType,
+    dns::DnsClient,
+};
+
+fn detector(config: MailGuardConfig) -> MailGuard {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "spam.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    MailGuard::builder()
+        .config(config)
+        .dns_client(DnsClient::playback(fixture))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_reasons_name_the_evidence() {
+    let detector = detector(MailGuardConfig {
+        allowlist: ["partner.test".to_string()].into_iter().collect(),
+        blocklist: ["burner.test".to_string()].into_iter().collect(),
+        detect_typos: true,
+        ..Default::default()
+    });
+
+    let status = detector.check_email("user@spam.test").await.unwrap();
+    assert_eq!(
+        status.reasons,
+        [Reason::ListedOn {
+            provider: "tempmail.so.multi.surbl.org".to_string(),
+            category: Some(ThreatType::Spam),
+        }]
+    );
+
+    let status = detector.check_domain("burner.test").await.unwrap();
+    assert_eq!(status.reasons, [Reason::Blocklisted]);
+
+    let status = detector.check_domain("partner.test").await.unwrap();
+    assert_eq!(status.reasons, [Reason::Allowlisted]);
+
+    let status = detector.check_domain("gmial.com").await.unwrap();
+    assert_eq!(
+        status.reasons,
+        [Reason::LikelyTypo {
+            provider: "gmail.com".to_string()
+        }]
+    );
+
+    assert!(
+        detector
+            .check_domain("example.com")
+            .await
+            .unwrap()
+            .reasons
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_reasons_explain_listings_that_dont_block() {
+    let detector = detector(MailGuardConfig {
+        min_blocking_severity: 3,
+        ..Default::default()
+    });
+
+    let status = detector.check_domain("spam.test").await.unwrap();
+    assert!(!status.is_threat);
+    assert_eq!(
+        status.reasons.last(),
+        Some(&Reason::BelowSeverity {
+            category: ThreatType::Spam,
+            min_blocking_severity: 3,
+        })
+    );
+}
+
+#[test]
+fn test_imported_verdicts_are_stored_reasons() {
+    let mut guard = OfflineMailGuard::new();
+    guard.import_verdicts([("tempmail.test", Some(ThreatType::Phishing))]);
+
+    let status = guard.check_domain("tempmail.test").unwrap();
+    assert_eq!(
+        status.reasons,
+        [Reason::StoredVerdict {
+            category: Some(ThreatType::Phishing)
+        }]
+    );
+}
+
+#[test]
+fn test_reason_serialization() {
+    let json = serde_json::to_string(&Reason::LikelyTypo {
+        provider: "gmail.com".to_string(),
+    })
+    .unwrap();
+    assert_eq!(json, r#"{"kind":"likely_typo","provider":"gmail.com"}"#);
+}
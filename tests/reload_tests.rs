@@ -0,0 +1,165 @@
+use std::{collections::HashSet, net::IpAddr, sync::Arc, time::Duration};
+
+use mailguard_rs::{
+    Check, CheckOutcome, Fixture, FixtureAnswer, MailGuard, MailGuardConfig, MailGuardError,
+    check::async_trait, dns::DnsClient, error::Result,
+};
+use tokio::sync::Semaphore;
+
+/// Holds every check until the test releases it
+struct Gate {
+    permits: Arc<Semaphore>,
+}
+
+#[async_trait]
+impl Check for Gate {
+    fn name(&self) -> &str {
+        "gate"
+    }
+
+    async fn check(&self, _domain: &str) -> Result<CheckOutcome> {
+        self.permits.acquire().await.unwrap().forget();
+        Ok(CheckOutcome::Pass)
+    }
+}
+
+fn domains(entries: &[&str]) -> HashSet<String> {
+    entries.iter().map(|entry| entry.to_string()).collect()
+}
+
+fn playback_client() -> DnsClient {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "listed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    DnsClient::playback(fixture)
+}
+
+#[tokio::test]
+async fn test_reload_swaps_lists() {
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig {
+            blocklist: domains(&["old.test"]),
+            ..Default::default()
+        },
+        playback_client(),
+    );
+    assert!(detector.check_domain("old.test").await.unwrap().is_threat);
+
+    detector
+        .reload(MailGuardConfig {
+            blocklist: domains(&["new.test"]),
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert!(!detector.check_domain("old.test").await.unwrap().is_threat);
+    assert!(detector.check_domain("new.test").await.unwrap().is_threat);
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_reload_keeps_domain_cache() {
+    let detector = MailGuard::with_dns_client(MailGuardConfig::default(), playback_client());
+    assert!(
+        detector
+            .check_domain("listed.test")
+            .await
+            .unwrap()
+            .is_threat
+    );
+
+    detector
+        .reload(MailGuardConfig {
+            blocklist: domains(&["new.test"]),
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(
+        detector
+            .check_domain("listed.test")
+            .await
+            .unwrap()
+            .from_cache
+    );
+
+    detector
+        .reload(MailGuardConfig {
+            cache_ttl: Duration::from_secs(60),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(detector.cache_stats(), Some(0));
+}
+
+#[tokio::test]
+async fn test_invalid_reload_keeps_current_config() {
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig {
+            blocklist: domains(&["old.test"]),
+            ..Default::default()
+        },
+        playback_client(),
+    );
+
+    let invalid = MailGuardConfig {
+        blocklist: domains(&["regex:("]),
+        ..Default::default()
+    };
+    assert!(matches!(
+        detector.reload(invalid),
+        Err(MailGuardError::InvalidRule(_))
+    ));
+    let unknown_check = MailGuardConfig {
+        check_order: vec!["missing".to_string()],
+        ..Default::default()
+    };
+    assert!(matches!(
+        detector.reload(unknown_check),
+        Err(MailGuardError::InvalidConfig(_))
+    ));
+
+    assert!(detector.check_domain("old.test").await.unwrap().is_threat);
+}
+
+#[tokio::test]
+async fn test_in_flight_checks_finish_under_old_config() {
+    let permits = Arc::new(Semaphore::new(0));
+    let detector = Arc::new(
+        MailGuard::builder()
+            .config(MailGuardConfig {
+                enable_cache: false,
+                check_order: vec!["gate".to_string()],
+                ..Default::default()
+            })
+            .dns_client(playback_client())
+            .with_check(Gate {
+                permits: permits.clone(),
+            })
+            .build()
+            .unwrap(),
+    );
+
+    let in_flight = tokio::spawn({
+        let detector = detector.clone();
+        async move { detector.check_domain("fresh.test").await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    detector
+        .reload(MailGuardConfig {
+            enable_cache: false,
+            check_order: vec!["gate".to_string()],
+            blocklist: domains(&["fresh.test"]),
+            ..Default::default()
+        })
+        .unwrap();
+    permits.add_permits(2);
+
+    assert!(!in_flight.await.unwrap().unwrap().is_threat);
+    assert!(detector.check_domain("fresh.test").await.unwrap().is_threat);
+}
@@ -0,0 +1,178 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use mailguard_rs::{
+    Check, CheckOutcome, Fixture, MailGuard, MailGuardConfig, MailGuardError, RetryKind,
+    RetryPolicy, ThreatType, check::async_trait, dns::DnsClient, error::Result,
+};
+
+/// Fails its first `failures` calls with `error`, then flags the domain
+struct Flaky {
+    failures: u32,
+    error: MailGuardError,
+    calls: Arc<AtomicU32>,
+}
+
+#[async_trait]
+impl Check for Flaky {
+    fn name(&self) -> &str {
+        "flaky"
+    }
+
+    async fn check(&self, _domain: &str) -> Result<CheckOutcome> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+            return Err(self.error.clone());
+        }
+        Ok(CheckOutcome::Threat(Some(ThreatType::Spam)))
+    }
+}
+
+fn policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 3,
+        retry_on: vec![RetryKind::Dns],
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(5),
+    }
+}
+
+fn flaky_detector(policy: Option<RetryPolicy>, flaky: Flaky) -> MailGuard {
+    MailGuard::builder()
+        .config(MailGuardConfig {
+            enable_cache: false,
+            retry_policy: policy,
+            ..Default::default()
+        })
+        .dns_client(DnsClient::playback(Fixture::new()))
+        .with_check(flaky)
+        .build()
+        .unwrap()
+}
+
+fn flaky(failures: u32, error: MailGuardError, calls: &Arc<AtomicU32>) -> Flaky {
+    Flaky {
+        failures,
+        error,
+        calls: calls.clone(),
+    }
+}
+
+fn dns_error() -> MailGuardError {
+    MailGuardError::DnsError("connection refused".into())
+}
+
+#[tokio::test]
+async fn test_transient_failures_are_retried() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let detector = flaky_detector(Some(policy()), flaky(2, dns_error(), &calls));
+
+    let status = detector.check_email("user@example.com").await.unwrap();
+    assert!(status.is_threat);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_retries_give_up() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let detector = flaky_detector(Some(policy()), flaky(5, dns_error(), &calls));
+    assert!(matches!(
+        detector
+            .check_email("user@example.com")
+            .await
+            .unwrap_err()
+            .root_cause(),
+        MailGuardError::DnsError(_)
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    // Kinds the policy doesn't name fail right away
+    let calls = Arc::new(AtomicU32::new(0));
+    let throttled = MailGuardError::RetryAfter(Duration::from_millis(1));
+    let detector = flaky_detector(Some(policy()), flaky(1, throttled, &calls));
+    assert!(detector.check_domain("example.com").await.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Without a policy nothing is retried
+    let calls = Arc::new(AtomicU32::new(0));
+    let detector = flaky_detector(None, flaky(1, dns_error(), &calls));
+    assert!(detector.check_domain("example.com").await.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_backoff() {
+    let policy = RetryPolicy {
+        retry_on: vec![RetryKind::Dns, RetryKind::Throttled],
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(300),
+        max_attempts: 4,
+    };
+
+    assert_eq!(
+        policy.backoff(&dns_error(), 1),
+        Some(Duration::from_millis(100))
+    );
+    assert_eq!(
+        policy.backoff(&dns_error(), 2),
+        Some(Duration::from_millis(200))
+    );
+    assert_eq!(
+        policy.backoff(&dns_error(), 3),
+        Some(Duration::from_millis(300))
+    );
+    assert_eq!(policy.backoff(&dns_error(), 4), None);
+
+    let throttled = MailGuardError::RetryAfter(Duration::from_millis(250));
+    assert_eq!(
+        policy.backoff(&throttled, 1),
+        Some(Duration::from_millis(250))
+    );
+    let throttled = MailGuardError::RetryAfter(Duration::from_secs(60));
+    assert_eq!(policy.backoff(&throttled, 1), None);
+
+    assert_eq!(
+        policy.backoff(&MailGuardError::invalid_domain("x", "rejected"), 1),
+        None
+    );
+
+    let invalid = MailGuardConfig {
+        retry_policy: Some(RetryPolicy {
+            max_attempts: 0,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    assert!(matches!(
+        invalid.validate(),
+        Err(MailGuardError::InvalidConfig(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_timeouts_are_retried_as_dns_failures() {
+    let timeout = MailGuardError::Timeout {
+        operation: "A query for example.com".to_string(),
+        elapsed: Duration::from_secs(5),
+    };
+    assert_eq!(RetryKind::of(&timeout), Some(RetryKind::Dns));
+    assert_eq!(
+        timeout.to_string(),
+        "A query for example.com timed out after 5s"
+    );
+
+    let calls = Arc::new(AtomicU32::new(0));
+    let detector = flaky_detector(Some(policy()), flaky(2, timeout, &calls));
+    assert!(
+        detector
+            .check_domain("example.com")
+            .await
+            .unwrap()
+            .is_threat
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
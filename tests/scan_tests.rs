@@ -0,0 +1,43 @@
+use mailguard_rs::{MailGuard, MailGuardConfig, extract_emails};
+
+#[test]
+fn test_extract_emails_from_text() {
+    let text = "From: Jane <jane.doe+billing@example.com>\nCC ops@sub.example.org. Ping me at me@localhost or at @handle.";
+    let found = extract_emails(text);
+
+    let emails: Vec<&str> = found.iter().map(|span| span.email.as_str()).collect();
+    assert_eq!(
+        emails,
+        ["jane.doe+billing@example.com", "ops@sub.example.org"]
+    );
+    for span in &found {
+        assert_eq!(&text[span.start..span.end], span.email);
+    }
+}
+
+#[test]
+fn test_extract_emails_handles_unicode_text() {
+    let text = "联系我们：support@example.com，谢谢";
+    let found = extract_emails(text);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(&text[found[0].start..found[0].end], "support@example.com");
+}
+
+#[tokio::test]
+async fn test_scan_text_checks_each_address() {
+    let detector = MailGuard::with_config(MailGuardConfig {
+        blocklist: ["burner.test".to_string()].into_iter().collect(),
+        allowlist: ["example.com".to_string()].into_iter().collect(),
+        ..Default::default()
+    });
+
+    let text = "signup from spam@burner.test, referred by alice@example.com";
+    let matches = detector.scan_text(text).await;
+
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].span.email, "spam@burner.test");
+    assert!(matches[0].result.as_ref().unwrap().is_threat);
+    assert_eq!(matches[1].span.start, text.find("alice").unwrap());
+    assert!(!matches[1].result.as_ref().unwrap().is_threat);
+}
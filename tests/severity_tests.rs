@@ -0,0 +1,156 @@
+use std::net::IpAddr;
+
+use mailguard_rs::{
+    Fixture, FixtureAnswer, MailGuard, MailGuardConfig, MailGuardError, OfflineMailGuard,
+    ThreatType, dns::DnsClient,
+};
+
+fn listed_fixture() -> Fixture {
+    let mut fixture = Fixture::new();
+    for (name, octet) in [("spam", 2), ("pup", 10), ("phish", 3), ("malware", 4)] {
+        fixture.insert(
+            format!("{name}.test.tempmail.so.multi.surbl.org"),
+            FixtureAnswer::Records {
+                ips: vec![IpAddr::from([127, 0, 0, octet])],
+                ttl_secs: 300,
+            },
+        );
+    }
+    fixture
+}
+
+#[tokio::test]
+async fn test_default_blocks_every_listing() {
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(listed_fixture()),
+    );
+
+    for domain in ["spam.test", "pup.test", "phish.test", "malware.test"] {
+        assert!(detector.check_domain(domain).await.unwrap().is_threat);
+    }
+}
+
+#[tokio::test]
+async fn test_low_severity_listings_do_not_block() {
+    let config = MailGuardConfig {
+        min_blocking_severity: 3,
+        blocklist: ["blocked.test".to_string()].into_iter().collect(),
+        ..Default::default()
+    };
+    let detector = MailGuard::with_dns_client(config, DnsClient::playback(listed_fixture()));
+
+    let status = detector.check_domain("spam.test").await.unwrap();
+    assert!(!status.is_threat);
+    // The listing is still reported
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+
+    assert!(!detector.check_domain("pup.test").await.unwrap().is_threat);
+    assert!(detector.check_domain("phish.test").await.unwrap().is_threat);
+    assert!(
+        detector
+            .check_email("a@malware.test")
+            .await
+            .unwrap()
+            .is_threat
+    );
+
+    // Untyped blocklist hits always block
+    assert!(
+        detector
+            .check_domain("blocked.test")
+            .await
+            .unwrap()
+            .is_threat
+    );
+}
+
+#[test]
+fn test_offline_uses_threshold() {
+    let config = MailGuardConfig {
+        min_blocking_severity: 3,
+        ..Default::default()
+    };
+    let mut guard = OfflineMailGuard::with_config(&config).unwrap();
+    guard.import_verdicts([
+        ("spam.test", Some(ThreatType::Spam)),
+        ("phish.test", Some(ThreatType::Phishing)),
+    ]);
+
+    assert!(!guard.check_domain("spam.test").unwrap().is_threat);
+    assert!(guard.check_domain("phish.test").unwrap().is_threat);
+}
+
+#[test]
+fn test_threshold_above_max_is_invalid() {
+    let config = MailGuardConfig {
+        min_blocking_severity: 6,
+        ..Default::default()
+    };
+    assert!(matches!(
+        config.validate(),
+        Err(MailGuardError::InvalidConfig(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_severity_overrides_decide_blocking() {
+    let config = MailGuardConfig {
+        min_blocking_severity: 3,
+        severity_overrides: [(ThreatType::Spam, 4), (ThreatType::Phishing, 1)]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    assert_eq!(config.severity(&ThreatType::Spam), 4);
+    assert_eq!(config.severity(&ThreatType::Malware), 5);
+
+    let mut guard = OfflineMailGuard::with_config(&config).unwrap();
+    guard.import_verdicts([("spam.test", Some(ThreatType::Spam))]);
+    assert!(guard.check_domain("spam.test").unwrap().is_threat);
+
+    let detector = MailGuard::with_dns_client(config, DnsClient::playback(listed_fixture()));
+    assert!(detector.check_domain("spam.test").await.unwrap().is_threat);
+    assert!(!detector.check_domain("phish.test").await.unwrap().is_threat);
+}
+
+#[test]
+fn test_severity_override_out_of_range_is_invalid() {
+    for level in [0, 6] {
+        let config = MailGuardConfig {
+            severity_overrides: [(ThreatType::Spam, level)].into_iter().collect(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(MailGuardError::InvalidConfig(_))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_overrides_pick_the_deciding_category() {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "mixed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2]), IpAddr::from([127, 0, 0, 10])],
+            ttl_secs: 300,
+        },
+    );
+    let config = MailGuardConfig {
+        min_blocking_severity: 3,
+        severity_overrides: [(ThreatType::Pup, 5), (ThreatType::Spam, 1)]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    let detector = MailGuard::with_dns_client(config, DnsClient::playback(fixture));
+
+    // Built in, spam outranks PUP; the overrides turn that around
+    let status = detector.check_domain("mixed.test").await.unwrap();
+    assert_eq!(status.threat_type, Some(ThreatType::Pup));
+    assert!(status.is_threat);
+    assert_eq!(status.threats[0].kind, ThreatType::Pup);
+    assert_eq!(status.threats[1].kind, ThreatType::Spam);
+}
@@ -0,0 +1,82 @@
+use std::net::IpAddr;
+
+use mailguard_rs::{
+    Confusion, Fixture, FixtureAnswer, MailGuard, MailGuardConfig, check, dns::DnsClient, simulate,
+};
+
+fn candidate() -> MailGuard {
+    let mut fixture = Fixture::new();
+    for domain in ["listed.test", "partner.test"] {
+        fixture.insert(
+            format!("{domain}.tempmail.so.multi.surbl.org"),
+            FixtureAnswer::Records {
+                ips: vec![IpAddr::from([127, 0, 0, 2])],
+                ttl_secs: 300,
+            },
+        );
+    }
+
+    MailGuard::builder()
+        .config(MailGuardConfig {
+            enable_cache: false,
+            short_circuit: false,
+            blocklist: ["burner.test".to_string()].into_iter().collect(),
+            ..Default::default()
+        })
+        .dns_client(DnsClient::playback(fixture))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_confusion_rates() {
+    let empty = Confusion::default();
+    assert_eq!(empty.precision(), 1.0);
+    assert_eq!(empty.recall(), 1.0);
+    assert_eq!(empty.false_positive_rate(), 0.0);
+
+    let mut confusion = Confusion::default();
+    confusion.record(true, true);
+    confusion.record(true, false);
+    confusion.record(false, false);
+    confusion.record(false, false);
+    confusion.record(false, true);
+
+    assert_eq!(confusion.total(), 5);
+    assert_eq!(confusion.precision(), 0.5);
+    assert_eq!(confusion.recall(), 0.5);
+    assert!((confusion.false_positive_rate() - 1.0 / 3.0).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn test_simulation_per_check() {
+    let report = simulate(
+        &candidate(),
+        [
+            ("fraud@burner.test", true),
+            ("spam@listed.test", true),
+            ("missed@fresh.test", true),
+            ("buyer@partner.test", false),
+            ("alice@example.com", false),
+            ("not an address", false),
+        ],
+    )
+    .await;
+
+    assert_eq!(report.overall.true_positives, 2);
+    assert_eq!(report.overall.false_negatives, 1);
+    assert_eq!(report.overall.false_positives, 1);
+    assert_eq!(report.overall.true_negatives, 1);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].0, "not an address");
+
+    let blocklist = &report.checks[check::BLOCKLIST];
+    assert_eq!(blocklist.total(), 5);
+    assert_eq!(blocklist.precision(), 1.0);
+    assert!((blocklist.recall() - 1.0 / 3.0).abs() < f64::EPSILON);
+
+    let surbl = &report.checks[check::SURBL];
+    assert_eq!(surbl.true_positives, 1);
+    assert_eq!(surbl.false_positives, 1);
+    assert_eq!(surbl.false_positive_rate(), 0.5);
+}
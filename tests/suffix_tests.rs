@@ -0,0 +1,95 @@
+use std::net::IpAddr;
+
+use mailguard_rs::{
+    Fixture, FixtureAnswer, MailGuard, MailGuardConfig, ThreatType,
+    dns::DnsClient,
+    suffix::{is_public_suffix, lookup_candidates, registrable_domain},
+};
+
+fn detector(parent_lookup_depth: usize) -> MailGuard {
+    let mut fixture = Fixture::new();
+    for domain in ["evil.com", "evil.co.uk", "co.uk"] {
+        fixture.insert(
+            format!("{domain}.tempmail.so.multi.surbl.org"),
+            FixtureAnswer::Records {
+                ips: vec![IpAddr::from([127, 0, 0, 2])],
+                ttl_secs: 300,
+            },
+        );
+    }
+    MailGuard::builder()
+        .config(MailGuardConfig {
+            parent_lookup_depth,
+            ..Default::default()
+        })
+        .dns_client(DnsClient::playback(fixture))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_registrable_domains() {
+    assert!(is_public_suffix("com"));
+    assert!(is_public_suffix("CO.UK"));
+    assert!(!is_public_suffix("evil.co.uk"));
+
+    assert_eq!(registrable_domain("a.b.c.evil.com"), Some("evil.com"));
+    assert_eq!(registrable_domain("evil.com"), Some("evil.com"));
+    assert_eq!(registrable_domain("mail.evil.co.uk"), Some("evil.co.uk"));
+    assert_eq!(registrable_domain("co.uk"), None);
+    assert_eq!(registrable_domain("com"), None);
+
+    // Suffixes outside the built-in subset are registered below their TLD
+    assert!(!is_public_suffix("co.at"));
+    assert_eq!(registrable_domain("mail.evil.co.at"), Some("co.at"));
+}
+
+#[test]
+fn test_lookup_candidates_respect_depth_and_suffixes() {
+    assert_eq!(lookup_candidates("a.b.c.evil.com", 0), ["a.b.c.evil.com"]);
+    assert_eq!(
+        lookup_candidates("a.b.c.evil.com", 2),
+        ["a.b.c.evil.com", "b.c.evil.com", "c.evil.com"]
+    );
+    assert_eq!(
+        lookup_candidates("mail.evil.co.uk", 10),
+        ["mail.evil.co.uk", "evil.co.uk"]
+    );
+    assert_eq!(lookup_candidates("evil.com", 10), ["evil.com"]);
+}
+
+#[tokio::test]
+async fn test_parent_lookups_find_listed_registrable_domain() {
+    assert!(
+        !detector(0)
+            .check_email("user@a.b.c.evil.com")
+            .await
+            .unwrap()
+            .is_threat
+    );
+
+    let status = detector(3)
+        .check_email("user@a.b.c.evil.com")
+        .await
+        .unwrap();
+    assert!(status.is_threat);
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+
+    // Too shallow to reach evil.com
+    assert!(
+        !detector(2)
+            .check_domain("a.b.c.evil.com")
+            .await
+            .unwrap()
+            .is_threat
+    );
+
+    // Never queries the public suffix itself
+    assert!(
+        !detector(5)
+            .check_domain("mail.fine.co.uk")
+            .await
+            .unwrap()
+            .is_threat
+    );
+}
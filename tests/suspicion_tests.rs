@@ -0,0 +1,52 @@
+use mailguard_rs::{
+    MailGuardConfig, OfflineMailGuard, SuspicionSignal, suspicion::score_local_part,
+    threat::LOW_CONFIDENCE,
+};
+
+#[test]
+fn test_readable_local_parts_score_low() {
+    for local in [
+        "jane.doe",
+        "support",
+        "christopher",
+        "j.smith+news",
+        "12345678",
+    ] {
+        let suspicion = score_local_part(local);
+        assert!(suspicion.score < 50, "{local}: {suspicion:?}");
+    }
+}
+
+#[test]
+fn test_generated_local_parts_score_high() {
+    let uuid = score_local_part("3F2504E0-4F89-11D3-9A0C-0305E82C3301");
+    assert_eq!(uuid.signals[0], SuspicionSignal::UuidLike);
+    assert!(uuid.score >= 60);
+
+    let random = score_local_part("x7kq9zt2mw");
+    assert!(random.signals.contains(&SuspicionSignal::HighEntropy));
+    assert!(random.signals.contains(&SuspicionSignal::DigitHeavy));
+
+    let walk = score_local_part("qwerty2024");
+    assert!(walk.signals.contains(&SuspicionSignal::KeyboardWalk));
+}
+
+#[test]
+fn test_status_reports_suspicion_when_enabled() {
+    let guard = OfflineMailGuard::new();
+    let status = guard.check_email("x7kq9zt2mw@example.com").unwrap();
+    assert_eq!(status.local_part_suspicion, None);
+
+    let guard = OfflineMailGuard::with_config(&MailGuardConfig {
+        score_local_parts: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let status = guard.check_email("x7kq9zt2mw@example.com").unwrap();
+    assert!(!status.is_threat);
+    assert!(status.local_part_suspicion.unwrap().score >= 50);
+    assert_eq!(status.confidence, LOW_CONFIDENCE);
+
+    let status = guard.check_email("jane.doe@example.com").unwrap();
+    assert_eq!(status.confidence, 0.0);
+}
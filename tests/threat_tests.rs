@@ -0,0 +1,110 @@
+use mailguard_rs::{MailGuardError, ThreatType};
+
+#[test]
+fn test_threat_types_round_trip_through_strings() {
+    for threat_type in [
+        ThreatType::Spam,
+        ThreatType::Phishing,
+        ThreatType::Malware,
+        ThreatType::Botnet,
+        ThreatType::Cracked,
+        ThreatType::Redirector,
+        ThreatType::AbusedLegit,
+        ThreatType::Pup,
+        ThreatType::Disposable,
+        ThreatType::Unknown(200),
+    ] {
+        let id = threat_type.to_string();
+        assert_eq!(id.parse::<ThreatType>().unwrap(), threat_type);
+    }
+
+    assert_eq!(ThreatType::Phishing.to_string(), "phishing");
+    assert_eq!(ThreatType::Unknown(200).to_string(), "unknown:200");
+    assert_eq!(ThreatType::Unknown(200).as_str(), "unknown");
+    assert_eq!("Spam".parse::<ThreatType>().unwrap(), ThreatType::Spam);
+}
+
+#[test]
+fn test_unknown_identifiers_are_rejected() {
+    for id in ["", "scam", "unknown", "unknown:300", "spam "] {
+        assert!(matches!(
+            id.parse::<ThreatType>(),
+            Err(MailGuardError::InvalidThreatType(_))
+        ));
+    }
+}
+
+#[test]
+fn test_taxonomy_identifiers() {
+    assert_eq!(ThreatType::Phishing.misp_tag(), r#"rsit:fraud="phishing""#);
+    assert_eq!(
+        ThreatType::Botnet.stix_indicator_type(),
+        "malicious-activity"
+    );
+    assert_eq!(
+        ThreatType::Disposable.stix_indicator_type(),
+        "anonymization"
+    );
+    assert_eq!(
+        ThreatType::Unknown(200).misp_tag(),
+        r#"rsit:other="undetermined""#
+    );
+
+    let taxonomy = serde_json::to_value(ThreatType::Spam.taxonomy()).unwrap();
+    assert_eq!(
+        taxonomy,
+        serde_json::json!({
+            "misp_tag": "rsit:abusive-content=\"spam\"",
+            "stix_indicator_type": "anomalous-activity",
+        })
+    );
+}
+
+#[test]
+fn test_documented_return_codes_have_their_own_categories() {
+    for (octet, threat_type) in [
+        (2, ThreatType::Spam),
+        (3, ThreatType::Phishing),
+        (4, ThreatType::Malware),
+        (5, ThreatType::Botnet),
+        (6, ThreatType::Cracked),
+        (7, ThreatType::Redirector),
+        (9, ThreatType::AbusedLegit),
+        (10, ThreatType::Pup),
+        (11, ThreatType::Malware),
+        (8, ThreatType::Unknown(8)),
+    ] {
+        assert_eq!(ThreatType::from_ip_last_octet(octet), threat_type);
+    }
+    assert_eq!(ThreatType::AbusedLegit.to_string(), "abused_legit");
+}
+
+#[test]
+fn test_serializes_as_identifier() {
+    assert_eq!(
+        serde_json::to_string(&ThreatType::AbusedLegit).unwrap(),
+        r#""abused_legit""#
+    );
+    assert_eq!(
+        serde_json::to_string(&ThreatType::Unknown(200)).unwrap(),
+        r#""unknown:200""#
+    );
+    assert_eq!(
+        serde_json::from_str::<ThreatType>(r#""phishing""#).unwrap(),
+        ThreatType::Phishing
+    );
+    assert!(serde_json::from_str::<ThreatType>(r#""scam""#).is_err());
+}
+
+#[test]
+fn test_deserializes_earlier_representation() {
+    assert_eq!(
+        serde_json::from_str::<ThreatType>(r#""Spam""#).unwrap(),
+        ThreatType::Spam
+    );
+    assert_eq!(
+        serde_json::from_str::<ThreatType>(r#"{"Unknown":200}"#).unwrap(),
+        ThreatType::Unknown(200)
+    );
+    assert!(serde_json::from_str::<ThreatType>(r#"{"Spam":2}"#).is_err());
+}
@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use mailguard_rs::{
+    CacheBackend, CachedVerdict, Fixture, FixtureAnswer, MailGuard, ThreatType, TieredCache,
+    check::async_trait, dns::DnsClient, error::Result,
+};
+
+/// Stands in for Redis, counting the lookups that reach it
+#[derive(Clone, Default)]
+struct Remote {
+    entries: Arc<Mutex<HashMap<String, CachedVerdict>>>,
+    gets: Arc<AtomicU32>,
+}
+
+#[async_trait]
+impl CacheBackend for Remote {
+    async fn get(&self, key: &str) -> Result<Option<CachedVerdict>> {
+        self.gets.fetch_add(1, Ordering::SeqCst);
+        Ok(self.entries.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: CachedVerdict, _ttl: Duration) -> Result<()> {
+        self.entries.lock().unwrap().insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        self.entries.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(self.entries.lock().unwrap().len())
+    }
+}
+
+#[tokio::test]
+async fn test_writes_go_through_to_the_backend() {
+    let mut fixture = Fixture::new();
+    fixture.insert(
+        "listed.test.tempmail.so.multi.surbl.org",
+        FixtureAnswer::Records {
+            ips: vec![IpAddr::from([127, 0, 0, 2])],
+            ttl_secs: 300,
+        },
+    );
+    let remote = Remote::default();
+    let detector = MailGuard::builder()
+        .dns_client(DnsClient::playback(fixture))
+        .cache_backend(TieredCache::new(
+            remote.clone(),
+            100,
+            Duration::from_secs(60),
+        ))
+        .build()
+        .unwrap();
+
+    detector.check_domain("listed.test").await.unwrap();
+    assert_eq!(
+        remote.entries.lock().unwrap()["surbl:listed.test"].threat_type(),
+        Some(&ThreatType::Spam)
+    );
+    assert_eq!(detector.cache_stats(), Some(1));
+
+    let status = detector.check_domain("listed.test").await.unwrap();
+    assert!(status.from_cache);
+    assert_eq!(status.threat_type, Some(ThreatType::Spam));
+
+    detector.invalidate("listed.test").await.unwrap();
+    assert!(remote.entries.lock().unwrap().is_empty());
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn test_hot_domains_are_answered_from_memory() {
+    let remote = Remote::default();
+    remote
+        .entries
+        .lock()
+        .unwrap()
+        .insert("known.test".to_string(), Some(ThreatType::Phishing).into());
+    let tiered = TieredCache::new(remote.clone(), 100, Duration::from_secs(60));
+
+    // The first lookup reaches the backend and warms memory
+    assert_eq!(
+        tiered.get("known.test").await.unwrap(),
+        Some(Some(ThreatType::Phishing).into())
+    );
+    assert_eq!(
+        tiered.get("known.test").await.unwrap(),
+        Some(Some(ThreatType::Phishing).into())
+    );
+    assert_eq!(remote.gets.load(Ordering::SeqCst), 1);
+
+    // Writes land in memory too
+    tiered
+        .set(
+            "fresh.test",
+            CachedVerdict::pass(),
+            Duration::from_secs(300),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        tiered.get("fresh.test").await.unwrap(),
+        Some(CachedVerdict::pass())
+    );
+    assert_eq!(remote.gets.load(Ordering::SeqCst), 1);
+
+    // Clearing empties both tiers
+    tiered.clear();
+    assert_eq!(tiered.get("known.test").await.unwrap(), None);
+    assert_eq!(remote.gets.load(Ordering::SeqCst), 2);
+    assert_eq!(tiered.memory_usage(), Some(0));
+}
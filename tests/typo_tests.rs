@@ -0,0 +1,64 @@
+use mailguard_rs::{Fixture, MailGuard, MailGuardConfig, dns::DnsClient, typo::likely_typo_of};
+
+#[test]
+fn test_single_edit_typos() {
+    assert_eq!(likely_typo_of("gmial.com"), Some("gmail.com"));
+    assert_eq!(likely_typo_of("gmail.con"), Some("gmail.com"));
+    assert_eq!(likely_typo_of("hotmal.com"), Some("hotmail.com"));
+    assert_eq!(likely_typo_of("yahooo.com"), Some("yahoo.com"));
+}
+
+#[test]
+fn test_homoglyph_typos() {
+    assert_eq!(likely_typo_of("outl00k.com"), Some("outlook.com"));
+    assert_eq!(likely_typo_of("hotrnail.com"), Some("hotmail.com"));
+}
+
+#[test]
+fn test_non_typos() {
+    assert_eq!(likely_typo_of("gmail.com"), None);
+    assert_eq!(likely_typo_of("GMAIL.COM"), None);
+    assert_eq!(likely_typo_of("example.com"), None);
+    assert_eq!(likely_typo_of("mycompany.org"), None);
+}
+
+#[test]
+fn test_providers_are_not_typos() {
+    // Each is one edit away from gmail.com
+    assert_eq!(likely_typo_of("ymail.com"), None);
+    assert_eq!(likely_typo_of("mail.com"), None);
+    assert_eq!(likely_typo_of("email.com"), None);
+}
+
+#[tokio::test]
+async fn test_configured_providers_are_not_typos() {
+    let config = MailGuardConfig {
+        detect_typos: true,
+        trusted_providers: ["gmaill.com".to_string()].into_iter().collect(),
+        ..Default::default()
+    };
+    let detector = MailGuard::with_dns_client(config, DnsClient::playback(Fixture::new()));
+    let status = detector.check_email("user@gmaill.com").await.unwrap();
+    assert_eq!(status.typo_of, None);
+    let status = detector.check_email("user@gmial.com").await.unwrap();
+    assert_eq!(status.typo_of.as_deref(), Some("gmail.com"));
+}
+
+#[tokio::test]
+async fn test_typo_detection_is_opt_in() {
+    let detector = MailGuard::with_dns_client(
+        MailGuardConfig::default(),
+        DnsClient::playback(Fixture::new()),
+    );
+    let status = detector.check_email("user@gmial.com").await.unwrap();
+    assert_eq!(status.typo_of, None);
+
+    let config = MailGuardConfig {
+        detect_typos: true,
+        ..Default::default()
+    };
+    let detector = MailGuard::with_dns_client(config, DnsClient::playback(Fixture::new()));
+    let status = detector.check_email("user@gmial.com").await.unwrap();
+    assert_eq!(status.typo_of.as_deref(), Some("gmail.com"));
+    assert!(!status.is_threat);
+}
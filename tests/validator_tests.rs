@@ -0,0 +1,71 @@
+use mailguard_rs::{
+    EmailAddress, EmailValidator, MailGuardConfig, MailGuardError, OfflineMailGuard,
+};
+
+fn no_quote_or_slash() -> EmailValidator {
+    EmailValidator::function(|email| {
+        let local = email.rsplit_once('@').map_or(email, |(local, _)| local);
+        if local.contains(['\'', '/']) {
+            return Err("local part may not contain ' or /".to_string());
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn test_regex_validator_replaces_builtin_rules() {
+    let validator = EmailValidator::regex(r"[a-z0-9.]+@[a-z0-9.-]+").unwrap();
+
+    // Valid RFC 5322, but not under the stricter pattern
+    assert!(EmailAddress::parse("o'brien@example.com").is_ok());
+    assert!(matches!(
+        EmailAddress::parse_with("o'brien@example.com", &validator),
+        Err(MailGuardError::InvalidEmail { .. })
+    ));
+
+    let address = EmailAddress::parse_with(" jane.doe@example.com ", &validator).unwrap();
+    assert_eq!(address.local_part.as_str(), "jane.doe");
+    assert_eq!(address.domain.as_str(), "example.com");
+
+    // The domain is still validated
+    assert!(EmailAddress::parse_with("jane@example..com", &validator).is_err());
+
+    assert!(matches!(
+        EmailValidator::regex("(unclosed"),
+        Err(MailGuardError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn test_function_validator_reports_reason() {
+    let err = EmailAddress::parse_with("a/b@example.com", &no_quote_or_slash()).unwrap_err();
+    assert!(err.to_string().contains("may not contain"));
+}
+
+#[test]
+fn test_detector_uses_configured_validator() {
+    let config = MailGuardConfig {
+        email_validator: Some(no_quote_or_slash()),
+        ..Default::default()
+    };
+    let guard = OfflineMailGuard::with_config(&config).unwrap();
+
+    assert!(matches!(
+        guard.check_email("o'brien@example.com"),
+        Err(MailGuardError::InvalidEmail { .. })
+    ));
+    assert!(guard.check_email("obrien@example.com").is_ok());
+}
+
+#[tokio::test]
+async fn test_async_detector_uses_configured_validator() {
+    let detector = mailguard_rs::MailGuard::with_config(MailGuardConfig {
+        email_validator: Some(no_quote_or_slash()),
+        ..Default::default()
+    });
+
+    assert!(matches!(
+        detector.check_email("o'brien@example.com").await,
+        Err(MailGuardError::InvalidEmail { .. })
+    ));
+}